@@ -1,6 +1,8 @@
 use std::path::PathBuf;
 
 fn main() {
+    embed_iwad();
+
     println!("cargo:rerun-if-changed=include/dg_bridge.h");
     println!("cargo:rerun-if-changed=csrc/doomgeneric");
     println!("cargo:rerun-if-changed=csrc/platform");
@@ -40,12 +42,54 @@ fn main() {
     // Rust-host platform shim:
     build.file("csrc/platform/doomgeneric_rust.c");
     build.file("csrc/platform/dg_host_bridge.c");
+    // WAD I/O served from Rust (memory, archives, embedded IWAD)
+    build.file("csrc/platform/w_file_host.c");
+    // DeHackEd tables and string replacements, fed by the Rust parser
+    build.file("csrc/platform/deh_host.c");
+    // In-memory level snapshots for rewind
+    build.file("csrc/platform/snapshot.c");
+    // Per-tic game state checksums for demo desync checks
+    build.file("csrc/platform/state_hash.c");
+    // Game events (DG_Event) forwarded to a Rust hook
+    build.file("csrc/platform/events.c");
+    // Player input supplied as ticcmds instead of key presses
+    build.file("csrc/platform/ticcmd.c");
+    // Player, level and map object state copied for the host after each tic
+    build.file("csrc/platform/game_state.c");
     // Backend stubs: no-op audio/joystick to satisfy I_* symbols
     build.file("csrc/platform/i_stubs.c");
 
     // C flags
     build.flag_if_supported("-std=c11");
+    // Strict C11 hides POSIX declarations such as strdup; undeclared, their
+    // pointer results get truncated to int. Config strings depend on it now.
+    build.define("_DEFAULT_SOURCE", None);
     build.warnings(false);
 
     build.compile("doomgeneric");
 }
+
+// With the `embed-iwad` feature, bake the WAD named by RUSTYDOOM_EMBED_IWAD
+// into the binary (see src/vfs.rs) for single-file distributions.
+fn embed_iwad() {
+    if std::env::var_os("CARGO_FEATURE_EMBED_IWAD").is_none() {
+        return;
+    }
+    println!("cargo:rerun-if-env-changed=RUSTYDOOM_EMBED_IWAD");
+    let iwad = std::env::var("RUSTYDOOM_EMBED_IWAD")
+        .expect("the embed-iwad feature needs RUSTYDOOM_EMBED_IWAD=/path/to/iwad.wad");
+    let iwad = std::fs::canonicalize(&iwad)
+        .unwrap_or_else(|e| panic!("RUSTYDOOM_EMBED_IWAD={}: {}", iwad, e));
+    println!("cargo:rerun-if-changed={}", iwad.display());
+
+    let name = iwad.file_name().unwrap().to_string_lossy().to_lowercase();
+    let out = PathBuf::from(std::env::var("OUT_DIR").unwrap()).join("embedded_iwad.rs");
+    std::fs::write(
+        out,
+        format!(
+            "pub const NAME: &str = {:?};\npub static DATA: &[u8] = include_bytes!({:?});\n",
+            name, iwad
+        ),
+    )
+    .unwrap();
+}
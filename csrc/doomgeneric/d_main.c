@@ -1164,7 +1164,9 @@ void D_DoomMain (void)
 {
     int p;
     char file[256];
-    char demolumpname[9];
+    // Static: G_DeferedPlayDemo keeps the pointer, and with doomgeneric
+    // this function returns before the demo starts.
+    static char demolumpname[9];
 #if ORIGCODE
     int numiwadlumps;
 #endif
@@ -1355,7 +1357,7 @@ void D_DoomMain (void)
 
     // Load configuration files before initialising other subsystems.
     DEH_printf("M_LoadDefaults: Load system defaults.\n");
-    M_SetConfigFilenames("default.cfg", PROGRAM_PREFIX "doom.cfg");
+    M_SetConfigFilenames("default.cfg", "extended.cfg");
     D_BindVariables();
     M_LoadDefaults();
 
@@ -20,12 +20,16 @@
 #define DOOM_FEATURES_H
 
 // Enables wad merging (the '-merge' command line parameter)
+// RustyDoom merges on the host instead (src/wad/merge.rs, --merge and
+// --nwtmerge) and hands the engine the merged IWAD, so this stays off.
 
 #undef FEATURE_WAD_MERGE
 
 // Enables dehacked support ('-deh')
+// Patches are parsed on the host (src/deh.rs) and applied through
+// csrc/platform/deh_host.c, which also provides DEH_String and friends.
 
-#undef FEATURE_DEHACKED
+#define FEATURE_DEHACKED
 
 // Enables multiplayer support (network games)
 
@@ -4,6 +4,8 @@
 #include <stdlib.h>
 #include <stdint.h>
 
+#include "d_ticcmd.h"
+
 #ifndef DOOMGENERIC_RESX
 #define DOOMGENERIC_RESX 640
 #endif  // DOOMGENERIC_RESX
@@ -42,6 +44,36 @@ uint32_t DG_GetTicksMs();
 int DG_GetKey(int* pressed, unsigned char* key);
 void DG_SetWindowTitle(const char * title);
 
+// Game events for the host, raised from inside the tic they happen in.
+// What player, a, b, c and name carry depends on the kind.
+enum
+{
+    DG_EV_LEVEL_START = 1,  // a = episode, b = map, c = skill
+    DG_EV_LEVEL_FINISH,     // a = episode, b = map, c = leveltime
+    DG_EV_DAMAGE,           // a = damage taken, b = health left, name = source sprite
+    DG_EV_DEATH,            // name = killer sprite
+    DG_EV_PICKUP,           // a = mobj type, name = sprite
+    DG_EV_KILL,             // player = killer or -1, a = mobj type, name = sprite
+    DG_EV_SECRET,           // a = sector number
+    DG_EV_WEAPON,           // a = weapon (weapontype_t) being raised
+};
+
+void DG_Event(int kind, int player, int a, int b, int c, const char *name);
+
+// While muted, DG_Event reports nothing: the platform mutes it around
+// reloading a level to jump within it (snapshot restore, dg_new_game), which
+// would otherwise report a level start and weapon raise that never happened.
+void DG_MuteEvents(int mute);
+
+// Input the host supplies as ticcmd fields; G_BuildTiccmd calls this last,
+// so when the host has one for `tic` it replaces what the controls produced.
+void DG_HostTiccmd(ticcmd_t *cmd, int tic);
+
+// The game has moved on: dg_game_state and dg_mobjs copy it afresh the next
+// time they're asked. The platform calls it after every tic, and after
+// jumping between tics (new game, snapshot restore).
+void DG_StateChanged(void);
+
 #ifdef __cplusplus
 }
 #endif
@@ -195,7 +195,13 @@ extern  boolean	demorecording;
 extern boolean lowres_turn;
 
 // Quit after playing a demo from cmdline.
-extern  boolean		singledemo;	
+extern  boolean		singledemo;
+
+// Set once a -record, -playdemo or -timedemo run is over; the host quits.
+extern  boolean		demofinished;
+extern  int		timedemo_gametics;
+extern  int		timedemo_realtics;	
+extern  int		demotics;	// tics played back or recorded so far
 
 
 
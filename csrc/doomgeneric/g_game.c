@@ -68,7 +68,7 @@
 #include "r_data.h"
 #include "r_sky.h"
 
-
+#include "doomgeneric.h"
 
 #include "g_game.h"
 
@@ -137,6 +137,10 @@ byte*		demobuffer;
 byte*		demo_p;
 byte*		demoend; 
 boolean         singledemo;            	// quit after playing a demo from cmdline 
+boolean         demofinished;           // a -record/-playdemo/-timedemo run is over
+int             timedemo_gametics;      // -timedemo result, once demofinished
+int             timedemo_realtics;
+int             demotics;               // tics played back or recorded so far
  
 boolean         precache = true;        // if true, load all graphics at start 
 
@@ -561,6 +565,9 @@ void G_BuildTiccmd (ticcmd_t* cmd, int maketic)
  
     cmd->forwardmove += forward; 
     cmd->sidemove += side;
+
+    // Host-supplied input (dg_set_ticcmd, dg_set_ticcmd_hook) wins over the controls.
+    DG_HostTiccmd(cmd, maketic);
     
     // special buttons
     if (sendpause) 
@@ -653,6 +660,7 @@ void G_DoLoadLevel (void)
     } 
 		 
     P_SetupLevel (gameepisode, gamemap, 0, gameskill);    
+    DG_Event(DG_EV_LEVEL_START, consoleplayer, gameepisode, gamemap, gameskill, NULL);
     displayplayer = consoleplayer;		// view the guy you are playing    
     gameaction = ga_nothing; 
     Z_CheckHeap ();
@@ -958,6 +966,9 @@ void G_Ticker (void)
 	    } 
 	}
     }
+
+    if (demoplayback || demorecording)
+        demotics++;
     
     // check for special buttons
     for (i=0 ; i<MAXPLAYERS ; i++)
@@ -1483,6 +1494,7 @@ void G_DoCompleted (void)
     automapactive = false; 
 
     StatCopy(&wminfo);
+    DG_Event(DG_EV_LEVEL_FINISH, consoleplayer, gameepisode, gamemap, leveltime, NULL);
  
     WI_Start (&wminfo); 
 } 
@@ -2072,6 +2084,7 @@ void G_BeginRecording (void)
     lowres_turn = !longtics;
     
     demo_p = demobuffer;
+    demotics = 0;
 	
     // Save the right version code for this demo
  
@@ -2159,6 +2172,7 @@ void G_DoPlayDemo (void)
     demobuffer = demo_p = W_CacheLumpName (defdemoname, PU_STATIC); 
 
     demoversion = *demo_p++;
+    demotics = 0;
 
     if (demoversion == G_VanillaVersionCode())
     {
@@ -2251,19 +2265,21 @@ boolean G_CheckDemoStatus (void)
 	 
     if (timingdemo) 
     { 
-        float fps;
         int realtics;
 
 	endtime = I_GetTime (); 
         realtics = endtime - starttime;
-        fps = ((float) gametic * TICRATE) / realtics;
 
         // Prevent recursive calls
         timingdemo = false;
         demoplayback = false;
 
-	I_Error ("timed %i gametics in %i realtics (%f fps)",
-                 gametic, realtics, fps);
+        // Instead of the I_Error report: the host prints the result
+        // (and fps) and quits.
+        timedemo_gametics = gametic;
+        timedemo_realtics = realtics;
+        demofinished = true;
+        return true;
     } 
 	 
     if (demoplayback) 
@@ -2279,8 +2295,10 @@ boolean G_CheckDemoStatus (void)
 	nomonsters = false;
 	consoleplayer = 0;
         
+        // The host quits once it sees demofinished, rather than
+        // I_Quit exiting from inside a tic.
         if (singledemo) 
-            I_Quit (); 
+            demofinished = true;
         else 
             D_AdvanceDemo (); 
 
@@ -2293,7 +2311,8 @@ boolean G_CheckDemoStatus (void)
 	M_WriteFile (demoname, demobuffer, demo_p - demobuffer); 
 	Z_Free (demobuffer); 
 	demorecording = false; 
-	I_Error ("Demo %s recorded",demoname); 
+	printf ("Demo %s recorded\n",demoname); 
+	demofinished = true;
     } 
 	 
     return false; 
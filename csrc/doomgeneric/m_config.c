@@ -1606,11 +1606,84 @@ static const int scantokey[128] =
 };
 
 
+// Format a variable's value the way it is written to the configuration
+// file, without the quotes around strings.
+
+static void FormatValue(default_t *def, char *buf, size_t buf_len)
+{
+    int v;
+
+    switch (def->type) 
+    {
+        case DEFAULT_KEY:
+
+            // use the untranslated version if we can, to reduce
+            // the possibility of screwing up the user's config
+            // file
+            
+            v = * (int *) def->location;
+
+            if (v == KEY_RSHIFT)
+            {
+                // Special case: for shift, force scan code for
+                // right shift, as this is what Vanilla uses.
+                // This overrides the change check below, to fix
+                // configuration files made by old versions that
+                // mistakenly used the scan code for left shift.
+
+                v = 54;
+            }
+            else if (def->untranslated
+                  && v == def->original_translated)
+            {
+                // Has not been changed since the last time we
+                // read the config file.
+
+                v = def->untranslated;
+            }
+            else
+            {
+                // search for a reverse mapping back to a scancode
+                // in the scantokey table
+
+                int s;
+
+                for (s=0; s<128; ++s)
+                {
+                    if (scantokey[s] == v)
+                    {
+                        v = s;
+                        break;
+                    }
+                }
+            }
+
+            M_snprintf(buf, buf_len, "%i", v);
+            break;
+
+        case DEFAULT_INT:
+            M_snprintf(buf, buf_len, "%i", * (int *) def->location);
+            break;
+
+        case DEFAULT_INT_HEX:
+            M_snprintf(buf, buf_len, "0x%x", * (int *) def->location);
+            break;
+
+        case DEFAULT_FLOAT:
+            M_snprintf(buf, buf_len, "%f", * (float *) def->location);
+            break;
+
+        case DEFAULT_STRING:
+            M_snprintf(buf, buf_len, "%s", * (char **) def->location);
+            break;
+    }
+}
+
 static void SaveDefaultCollection(default_collection_t *collection)
 {
-#if ORIGCODE
     default_t *defaults;
-    int i, v;
+    char value[100];
+    int i;
     FILE *f;
 	
     f = fopen (collection->filename, "w");
@@ -1639,76 +1712,21 @@ static void SaveDefaultCollection(default_collection_t *collection)
 
         // Print the value
 
-        switch (defaults[i].type) 
-        {
-            case DEFAULT_KEY:
-
-                // use the untranslated version if we can, to reduce
-                // the possibility of screwing up the user's config
-                // file
-                
-                v = * (int *) defaults[i].location;
-
-                if (v == KEY_RSHIFT)
-                {
-                    // Special case: for shift, force scan code for
-                    // right shift, as this is what Vanilla uses.
-                    // This overrides the change check below, to fix
-                    // configuration files made by old versions that
-                    // mistakenly used the scan code for left shift.
-
-                    v = 54;
-                }
-                else if (defaults[i].untranslated
-                      && v == defaults[i].original_translated)
-                {
-                    // Has not been changed since the last time we
-                    // read the config file.
-
-                    v = defaults[i].untranslated;
-                }
-                else
-                {
-                    // search for a reverse mapping back to a scancode
-                    // in the scantokey table
-
-                    int s;
-
-                    for (s=0; s<128; ++s)
-                    {
-                        if (scantokey[s] == v)
-                        {
-                            v = s;
-                            break;
-                        }
-                    }
-                }
-
-	        fprintf(f, "%i", v);
-                break;
-
-            case DEFAULT_INT:
-	        fprintf(f, "%i", * (int *) defaults[i].location);
-                break;
+        FormatValue(&defaults[i], value, sizeof(value));
 
-            case DEFAULT_INT_HEX:
-	        fprintf(f, "0x%x", * (int *) defaults[i].location);
-                break;
-
-            case DEFAULT_FLOAT:
-                fprintf(f, "%f", * (float *) defaults[i].location);
-                break;
-
-            case DEFAULT_STRING:
-	        fprintf(f,"\"%s\"", * (char **) (defaults[i].location));
-                break;
+        if (defaults[i].type == DEFAULT_STRING)
+        {
+            fprintf(f, "\"%s\"", value);
+        }
+        else
+        {
+            fprintf(f, "%s", value);
         }
 
         fprintf(f, "\n");
     }
 
     fclose (f);
-#endif
 }
 
 // Parses integer values in the configuration file
@@ -1770,7 +1788,6 @@ static void SetVariable(default_t *def, char *value)
 
 static void LoadDefaultCollection(default_collection_t *collection)
 {
-#if ORIGCODE
     FILE *f;
     default_t *def;
     char defname[80];
@@ -1828,7 +1845,6 @@ static void LoadDefaultCollection(default_collection_t *collection)
     }
 
     fclose (f);
-#endif
 }
 
 // Set the default filenames to use for configuration files.
@@ -1905,8 +1921,6 @@ void M_LoadDefaults (void)
             = M_StringJoin(configdir, default_main_config, NULL);
     }
 
-    printf("saving config in %s\n", doom_defaults.filename);
-
     //!
     // @arg <file>
     //
@@ -1990,6 +2004,53 @@ boolean M_SetVariable(char *name, char *value)
     return true;
 }
 
+// Name of the i-th bound variable, for listing them all; NULL past the
+// end.
+
+char *M_GetVariableName(int i)
+{
+    default_collection_t *collections[] = { &doom_defaults, &extra_defaults };
+    int c, n;
+
+    for (c=0; c<2; ++c)
+    {
+        for (n=0; n<collections[c]->numdefaults; ++n)
+        {
+            if (collections[c]->defaults[n].bound && i-- == 0)
+            {
+                return collections[c]->defaults[n].name;
+            }
+        }
+    }
+
+    return NULL;
+}
+
+// Format a variable's value as it appears in the configuration file.
+// Unlike the other accessors, an unknown name is not an error: it returns
+// false, so the host can check names it got from the user.
+
+boolean M_FormatVariable(char *name, char *buf, size_t buf_len)
+{
+    default_t *variable;
+
+    variable = SearchCollection(&doom_defaults, name);
+
+    if (variable == NULL)
+    {
+        variable = SearchCollection(&extra_defaults, name);
+    }
+
+    if (variable == NULL || !variable->bound)
+    {
+        return false;
+    }
+
+    FormatValue(variable, buf, buf_len);
+
+    return true;
+}
+
 // Get the value of a variable.
 
 int M_GetIntVariable(char *name)
@@ -2042,11 +2103,23 @@ float M_GetFloatVariable(char *name)
 
 static char *GetDefaultConfigDir(void)
 {
-    char *result = (char *)malloc(2);
-    result[0] = '.';
-    result[1] = '\0';
+    int p;
 
-    return result;
+    //!
+    // @arg <directory>
+    //
+    // Read and write default.cfg and extended.cfg in the given directory
+    // rather than the current one. The host passes its own config
+    // directory here.
+    //
+
+    p = M_CheckParmWithArgs("-configdir", 1);
+    if (p)
+    {
+        return M_StringJoin(myargv[p + 1], DIR_SEPARATOR_S, NULL);
+    }
+
+    return M_StringJoin(".", DIR_SEPARATOR_S, NULL);
 }
 
 // 
@@ -2087,14 +2160,35 @@ void M_SetConfigDir(char *dir)
 char *M_GetSaveGameDir(char *iwadname)
 {
     char *savegamedir;
+    int p;
 #if ORIGCODE
     char *topdir;
 #endif
 
+    //!
+    // @arg <directory>
+    //
+    // Specify a path from which to load and save games. If the directory
+    // does not exist then it will automatically be created.
+    //
+
+    p = M_CheckParmWithArgs("-savedir", 1);
+    if (p)
+    {
+        savegamedir = myargv[p + 1];
+        if (!M_FileExists(savegamedir))
+        {
+            M_MakeDirectory(savegamedir);
+        }
+
+        // add separator at end just in case
+        savegamedir = M_StringJoin(savegamedir, DIR_SEPARATOR_S, NULL);
+
+        printf("Save directory changed to %s.\n", savegamedir);
+    }
     // If not "doing" a configuration directory (Windows), don't "do"
     // a savegame directory, either.
-
-    if (!strcmp(configdir, ""))
+    else if (!strcmp(configdir, ""))
     {
     	savegamedir = strdup("");
     }
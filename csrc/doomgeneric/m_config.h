@@ -31,6 +31,8 @@ boolean M_SetVariable(char *name, char *value);
 int M_GetIntVariable(char *name);
 const char *M_GetStrVariable(char *name);
 float M_GetFloatVariable(char *name);
+char *M_GetVariableName(int i);
+boolean M_FormatVariable(char *name, char *buf, size_t buf_len);
 void M_SetConfigFilenames(char *main_config, char *extra_config);
 char *M_GetSaveGameDir(char *iwadname);
 
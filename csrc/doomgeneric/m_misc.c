@@ -63,10 +63,19 @@ void M_MakeDirectory(char *path)
 
 // Check if a file exists
 
+int W_HostFileExists(char *path);
+
 boolean M_FileExists(char *filename)
 {
     FILE *fstream;
 
+    // Virtual files served by the Rust host
+
+    if (W_HostFileExists(filename))
+    {
+        return true;
+    }
+
     fstream = fopen(filename, "r");
 
     if (fstream != NULL)
@@ -46,6 +46,10 @@ static const unsigned char rndtable[256] = {
 int	rndindex = 0;
 int	prndindex = 0;
 
+// Where M_ClearRandom starts both indices; 0 as in vanilla unless the host
+// picks another place in the table (dg_new_game's seed).
+int	rndseed = 0;
+
 // Which one is deterministic?
 int P_Random (void)
 {
@@ -61,5 +65,5 @@ int M_Random (void)
 
 void M_ClearRandom (void)
 {
-    rndindex = prndindex = 0;
+    rndindex = prndindex = rndseed & 0xff;
 }
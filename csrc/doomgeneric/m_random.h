@@ -35,5 +35,8 @@ int P_Random (void);
 // Fix randoms for demos.
 void M_ClearRandom (void);
 
+// Index M_ClearRandom resets to (0-255).
+extern int rndseed;
+
 
 #endif
@@ -39,6 +39,8 @@
 
 #include "p_inter.h"
 
+#include "doomgeneric.h"
+
 
 #define BONUSADD	6
 
@@ -652,6 +654,8 @@ P_TouchSpecialThing
 	
     if (special->flags & MF_COUNTITEM)
 	player->itemcount++;
+    DG_Event(DG_EV_PICKUP, player - players, special->type, 0, 0,
+             sprnames[special->sprite]);
     P_RemoveMobj (special);
     player->bonuscount += BONUSADD;
     if (player == &players[consoleplayer])
@@ -693,6 +697,12 @@ P_KillMobj
 	// even those caused by other monsters
 	players[0].killcount++;
     }
+
+    if (target->flags & MF_COUNTKILL)
+    {
+        DG_Event(DG_EV_KILL, source && source->player ? source->player - players : -1,
+                 target->type, 0, 0, sprnames[target->sprite]);
+    }
     
     if (target->player)
     {
@@ -702,6 +712,8 @@ P_KillMobj
 			
 	target->flags &= ~MF_SOLID;
 	target->player->playerstate = PST_DEAD;
+	DG_Event(DG_EV_DEATH, target->player - players, 0, 0, 0,
+	         source ? sprnames[source->sprite] : NULL);
 	P_DropWeapon (target->player);
 
 	if (target->player == &players[consoleplayer]
@@ -885,6 +897,9 @@ P_DamageMobj
 
 	if (player == &players[consoleplayer])
 	    I_Tactile (40,10,40+temp*2);
+
+	DG_Event(DG_EV_DAMAGE, player - players, damage, player->health, 0,
+	         source ? sprnames[source->sprite] : NULL);
     }
     
     // do the damage	
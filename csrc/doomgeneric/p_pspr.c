@@ -35,6 +35,8 @@
 
 #include "p_pspr.h"
 
+#include "doomgeneric.h"
+
 #define LOWERSPEED		FRACUNIT*6
 #define RAISESPEED		FRACUNIT*6
 
@@ -137,6 +139,7 @@ void P_BringUpWeapon (player_t* player)
 	S_StartSound (player->mo, sfx_sawup);
 		
     newstate = weaponinfo[player->pendingweapon].upstate;
+    DG_Event(DG_EV_WEAPON, player - players, player->pendingweapon, 0, 0, NULL);
 
     player->pendingweapon = wp_nochange;
     player->psprites[ps_weapon].sy = WEAPONBOTTOM;
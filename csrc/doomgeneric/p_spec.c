@@ -47,6 +47,8 @@
 // Data.
 #include "sounds.h"
 
+#include "doomgeneric.h"
+
 
 //
 // Animating textures and planes
@@ -1059,6 +1061,7 @@ void P_PlayerInSpecialSector (player_t* player)
 	// SECRET SECTOR
 	player->secretcount++;
 	sector->special = 0;
+	DG_Event(DG_EV_SECRET, player - players, sector - sectors, 0, 0, NULL);
 	break;
 			
       case 11:
@@ -376,6 +376,11 @@ static boolean	oldweaponsowned[NUMWEAPONS];
  // count until face changes
 static int	st_facecount = 0;
 
+// how long fire has been held, and what the face is showing; file-level
+// rather than in ST_updateFaceWidget so ST_initData can reset them
+static int	st_lastattackdown = -1;
+static int	st_facepriority = 0;
+
 // current face index, used by w_faces
 static int	st_faceindex = 0;
 
@@ -690,22 +695,20 @@ void ST_updateFaceWidget(void)
     int		i;
     angle_t	badguyangle;
     angle_t	diffang;
-    static int	lastattackdown = -1;
-    static int	priority = 0;
     boolean	doevilgrin;
 
-    if (priority < 10)
+    if (st_facepriority < 10)
     {
 	// dead
 	if (!plyr->health)
 	{
-	    priority = 9;
+	    st_facepriority = 9;
 	    st_faceindex = ST_DEADFACE;
 	    st_facecount = 1;
 	}
     }
 
-    if (priority < 9)
+    if (st_facepriority < 9)
     {
 	if (plyr->bonuscount)
 	{
@@ -723,7 +726,7 @@ void ST_updateFaceWidget(void)
 	    if (doevilgrin) 
 	    {
 		// evil grin if just picked up weapon
-		priority = 8;
+		st_facepriority = 8;
 		st_facecount = ST_EVILGRINCOUNT;
 		st_faceindex = ST_calcPainOffset() + ST_EVILGRINOFFSET;
 	    }
@@ -731,14 +734,14 @@ void ST_updateFaceWidget(void)
 
     }
   
-    if (priority < 8)
+    if (st_facepriority < 8)
     {
 	if (plyr->damagecount
 	    && plyr->attacker
 	    && plyr->attacker != plyr->mo)
 	{
 	    // being attacked
-	    priority = 7;
+	    st_facepriority = 7;
 	    
 	    if (plyr->health - st_oldhealth > ST_MUCHPAIN)
 	    {
@@ -788,20 +791,20 @@ void ST_updateFaceWidget(void)
 	}
     }
   
-    if (priority < 7)
+    if (st_facepriority < 7)
     {
 	// getting hurt because of your own damn stupidity
 	if (plyr->damagecount)
 	{
 	    if (plyr->health - st_oldhealth > ST_MUCHPAIN)
 	    {
-		priority = 7;
+		st_facepriority = 7;
 		st_facecount = ST_TURNCOUNT;
 		st_faceindex = ST_calcPainOffset() + ST_OUCHOFFSET;
 	    }
 	    else
 	    {
-		priority = 6;
+		st_facepriority = 6;
 		st_facecount = ST_TURNCOUNT;
 		st_faceindex = ST_calcPainOffset() + ST_RAMPAGEOFFSET;
 	    }
@@ -810,33 +813,33 @@ void ST_updateFaceWidget(void)
 
     }
   
-    if (priority < 6)
+    if (st_facepriority < 6)
     {
 	// rapid firing
 	if (plyr->attackdown)
 	{
-	    if (lastattackdown==-1)
-		lastattackdown = ST_RAMPAGEDELAY;
-	    else if (!--lastattackdown)
+	    if (st_lastattackdown==-1)
+		st_lastattackdown = ST_RAMPAGEDELAY;
+	    else if (!--st_lastattackdown)
 	    {
-		priority = 5;
+		st_facepriority = 5;
 		st_faceindex = ST_calcPainOffset() + ST_RAMPAGEOFFSET;
 		st_facecount = 1;
-		lastattackdown = 1;
+		st_lastattackdown = 1;
 	    }
 	}
 	else
-	    lastattackdown = -1;
+	    st_lastattackdown = -1;
 
     }
   
-    if (priority < 5)
+    if (st_facepriority < 5)
     {
 	// invulnerability
 	if ((plyr->cheats & CF_GODMODE)
 	    || plyr->powers[pw_invulnerability])
 	{
-	    priority = 4;
+	    st_facepriority = 4;
 
 	    st_faceindex = ST_GODFACE;
 	    st_facecount = 1;
@@ -850,7 +853,7 @@ void ST_updateFaceWidget(void)
     {
 	st_faceindex = ST_calcPainOffset() + (st_randomnumber % 3);
 	st_facecount = ST_STRAIGHTFACECOUNT;
-	priority = 0;
+	st_facepriority = 0;
     }
 
     st_facecount--;
@@ -1207,7 +1210,12 @@ void ST_initData(void)
     st_oldchat = st_chat = false;
     st_cursoron = false;
 
+    // Vanilla keeps the face going from the last game; start it over, so
+    // a new game looks the same whatever came before.
     st_faceindex = 0;
+    st_facecount = 0;
+    st_lastattackdown = -1;
+    st_facepriority = 0;
     st_palette = -1;
 
     st_oldhealth = -1;
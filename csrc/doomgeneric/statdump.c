@@ -330,9 +330,14 @@ static void PrintStats(FILE *stream, wbstartstruct_t *stats)
 
 #endif
 
+// Set by the host (--stats-out): capture every level, to be collected
+// with StatTake.
+boolean statcapture = false;
+
 void StatCopy(wbstartstruct_t *stats)
 {
-    if (M_ParmExists("-statdump") && num_captured_stats < MAX_CAPTURES)
+    if ((statcapture || M_ParmExists("-statdump"))
+     && num_captured_stats < MAX_CAPTURES)
     {
         memcpy(&captured_stats[num_captured_stats], stats,
                sizeof(wbstartstruct_t));
@@ -340,6 +345,23 @@ void StatCopy(wbstartstruct_t *stats)
     }
 }
 
+// Hand the oldest captured level over and forget it, so a long session
+// never runs into MAX_CAPTURES. Returns false if there is none.
+boolean StatTake(wbstartstruct_t *stats)
+{
+    if (num_captured_stats == 0)
+    {
+        return false;
+    }
+
+    memcpy(stats, &captured_stats[0], sizeof(wbstartstruct_t));
+    --num_captured_stats;
+    memmove(&captured_stats[0], &captured_stats[1],
+            num_captured_stats * sizeof(wbstartstruct_t));
+
+    return true;
+}
+
 void StatDump(void)
 {
 #if ORIGCODE
@@ -17,7 +17,10 @@
 #ifndef DOOM_STATDUMP_H
 #define DOOM_STATDUMP_H
 
+extern boolean statcapture;
+
 void StatCopy(wbstartstruct_t *stats);
+boolean StatTake(wbstartstruct_t *stats);
 void StatDump(void);
 
 #endif /* #ifndef DOOM_STATDUMP_H */
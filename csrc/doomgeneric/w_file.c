@@ -27,6 +27,9 @@
 
 extern wad_file_class_t stdc_wad_file;
 
+// Rust host virtual filesystem (csrc/platform/w_file_host.c)
+extern wad_file_class_t host_wad_file;
+
 /*
 #ifdef _WIN32
 extern wad_file_class_t win32_wad_file;
@@ -60,6 +63,15 @@ wad_file_t *W_OpenFile(char *path)
     // directly into memory.
     //
 
+    // Files the host serves from memory take priority over the disk.
+
+    result = host_wad_file.OpenFile(path);
+
+    if (result != NULL)
+    {
+        return result;
+    }
+
     if (!M_CheckParm("-mmap"))
     {
         return stdc_wad_file.OpenFile(path);
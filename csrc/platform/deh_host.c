@@ -0,0 +1,278 @@
+// csrc/platform/deh_host.c
+// DeHackEd support with the parsing done in Rust (src/deh.rs): this file only
+// holds what the engine links against (DEH_String and friends, the deh_misc
+// globals) plus dg_deh_set and dg_deh_text, which the host uses to poke the
+// info tables and replace strings.
+
+#include <stdarg.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "doomdef.h"
+#include "d_items.h"
+#include "info.h"
+#include "sounds.h"
+#include "deh_main.h"
+#include "deh_misc.h"
+#include "dg_bridge.h"
+
+extern int maxammo[NUMAMMO];
+extern int clipammo[NUMAMMO];
+
+int deh_initial_health      = DEH_DEFAULT_INITIAL_HEALTH;
+int deh_initial_bullets     = DEH_DEFAULT_INITIAL_BULLETS;
+int deh_max_health          = DEH_DEFAULT_MAX_HEALTH;
+int deh_max_armor           = DEH_DEFAULT_MAX_ARMOR;
+int deh_green_armor_class   = DEH_DEFAULT_GREEN_ARMOR_CLASS;
+int deh_blue_armor_class    = DEH_DEFAULT_BLUE_ARMOR_CLASS;
+int deh_max_soulsphere      = DEH_DEFAULT_MAX_SOULSPHERE;
+int deh_soulsphere_health   = DEH_DEFAULT_SOULSPHERE_HEALTH;
+int deh_megasphere_health   = DEH_DEFAULT_MEGASPHERE_HEALTH;
+int deh_god_mode_health     = DEH_DEFAULT_GOD_MODE_HEALTH;
+int deh_idfa_armor          = DEH_DEFAULT_IDFA_ARMOR;
+int deh_idfa_armor_class    = DEH_DEFAULT_IDFA_ARMOR_CLASS;
+int deh_idkfa_armor         = DEH_DEFAULT_IDKFA_ARMOR;
+int deh_idkfa_armor_class   = DEH_DEFAULT_IDKFA_ARMOR_CLASS;
+int deh_bfg_cells_per_shot  = DEH_DEFAULT_BFG_CELLS_PER_SHOT;
+int deh_species_infighting  = DEH_DEFAULT_SPECIES_INFIGHTING;
+
+static int* const misc_fields[] = {
+    &deh_initial_health, &deh_initial_bullets, &deh_max_health, &deh_max_armor,
+    &deh_green_armor_class, &deh_blue_armor_class, &deh_max_soulsphere,
+    &deh_soulsphere_health, &deh_megasphere_health, &deh_god_mode_health,
+    &deh_idfa_armor, &deh_idfa_armor_class, &deh_idkfa_armor,
+    &deh_idkfa_armor_class, &deh_bfg_cells_per_shot, &deh_species_infighting,
+};
+
+#define COUNT(a) ((int)(sizeof(a) / sizeof((a)[0])))
+
+// ---- String replacements ----
+
+typedef struct
+{
+    char* from;
+    char* to;
+    int from_patch;   // set by dg_deh_text; the engine's own don't replace it
+} replacement_t;
+
+static replacement_t* replacements;
+static int num_replacements, max_replacements;
+
+static replacement_t* FindReplacement(const char* s)
+{
+    for (int i = 0; i < num_replacements; ++i)
+    {
+        if (!strcmp(replacements[i].from, s))
+        {
+            return &replacements[i];
+        }
+    }
+    return NULL;
+}
+
+// strdup isn't declared under -std=c11.
+static char* CopyString(const char* s)
+{
+    size_t len = strlen(s) + 1;
+    return memcpy(malloc(len), s, len);
+}
+
+char* DEH_String(char* s)
+{
+    replacement_t* r = FindReplacement(s);
+    return r != NULL ? r->to : s;
+}
+
+static void AddReplacement(const char* from_text, const char* to_text, int from_patch)
+{
+    replacement_t* r = FindReplacement(from_text);
+
+    if (r != NULL)
+    {
+        if (r->from_patch && !from_patch)
+        {
+            return;
+        }
+        free(r->to);
+        r->to = CopyString(to_text);
+        r->from_patch = from_patch;
+        return;
+    }
+    if (num_replacements == max_replacements)
+    {
+        max_replacements = max_replacements ? max_replacements * 2 : 64;
+        replacements = realloc(replacements, max_replacements * sizeof(*replacements));
+    }
+    replacements[num_replacements].from = CopyString(from_text);
+    replacements[num_replacements].to = CopyString(to_text);
+    replacements[num_replacements].from_patch = from_patch;
+    ++num_replacements;
+}
+
+// The engine's own replacements (d_main.c's BFG Edition workarounds) run
+// after the host has loaded the patches, so they give way to them, as they
+// would have in Chocolate Doom where patches load later.
+void DEH_AddStringReplacement(char* from_text, char* to_text)
+{
+    AddReplacement(from_text, to_text, 0);
+}
+
+void dg_deh_text(const char* from_text, const char* to_text)
+{
+    AddReplacement(from_text, to_text, 1);
+}
+
+void DEH_printf(char* fmt, ...)
+{
+    va_list args;
+    va_start(args, fmt);
+    vprintf(DEH_String(fmt), args);
+    va_end(args);
+}
+
+void DEH_fprintf(FILE* fstream, char* fmt, ...)
+{
+    va_list args;
+    va_start(args, fmt);
+    vfprintf(fstream, DEH_String(fmt), args);
+    va_end(args);
+}
+
+void DEH_snprintf(char* buffer, size_t len, char* fmt, ...)
+{
+    va_list args;
+    va_start(args, fmt);
+    vsnprintf(buffer, len, DEH_String(fmt), args);
+    va_end(args);
+}
+
+// Patches (-deh, IWAD and PWAD DEHACKED lumps) are applied by the host
+// before the engine starts, in the same order Chocolate Doom uses.
+void DEH_ParseCommandLine(void)
+{
+}
+
+// ---- Tables ----
+
+static int SetThing(int index, int field, int value)
+{
+    if (index < 0 || index >= NUMMOBJTYPES)
+    {
+        return -1;
+    }
+    mobjinfo_t* m = &mobjinfo[index];
+    int* fields[] = {
+        &m->doomednum, &m->spawnstate, &m->spawnhealth, &m->seestate,
+        &m->seesound, &m->reactiontime, &m->attacksound, &m->painstate,
+        &m->painchance, &m->painsound, &m->meleestate, &m->missilestate,
+        &m->deathstate, &m->xdeathstate, &m->deathsound, &m->speed,
+        &m->radius, &m->height, &m->mass, &m->damage, &m->activesound,
+        &m->flags, &m->raisestate,
+    };
+    if (field < 0 || field >= COUNT(fields))
+    {
+        return -1;
+    }
+    *fields[field] = value;
+    return 0;
+}
+
+static int SetFrame(int index, int field, int value)
+{
+    // Code pointers are copied from the unpatched table, so the order
+    // Pointer sections are applied in doesn't matter.
+    static actionf_t original_actions[NUMSTATES];
+    static int saved;
+
+    if (!saved)
+    {
+        for (int i = 0; i < NUMSTATES; ++i)
+        {
+            original_actions[i] = states[i].action;
+        }
+        saved = 1;
+    }
+    if (index < 0 || index >= NUMSTATES)
+    {
+        return -1;
+    }
+    state_t* s = &states[index];
+    switch (field)
+    {
+        case 0: s->sprite = value; break;
+        case 1: s->frame = value; break;
+        case 2: s->tics = value; break;
+        case 3: s->nextstate = value; break;
+        case 4: s->misc1 = value; break;
+        case 5: s->misc2 = value; break;
+        case 6:
+            if (value < 0 || value >= NUMSTATES)
+            {
+                return -1;
+            }
+            s->action = original_actions[value];
+            break;
+        default: return -1;
+    }
+    return 0;
+}
+
+static int SetWeapon(int index, int field, int value)
+{
+    if (index < 0 || index >= NUMWEAPONS)
+    {
+        return -1;
+    }
+    weaponinfo_t* w = &weaponinfo[index];
+    int* fields[] = {
+        (int*)&w->ammo, &w->upstate, &w->downstate, &w->readystate,
+        &w->atkstate, &w->flashstate,
+    };
+    if (field < 0 || field >= COUNT(fields))
+    {
+        return -1;
+    }
+    *fields[field] = value;
+    return 0;
+}
+
+int dg_deh_set(int table, int index, int field, int value)
+{
+    switch (table)
+    {
+        case DG_DEH_THING:
+            return SetThing(index, field, value);
+
+        case DG_DEH_FRAME:
+            return SetFrame(index, field, value);
+
+        case DG_DEH_SOUND:
+            if (index < 0 || index >= NUMSFX || field != 0)
+            {
+                return -1;
+            }
+            S_sfx[index].priority = value;
+            return 0;
+
+        case DG_DEH_WEAPON:
+            return SetWeapon(index, field, value);
+
+        case DG_DEH_AMMO:
+            if (index < 0 || index >= NUMAMMO || field < 0 || field > 1)
+            {
+                return -1;
+            }
+            (field == 0 ? maxammo : clipammo)[index] = value;
+            return 0;
+
+        case DG_DEH_MISC:
+            if (index != 0 || field < 0 || field >= COUNT(misc_fields))
+            {
+                return -1;
+            }
+            *misc_fields[field] = value;
+            return 0;
+    }
+    return -1;
+}
@@ -24,6 +24,8 @@
 #define DGK_RIGHT   1103
 #define DGK_USE     1200
 #define DGK_FIRE    1201
+#define DGK_RUN     1202
+#define DGK_STRAFE  1203
 
 typedef enum {
   HE_NONE=0, HE_KEY, HE_MOUSE_BTN, HE_MOUSE_REL, HE_MOUSE_ABS, HE_WHEEL
@@ -70,6 +72,8 @@ static int map_host_key_to_doom(int code) {
     case DGK_RIGHT:  return KEY_RIGHTARROW;
     case DGK_USE:    return KEY_USE;   // Space (optional)
     case DGK_FIRE:   return KEY_FIRE;  // Ctrl (optional)
+    case DGK_RUN:    return KEY_RSHIFT;  // the default key_speed
+    case DGK_STRAFE: return KEY_RALT;    // the default key_strafe
     default:         return 0;
   }
 }
@@ -1,8 +1,26 @@
 #include <stdint.h>
+#include <string.h>
 #include <time.h>
 #include "doomgeneric.h"
+#include "doomstat.h"
+#include "d_loop.h"
+#include "d_main.h"
+#include "g_game.h"
+#include "m_config.h"
+#include "m_misc.h"
+#include "m_random.h"
+#include "p_saveg.h"
+#include "statdump.h"
+#include "i_timer.h"
+#include "w_wad.h"
 #include "dg_bridge.h"
 
+// d_main.c, not exported through any header.
+void D_BindVariables(void);
+extern boolean advancedemo;
+// g_game.c, likewise.
+extern boolean netdemo;
+
 // Provided by engine
 extern pixel_t* DG_ScreenBuffer;
 
@@ -22,7 +40,17 @@ void DG_DrawFrame(void) {
     // no-op: Rust reads DG_ScreenBuffer and presents via softbuffer
 }
 
+// dg_use_virtual_clock: time only moves when the host ticks or the engine
+// sleeps. Counted in microseconds so a tic is exact enough; it starts past
+// zero because i_timer.c treats a zero reading as "clock not started yet".
+static int virtual_clock = 0;
+static uint64_t virtual_us = 1000;
+
 void DG_SleepMs(uint32_t ms) {
+    if (virtual_clock) {
+        virtual_us += (uint64_t)ms * 1000u;
+        return;
+    }
     struct timespec ts;
     ts.tv_sec  = ms / 1000u;
     ts.tv_nsec = (long)(ms % 1000u) * 1000000L;
@@ -30,6 +58,7 @@ void DG_SleepMs(uint32_t ms) {
 }
 
 uint32_t DG_GetTicksMs(void) {
+    if (virtual_clock) return (uint32_t)(virtual_us / 1000u);
 #if defined(CLOCK_MONOTONIC)
     struct timespec ts;
     clock_gettime(CLOCK_MONOTONIC, &ts);
@@ -45,8 +74,15 @@ int DG_GetKey(int* pressed, unsigned char* key) {
     return 0;
 }
 
+// Last title the engine asked for; Rust polls it and owns the actual window.
+static char dg_title[128];
+static int  dg_title_set = 0;
+
 void DG_SetWindowTitle(const char * title) {
-    (void)title; // Rust controls window title
+    if (!title) return;
+    strncpy(dg_title, title, sizeof(dg_title) - 1);
+    dg_title[sizeof(dg_title) - 1] = '\0';
+    dg_title_set = 1;
 }
 
 /*======================
@@ -65,8 +101,31 @@ int dg_create_simple(const char* iwad_path) {
     return 0;
 }
 
+int dg_create(int argc, const char* const* argv) {
+    // The engine keeps myargv for the whole run; take our own copy.
+    char** args = malloc(sizeof(char*) * (size_t)(argc + 1));
+    if (!args) return -1;
+    for (int i = 0; i < argc; ++i) {
+        size_t n = strlen(argv[i]) + 1;
+        args[i] = malloc(n);
+        if (!args[i]) return -1;
+        memcpy(args[i], argv[i], n);
+    }
+    args[argc] = NULL;
+    doomgeneric_Create(argc, args);
+    // From here on the host paces the game: one tic per dg_tick.
+    singletics = true;
+    return 0;
+}
+
+void dg_use_virtual_clock(void) {
+    virtual_clock = 1;
+}
+
 void dg_tick(void) {
+    if (virtual_clock) virtual_us += 1000000u / TICRATE;
     doomgeneric_Tick();
+    DG_StateChanged();
 }
 
 const uint32_t* dg_framebuffer32(int* w, int* h) {
@@ -74,3 +133,123 @@ const uint32_t* dg_framebuffer32(int* w, int* h) {
     if (h) *h = dg_height();
     return (const uint32_t*)DG_ScreenBuffer;
 }
+
+const char* dg_window_title(void) {
+    return dg_title_set ? dg_title : NULL;
+}
+
+int dg_new_game(int skill, int episode, int map, int seed) {
+    char lump[9];
+    if (skill < sk_baby || skill > sk_nightmare || episode < 1 || map < 1) return -1;
+    // G_InitNew gives a fatal error for a map that isn't there.
+    if (gamemode == commercial) M_snprintf(lump, sizeof(lump), "MAP%02d", map);
+    else M_snprintf(lump, sizeof(lump), "E%dM%d", episode, map);
+    if (W_CheckNumForName(lump) < 0) return -1;
+
+    // G_DoNewGame, minus the deferral, and leaving the demo loop behind.
+    demoplayback = false;
+    netdemo = false;
+    netgame = false;
+    deathmatch = false;
+    playeringame[1] = playeringame[2] = playeringame[3] = 0;
+    respawnparm = false;
+    fastparm = false;
+    nomonsters = false;
+    consoleplayer = 0;
+    advancedemo = false;
+    rndseed = seed;
+    DG_MuteEvents(1);
+    G_InitNew((skill_t)skill, episode, map);
+    DG_MuteEvents(0);
+    rndseed = 0;
+    gameaction = ga_nothing;
+    DG_StateChanged();
+    return 0;
+}
+
+int dg_save_game(int slot, const char* description) {
+    // Same conditions as the save menu: only mid-level in a game you're playing.
+    if (slot < 0 || !usergame || gamestate != GS_LEVEL || demoplayback) return -1;
+    G_SaveGame(slot, (char*)(description ? description : ""));
+    return 0;
+}
+
+int dg_load_game(int slot) {
+    if (slot < 0 || netgame) return -1;
+    char* file = P_SaveGameFile(slot);
+    if (!M_FileExists(file)) return -1;
+    G_LoadGame(file);
+    return 0;
+}
+
+int dg_config_load(const char* dir) {
+    // Without the rest of D_DoomMain: bind and read the variables, nothing else.
+    if (!dir) return -1;
+    configdir = M_StringJoin((char*)dir, DIR_SEPARATOR_S, NULL);
+    M_SetConfigFilenames("default.cfg", "extended.cfg");
+    D_BindVariables();
+    M_LoadDefaults();
+    return 0;
+}
+
+const char* dg_config_name(int i) {
+    return M_GetVariableName(i);
+}
+
+int dg_config_get(const char* name, char* buf, size_t len) {
+    if (!name || !buf || len == 0) return -1;
+    return M_FormatVariable((char*)name, buf, len) ? 0 : -1;
+}
+
+int dg_config_set(const char* name, const char* value) {
+    char unused[2];
+    // M_SetVariable treats an unknown name as a fatal error; check first.
+    if (!name || !value || !M_FormatVariable((char*)name, unused, sizeof(unused))) return -1;
+    return M_SetVariable((char*)name, (char*)value) ? 0 : -1;
+}
+
+void dg_config_save(void) {
+    M_SaveDefaults();
+}
+
+void dg_demo_status(dg_demo_status_t* out) {
+    if (!out) return;
+    out->playback  = demoplayback ? 1 : 0;
+    out->recording = demorecording ? 1 : 0;
+    out->finished  = demofinished ? 1 : 0;
+    out->gametics  = timedemo_gametics;
+    out->realtics  = timedemo_realtics;
+    out->tics      = demotics;
+}
+
+void dg_demo_end(void) {
+    // Same as pressing the demo quit key: write the recording out.
+    if (demorecording) G_CheckDemoStatus();
+}
+
+void dg_stats_capture(int on) {
+    statcapture = on ? true : false;
+}
+
+int dg_stats_take(dg_level_stats_t* out) {
+    wbstartstruct_t wb;
+    if (!out || !StatTake(&wb)) return 0;
+    out->episode   = wb.epsd + 1;
+    out->map       = wb.last + 1;
+    out->next      = wb.next + 1;
+    out->didsecret = wb.didsecret ? 1 : 0;
+    out->maxkills  = wb.maxkills;
+    out->maxitems  = wb.maxitems;
+    out->maxsecret = wb.maxsecret;
+    out->time      = wb.plyr[0].stime;
+    out->partime   = wb.partime;
+    out->pnum      = wb.pnum;
+    for (int i = 0; i < 4; ++i) {
+        out->players[i].in      = wb.plyr[i].in ? 1 : 0;
+        out->players[i].kills   = wb.plyr[i].skills;
+        out->players[i].items   = wb.plyr[i].sitems;
+        out->players[i].secrets = wb.plyr[i].ssecret;
+        for (int j = 0; j < 4; ++j) out->players[i].frags[j] = wb.plyr[i].frags[j];
+    }
+    return 1;
+}
@@ -0,0 +1,46 @@
+// csrc/platform/events.c
+// DG_Event, raised by the engine when something happens in a tic, passed
+// on to the host's hook with the tic it happened in.
+
+#include <string.h>
+
+#include "doomgeneric.h"
+#include "doomstat.h"
+#include "dg_bridge.h"
+
+static dg_event_hook_t event_hook = NULL;
+static int muted = 0;
+
+void dg_set_event_hook(dg_event_hook_t hook)
+{
+    event_hook = hook;
+}
+
+void DG_MuteEvents(int mute)
+{
+    muted = mute;
+}
+
+void DG_Event(int kind, int player, int a, int b, int c, const char *name)
+{
+    dg_event_t ev;
+
+    if (event_hook == NULL || muted)
+    {
+        return;
+    }
+
+    memset(&ev, 0, sizeof(ev));
+    ev.kind = kind;
+    ev.tic = gametic;
+    ev.player = player;
+    ev.a = a;
+    ev.b = b;
+    ev.c = c;
+    if (name != NULL)
+    {
+        strncpy(ev.name, name, sizeof(ev.name) - 1);
+    }
+
+    event_hook(&ev);
+}
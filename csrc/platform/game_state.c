@@ -0,0 +1,138 @@
+// csrc/platform/game_state.c
+// The console player, level totals and map objects, copied when the host
+// first asks after a tic and kept until the next, so asking again (or for
+// the mobjs, after their count) reads the same tic's state.
+
+#include <stdlib.h>
+#include <string.h>
+
+#include "doomgeneric.h"
+#include "doomstat.h"
+#include "i_system.h"
+#include "p_local.h"
+#include "dg_bridge.h"
+
+static dg_game_state_t state;
+static boolean stale = true;
+static dg_mobj_t *mobjs;
+static int nummobjs;
+static int maxmobjs;
+
+static void CopyMobj(mobj_t *mo)
+{
+    dg_mobj_t *out;
+
+    if (nummobjs == maxmobjs)
+    {
+        maxmobjs = maxmobjs ? maxmobjs * 2 : 256;
+        mobjs = realloc(mobjs, maxmobjs * sizeof(*mobjs));
+        if (mobjs == NULL)
+        {
+            I_Error("dg_mobjs: out of memory");
+        }
+    }
+    out = &mobjs[nummobjs++];
+    memset(out, 0, sizeof(*out));
+    out->type = mo->type;
+    out->doomednum = mo->info->doomednum;
+    out->x = mo->x;
+    out->y = mo->y;
+    out->z = mo->z;
+    out->angle = mo->angle;
+    out->health = mo->health;
+    out->flags = mo->flags;
+    strncpy(out->sprite, sprnames[mo->sprite], sizeof(out->sprite) - 1);
+}
+
+void DG_StateChanged(void)
+{
+    stale = true;
+}
+
+static void Capture(void)
+{
+    player_t *p = &players[consoleplayer];
+    thinker_t *th;
+
+    if (!stale)
+    {
+        return;
+    }
+    stale = false;
+    memset(&state, 0, sizeof(state));
+    state.gametic = gametic;
+    state.mission = gamemission;
+    state.mode = gamemode;
+    state.gamestate = gamestate;
+    state.episode = gameepisode;
+    state.map = gamemap;
+    state.skill = gameskill;
+    state.leveltime = leveltime;
+    state.paused = paused;
+    state.menu = menuactive;
+    state.totalkills = totalkills;
+    state.totalitems = totalitems;
+    state.totalsecret = totalsecret;
+
+    state.ingame = playeringame[consoleplayer] && gamestate == GS_LEVEL;
+    state.dead = p->playerstate == PST_DEAD;
+    state.health = p->health;
+    state.armor = p->armorpoints;
+    state.armortype = p->armortype;
+    for (int i = 0; i < NUMAMMO; ++i)
+    {
+        state.ammo[i] = p->ammo[i];
+        state.maxammo[i] = p->maxammo[i];
+    }
+    state.weapon = p->readyweapon;
+    for (int i = 0; i < NUMWEAPONS; ++i)
+    {
+        state.weaponowned[i] = p->weaponowned[i];
+    }
+    for (int i = 0; i < NUMCARDS; ++i)
+    {
+        state.cards[i] = p->cards[i];
+    }
+    if (p->mo != NULL)
+    {
+        state.x = p->mo->x;
+        state.y = p->mo->y;
+        state.z = p->mo->z;
+        state.angle = p->mo->angle;
+    }
+    state.kills = p->killcount;
+    state.items = p->itemcount;
+    state.secrets = p->secretcount;
+
+    nummobjs = 0;
+    if (gamestate == GS_LEVEL)
+    {
+        for (th = thinkercap.next; th != &thinkercap; th = th->next)
+        {
+            if (th->function.acp1 == (actionf_p1) P_MobjThinker)
+            {
+                CopyMobj((mobj_t *) th);
+            }
+        }
+    }
+    state.mobjs = nummobjs;
+}
+
+void dg_game_state(dg_game_state_t *out)
+{
+    Capture();
+    if (out != NULL)
+    {
+        *out = state;
+    }
+}
+
+int dg_mobjs(dg_mobj_t *out, int max)
+{
+    Capture();
+    if (out != NULL && max > 0)
+    {
+        memcpy(out, mobjs, (max < nummobjs ? max : nummobjs) * sizeof(*out));
+    }
+    return nummobjs;
+}
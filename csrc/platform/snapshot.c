@@ -0,0 +1,715 @@
+// csrc/platform/snapshot.c
+// In-memory snapshots of the running level for rewind. Reuses the savegame
+// archive code (p_saveg.c) against a memory stream instead of a file, without
+// the vanilla size limit, and adds what the savegame format leaves out so a
+// restored snapshot plays on exactly as the original did: the RNG indices,
+// the mobj references the archive drops (target, tracer, attacker, sector
+// soundtargets) as indices, full-precision heights, the order of the thinker
+// list and of the sector and blockmap chains, switches waiting to pop back,
+// the boss brain's targets and plats stopped in stasis.
+//
+// A header with the size and a checksum goes in front, so a damaged or
+// foreign buffer is turned away before any game state is touched.
+
+#define _POSIX_C_SOURCE 200809L
+
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "doomgeneric.h"
+#include "doomstat.h"
+#include "g_game.h"
+#include "i_system.h"
+#include "p_local.h"
+#include "p_saveg.h"
+#include "p_spec.h"
+#include "r_local.h"
+#include "z_zone.h"
+#include "dg_bridge.h"
+
+// Not exported through any header.
+extern int rndindex;
+extern int prndindex;
+extern boolean setsizeneeded;
+extern mobj_t* braintargets[32];
+extern int numbraintargets;
+extern int braintargeton;
+void R_ExecuteSetViewSize(void);
+
+#define SNAPSHOT_MAGIC "DGSNAP2"
+
+typedef struct
+{
+    char     magic[8];
+    uint64_t size;       // bytes after the header
+    uint64_t checksum;   // FNV-1a of those bytes
+} snapshot_header_t;
+
+static uint64_t Checksum(const uint8_t* data, size_t len)
+{
+    uint64_t hash = 0xcbf29ce484222325ULL;
+
+    for (size_t i = 0; i < len; ++i)
+    {
+        hash ^= data[i];
+        hash *= 0x100000001b3ULL;
+    }
+    return hash;
+}
+
+static int CanSnapshot(void)
+{
+    return usergame && gamestate == GS_LEVEL && !demoplayback && !demorecording && !netgame;
+}
+
+// ---- The thinkers the archive writes, in the order it writes them ----
+
+// Mobjs first, then the specials P_ArchiveSpecials knows, then the plats in
+// stasis it misses; a thinker's place here is its index in the snapshot.
+typedef struct
+{
+    thinker_t** thinkers;
+    int         mobjs, specials, plats;
+} archived_t;
+
+static boolean IsActiveCeiling(thinker_t* th)
+{
+    for (int i = 0; i < MAXCEILINGS; ++i)
+    {
+        if (activeceilings[i] == (ceiling_t*)th)
+        {
+            return true;
+        }
+    }
+    return false;
+}
+
+static boolean IsActivePlat(thinker_t* th)
+{
+    for (int i = 0; i < MAXPLATS; ++i)
+    {
+        if (activeplats[i] == (plat_t*)th)
+        {
+            return true;
+        }
+    }
+    return false;
+}
+
+// 0 for a mobj, 1 for a special, 2 for a plat in stasis, -1 for anything
+// the snapshot doesn't keep (thinkers already removed).
+static int ThinkerKind(thinker_t* th)
+{
+    actionf_p1 f = th->function.acp1;
+
+    if (f == (actionf_p1)P_MobjThinker)
+    {
+        return 0;
+    }
+    if (th->function.acv == (actionf_v)NULL)
+    {
+        return IsActiveCeiling(th) ? 1 : IsActivePlat(th) ? 2 : -1;
+    }
+    if (f == (actionf_p1)T_MoveCeiling || f == (actionf_p1)T_VerticalDoor
+     || f == (actionf_p1)T_MoveFloor || f == (actionf_p1)T_PlatRaise
+     || f == (actionf_p1)T_LightFlash || f == (actionf_p1)T_StrobeFlash
+     || f == (actionf_p1)T_Glow)
+    {
+        return 1;
+    }
+    return -1;
+}
+
+static void CollectThinkers(archived_t* a)
+{
+    int counts[3] = {0, 0, 0};
+    int next[3];
+    thinker_t* th;
+
+    for (th = thinkercap.next; th != &thinkercap; th = th->next)
+    {
+        int kind = ThinkerKind(th);
+        if (kind >= 0)
+        {
+            ++counts[kind];
+        }
+    }
+    a->mobjs = counts[0];
+    a->specials = counts[1];
+    a->plats = counts[2];
+    a->thinkers = malloc((counts[0] + counts[1] + counts[2] + 1) * sizeof(*a->thinkers));
+    next[0] = 0;
+    next[1] = counts[0];
+    next[2] = counts[0] + counts[1];
+    for (th = thinkercap.next; th != &thinkercap; th = th->next)
+    {
+        int kind = ThinkerKind(th);
+        if (kind >= 0)
+        {
+            a->thinkers[next[kind]++] = th;
+        }
+    }
+}
+
+// Pointer to index, by binary search over the archived thinkers.
+typedef struct
+{
+    const void* ptr;
+    int         index;
+} lookup_t;
+
+static lookup_t* lookup;
+static int lookup_len;
+
+static int ComparePtr(const void* a, const void* b)
+{
+    uintptr_t x = (uintptr_t)((const lookup_t*)a)->ptr;
+    uintptr_t y = (uintptr_t)((const lookup_t*)b)->ptr;
+    return x < y ? -1 : x > y;
+}
+
+static void BuildLookup(const archived_t* a)
+{
+    lookup_len = a->mobjs + a->specials + a->plats;
+    lookup = malloc((lookup_len + 1) * sizeof(*lookup));
+    for (int i = 0; i < lookup_len; ++i)
+    {
+        lookup[i].ptr = a->thinkers[i];
+        lookup[i].index = i;
+    }
+    qsort(lookup, lookup_len, sizeof(*lookup), ComparePtr);
+}
+
+// A mobj's index, or -1 for NULL and anything no longer in the level.
+static int MobjIndex(const mobj_t* mo, int mobjs)
+{
+    lookup_t key = { mo, 0 };
+    lookup_t* found;
+
+    if (mo == NULL)
+    {
+        return -1;
+    }
+    found = bsearch(&key, lookup, lookup_len, sizeof(*lookup), ComparePtr);
+    return found != NULL && found->index < mobjs ? found->index : -1;
+}
+
+// ---- Writing and reading the extra section ----
+
+static void Put(int32_t value)
+{
+    if (fwrite(&value, sizeof(value), 1, save_stream) != 1)
+    {
+        savegame_error = true;
+    }
+}
+
+static int32_t Get(void)
+{
+    int32_t value = 0;
+
+    if (fread(&value, sizeof(value), 1, save_stream) != 1)
+    {
+        savegame_error = true;
+    }
+    return value;
+}
+
+// An index below `limit`, or -1; anything else marks the snapshot corrupt.
+static int GetIndex(int limit)
+{
+    int32_t value = Get();
+
+    if (value < -1 || value >= limit)
+    {
+        savegame_error = true;
+        return -1;
+    }
+    return value;
+}
+
+static void WriteExtras(void)
+{
+    archived_t a;
+    int total, cells, used;
+    thinker_t* th;
+
+    CollectThinkers(&a);
+    BuildLookup(&a);
+    total = a.mobjs + a.specials + a.plats;
+
+    Put(a.mobjs);
+    Put(a.specials);
+    Put(a.plats);
+
+    // The thinker list, as indices.
+    for (th = thinkercap.next; th != &thinkercap; th = th->next)
+    {
+        if (ThinkerKind(th) >= 0)
+        {
+            lookup_t key = { th, 0 };
+            Put(((lookup_t*)bsearch(&key, lookup, lookup_len, sizeof(*lookup), ComparePtr))->index);
+        }
+    }
+
+    for (int i = 0; i < a.mobjs; ++i)
+    {
+        mobj_t* mo = (mobj_t*)a.thinkers[i];
+
+        Put(MobjIndex(mo->target, a.mobjs));
+        Put(MobjIndex(mo->tracer, a.mobjs));
+        // Only followed while the mobj is linked in.
+        Put(mo->flags & MF_NOSECTOR ? -1 : MobjIndex(mo->snext, a.mobjs));
+        Put(mo->flags & MF_NOBLOCKMAP ? -1 : MobjIndex(mo->bnext, a.mobjs));
+        Put(mo->floorz);
+        Put(mo->ceilingz);
+    }
+
+    for (int i = 0; i < MAXPLAYERS; ++i)
+    {
+        Put(playeringame[i] ? MobjIndex(players[i].attacker, a.mobjs) : -1);
+    }
+
+    Put(numsectors);
+    for (int i = 0; i < numsectors; ++i)
+    {
+        Put(sectors[i].floorheight);
+        Put(sectors[i].ceilingheight);
+        Put(MobjIndex(sectors[i].soundtarget, a.mobjs));
+        Put(MobjIndex(sectors[i].thinglist, a.mobjs));
+    }
+
+    Put(numsides);
+    for (int i = 0; i < numsides; ++i)
+    {
+        Put(sides[i].textureoffset);
+        Put(sides[i].rowoffset);
+    }
+
+    cells = bmapwidth * bmapheight;
+    used = 0;
+    for (int i = 0; i < cells; ++i)
+    {
+        used += blocklinks[i] != NULL;
+    }
+    Put(cells);
+    Put(used);
+    for (int i = 0; i < cells; ++i)
+    {
+        if (blocklinks[i] != NULL)
+        {
+            Put(i);
+            Put(MobjIndex(blocklinks[i], a.mobjs));
+        }
+    }
+
+    for (int i = 0; i < MAXBUTTONS; ++i)
+    {
+        button_t* b = &buttonlist[i];
+
+        Put(b->line ? b->line - lines : -1);
+        Put(b->where);
+        Put(b->btexture);
+        Put(b->btimer);
+    }
+
+    Put(numbraintargets);
+    Put(braintargeton);
+    for (int i = 0; i < numbraintargets; ++i)
+    {
+        Put(MobjIndex(braintargets[i], a.mobjs));
+    }
+
+    for (int i = a.mobjs + a.specials; i < total; ++i)
+    {
+        plat_t* plat = (plat_t*)a.thinkers[i];
+
+        Put(plat->sector - sectors);
+        Put(plat->speed);
+        Put(plat->low);
+        Put(plat->high);
+        Put(plat->wait);
+        Put(plat->count);
+        Put(plat->status);
+        Put(plat->oldstatus);
+        Put(plat->crush);
+        Put(plat->tag);
+        Put(plat->type);
+    }
+
+    free(lookup);
+    free(a.thinkers);
+    lookup = NULL;
+}
+
+// After P_UnArchive*: the thinker list holds the mobjs, then the specials.
+// Puts back everything WriteExtras recorded; false if the snapshot doesn't
+// fit the level that was loaded.
+static boolean ReadExtras(void)
+{
+    archived_t a;
+    int mobjs, specials, plats, total, cells, used;
+    int* order;
+    boolean ok = false;
+    thinker_t* th;
+
+    mobjs = Get();
+    specials = Get();
+    plats = Get();
+    CollectThinkers(&a);
+    if (savegame_error || mobjs != a.mobjs || specials != a.specials || a.plats != 0
+     || plats < 0 || plats > MAXPLATS)
+    {
+        free(a.thinkers);
+        return false;
+    }
+    total = mobjs + specials + plats;
+    a.thinkers = realloc(a.thinkers, (total + 1) * sizeof(*a.thinkers));
+    order = malloc((total + 1) * sizeof(*order));
+
+    // The plats in stasis are read last but need to exist before the
+    // thinker list is put back in order.
+    for (int i = 0; i < total; ++i)
+    {
+        order[i] = GetIndex(total);
+    }
+
+    for (int i = 0; i < mobjs; ++i)
+    {
+        mobj_t* mo = (mobj_t*)a.thinkers[i];
+
+        mo->sprev = mo->bprev = NULL;
+    }
+    for (int i = 0; i < mobjs; ++i)
+    {
+        mobj_t* mo = (mobj_t*)a.thinkers[i];
+        int target = GetIndex(mobjs);
+        int tracer = GetIndex(mobjs);
+        int snext = GetIndex(mobjs);
+        int bnext = GetIndex(mobjs);
+
+        mo->target = target < 0 ? NULL : (mobj_t*)a.thinkers[target];
+        mo->tracer = tracer < 0 ? NULL : (mobj_t*)a.thinkers[tracer];
+        mo->snext = snext < 0 ? NULL : (mobj_t*)a.thinkers[snext];
+        mo->bnext = bnext < 0 ? NULL : (mobj_t*)a.thinkers[bnext];
+        mo->floorz = Get();
+        mo->ceilingz = Get();
+    }
+    for (int i = 0; i < mobjs; ++i)
+    {
+        mobj_t* mo = (mobj_t*)a.thinkers[i];
+
+        if (mo->snext)
+        {
+            mo->snext->sprev = mo;
+        }
+        if (mo->bnext)
+        {
+            mo->bnext->bprev = mo;
+        }
+    }
+
+    for (int i = 0; i < MAXPLAYERS; ++i)
+    {
+        int attacker = GetIndex(mobjs);
+        players[i].attacker = attacker < 0 ? NULL : (mobj_t*)a.thinkers[attacker];
+    }
+
+    if (Get() != numsectors)
+    {
+        goto done;
+    }
+    for (int i = 0; i < numsectors; ++i)
+    {
+        int soundtarget, thinglist;
+
+        sectors[i].floorheight = Get();
+        sectors[i].ceilingheight = Get();
+        soundtarget = GetIndex(mobjs);
+        thinglist = GetIndex(mobjs);
+        sectors[i].soundtarget = soundtarget < 0 ? NULL : (mobj_t*)a.thinkers[soundtarget];
+        sectors[i].thinglist = thinglist < 0 ? NULL : (mobj_t*)a.thinkers[thinglist];
+    }
+
+    if (Get() != numsides)
+    {
+        goto done;
+    }
+    for (int i = 0; i < numsides; ++i)
+    {
+        sides[i].textureoffset = Get();
+        sides[i].rowoffset = Get();
+    }
+
+    cells = Get();
+    used = Get();
+    if (cells != bmapwidth * bmapheight || used < 0 || used > cells)
+    {
+        goto done;
+    }
+    memset(blocklinks, 0, cells * sizeof(*blocklinks));
+    for (int i = 0; i < used; ++i)
+    {
+        int cell = GetIndex(cells);
+        int head = GetIndex(mobjs);
+
+        if (cell >= 0 && head >= 0)
+        {
+            blocklinks[cell] = (mobj_t*)a.thinkers[head];
+        }
+    }
+
+    for (int i = 0; i < MAXBUTTONS; ++i)
+    {
+        button_t* b = &buttonlist[i];
+        int line = GetIndex(numlines);
+
+        b->line = line < 0 ? NULL : &lines[line];
+        b->where = Get();
+        b->btexture = Get();
+        b->btimer = Get();
+        b->soundorg = b->line ? &b->line->frontsector->soundorg : NULL;
+    }
+
+    numbraintargets = Get();
+    braintargeton = Get();
+    if (numbraintargets < 0 || numbraintargets > 32)
+    {
+        goto done;
+    }
+    for (int i = 0; i < numbraintargets; ++i)
+    {
+        int target = GetIndex(mobjs);
+        braintargets[i] = target < 0 ? NULL : (mobj_t*)a.thinkers[target];
+    }
+
+    for (int i = mobjs + specials; i < total; ++i)
+    {
+        int sector = GetIndex(numsectors);
+        plat_t* plat = Z_Malloc(sizeof(*plat), PU_LEVEL, NULL);
+
+        if (sector < 0)
+        {
+            savegame_error = true;
+            sector = 0;
+        }
+        memset(plat, 0, sizeof(*plat));
+        plat->sector = &sectors[sector];
+        plat->speed = Get();
+        plat->low = Get();
+        plat->high = Get();
+        plat->wait = Get();
+        plat->count = Get();
+        plat->status = Get();
+        plat->oldstatus = Get();
+        plat->crush = Get();
+        plat->tag = Get();
+        plat->type = Get();
+        plat->sector->specialdata = plat;
+        P_AddThinker(&plat->thinker);
+        plat->thinker.function.acv = (actionf_v)NULL;
+        P_AddActivePlat(plat);
+        a.thinkers[i] = &plat->thinker;
+    }
+
+    if (savegame_error)
+    {
+        goto done;
+    }
+
+    // Relink the thinker list in the original order; every index once.
+    {
+        char* seen = calloc(total + 1, 1);
+        boolean permutation = true;
+
+        for (int i = 0; i < total; ++i)
+        {
+            if (order[i] < 0 || seen[order[i]])
+            {
+                permutation = false;
+                break;
+            }
+            seen[order[i]] = 1;
+        }
+        free(seen);
+        if (!permutation)
+        {
+            goto done;
+        }
+    }
+    th = &thinkercap;
+    for (int i = 0; i < total; ++i)
+    {
+        thinker_t* next = a.thinkers[order[i]];
+
+        th->next = next;
+        next->prev = th;
+        th = next;
+    }
+    th->next = &thinkercap;
+    thinkercap.prev = th;
+    ok = true;
+
+done:
+    free(order);
+    free(a.thinkers);
+    return ok;
+}
+
+// ---- The bridge ----
+
+int dg_snapshot_save(uint8_t** data, size_t* len)
+{
+    snapshot_header_t header;
+    char* buf = NULL;
+    size_t size = 0;
+
+    if (!data || !len || !CanSnapshot())
+    {
+        return -1;
+    }
+    save_stream = open_memstream(&buf, &size);
+    if (save_stream == NULL)
+    {
+        return -1;
+    }
+    savegame_error = false;
+
+    // Filled in once the rest is written.
+    memset(&header, 0, sizeof(header));
+    fwrite(&header, sizeof(header), 1, save_stream);
+
+    P_WriteSaveGameHeader("");
+    P_ArchivePlayers();
+    P_ArchiveWorld();
+    P_ArchiveThinkers();
+    P_ArchiveSpecials();
+    P_WriteSaveGameEOF();
+
+    fputc(rndindex, save_stream);
+    fputc(prndindex, save_stream);
+    WriteExtras();
+
+    fclose(save_stream);
+    save_stream = NULL;
+
+    if (savegame_error || size < sizeof(header))
+    {
+        free(buf);
+        return -1;
+    }
+    memcpy(header.magic, SNAPSHOT_MAGIC, sizeof(header.magic));
+    header.size = size - sizeof(header);
+    header.checksum = Checksum((uint8_t*)buf + sizeof(header), header.size);
+    memcpy(buf, &header, sizeof(header));
+
+    *data = (uint8_t*)buf;
+    *len = size;
+    return 0;
+}
+
+void dg_snapshot_free(uint8_t* data)
+{
+    free(data);
+}
+
+// Whether `data` is a whole, undamaged snapshot from this build.
+static boolean Intact(const uint8_t* data, size_t len)
+{
+    snapshot_header_t header;
+
+    if (data == NULL || len < sizeof(header))
+    {
+        return false;
+    }
+    memcpy(&header, data, sizeof(header));
+    return memcmp(header.magic, SNAPSHOT_MAGIC, sizeof(header.magic)) == 0
+        && header.size == len - sizeof(header)
+        && header.checksum == Checksum(data + sizeof(header), header.size);
+}
+
+// Restore an intact snapshot. False if it turns out not to fit, with the
+// level left half restored.
+static boolean Restore(const uint8_t* data, size_t len)
+{
+    int savedleveltime;
+    int r, p;
+    boolean ok;
+
+    save_stream = fmemopen((void*)(data + sizeof(snapshot_header_t)), len - sizeof(snapshot_header_t), "rb");
+    if (save_stream == NULL)
+    {
+        return false;
+    }
+    savegame_error = false;
+
+    if (!P_ReadSaveGameHeader())
+    {
+        fclose(save_stream);
+        save_stream = NULL;
+        return false;
+    }
+
+    // Same sequence as G_DoLoadGame.
+    savedleveltime = leveltime;
+    G_InitNew(gameskill, gameepisode, gamemap);
+    leveltime = savedleveltime;
+
+    P_UnArchivePlayers();
+    P_UnArchiveWorld();
+    P_UnArchiveThinkers();
+    P_UnArchiveSpecials();
+
+    ok = P_ReadSaveGameEOF();
+    r = fgetc(save_stream);
+    p = fgetc(save_stream);
+    ok = ok && r != EOF && p != EOF;
+    if (ok)
+    {
+        rndindex = r;
+        prndindex = p;
+        ok = ReadExtras() && !savegame_error;
+    }
+    fclose(save_stream);
+    save_stream = NULL;
+    return ok;
+}
+
+int dg_snapshot_load(const uint8_t* data, size_t len)
+{
+    uint8_t* before;
+    size_t before_len;
+
+    if (!CanSnapshot() || !Intact(data, len))
+    {
+        return -1;
+    }
+    // What to go back to should the snapshot not fit after all.
+    if (dg_snapshot_save(&before, &before_len) != 0)
+    {
+        return -1;
+    }
+    DG_MuteEvents(1);
+    if (!Restore(data, len))
+    {
+        Restore(before, before_len);
+        DG_MuteEvents(0);
+        free(before);
+        return -1;
+    }
+    DG_MuteEvents(0);
+    free(before);
+
+    // Jumping around inside a level shouldn't play the screen melt.
+    wipegamestate = GS_LEVEL;
+
+    if (setsizeneeded)
+    {
+        R_ExecuteSetViewSize();
+    }
+    R_FillBackScreen();
+    DG_StateChanged();
+    return 0;
+}
@@ -0,0 +1,97 @@
+// csrc/platform/state_hash.c
+// A checksum of the game state after a tic, for finding where a demo
+// desyncs: the RNG indices, the players, and every map object. Two runs of
+// the same demo agree on it tic for tic until they diverge.
+
+#include <stdint.h>
+
+#include "doomstat.h"
+#include "p_local.h"
+#include "dg_bridge.h"
+
+// Not exported through any header.
+extern int rndindex;
+extern int prndindex;
+
+// FNV-1a, 64-bit.
+static uint64_t hash;
+
+static void HashInt(int64_t value)
+{
+    uint64_t v = (uint64_t) value;
+
+    for (int i = 0; i < 8; ++i)
+    {
+        hash ^= (v >> (i * 8)) & 0xff;
+        hash *= 0x100000001b3ULL;
+    }
+}
+
+static void HashMobj(mobj_t *mo)
+{
+    HashInt(mo->type);
+    HashInt(mo->x);
+    HashInt(mo->y);
+    HashInt(mo->z);
+    HashInt(mo->angle);
+    HashInt(mo->momx);
+    HashInt(mo->momy);
+    HashInt(mo->momz);
+    HashInt(mo->health);
+    HashInt(mo->flags);
+    HashInt(mo->tics);
+    HashInt(mo->state ? mo->state - states : -1);
+}
+
+uint64_t dg_state_hash(void)
+{
+    thinker_t *th;
+
+    hash = 0xcbf29ce484222325ULL;
+
+    HashInt(gamestate);
+    HashInt(gameepisode);
+    HashInt(gamemap);
+    HashInt(leveltime);
+    HashInt(rndindex);
+    HashInt(prndindex);
+
+    for (int i = 0; i < MAXPLAYERS; ++i)
+    {
+        player_t *p = &players[i];
+
+        if (!playeringame[i])
+        {
+            continue;
+        }
+        HashInt(p->playerstate);
+        HashInt(p->health);
+        HashInt(p->armorpoints);
+        HashInt(p->readyweapon);
+        HashInt(p->killcount);
+        HashInt(p->itemcount);
+        HashInt(p->secretcount);
+        for (int a = 0; a < NUMAMMO; ++a)
+        {
+            HashInt(p->ammo[a]);
+        }
+        HashInt(p->mo != NULL);
+        if (p->mo)
+        {
+            HashMobj(p->mo);
+        }
+    }
+
+    if (gamestate == GS_LEVEL)
+    {
+        for (th = thinkercap.next; th != &thinkercap; th = th->next)
+        {
+            if (th->function.acp1 == (actionf_p1) P_MobjThinker)
+            {
+                HashMobj((mobj_t *) th);
+            }
+        }
+    }
+
+    return hash;
+}
@@ -0,0 +1,66 @@
+// csrc/platform/ticcmd.c
+// Player input supplied by the host as ticcmd fields, copied into the
+// ticcmds G_BuildTiccmd makes: per tic from a hook (dg_set_ticcmd_hook), or
+// one held for every tic (dg_set_ticcmd) until the host hands control back.
+
+#include <stddef.h>
+
+#include "doomgeneric.h"
+#include "doomstat.h"
+#include "dg_bridge.h"
+
+static dg_ticcmd_t host_cmd;
+static int host_cmd_set = 0;
+static dg_ticcmd_hook_t host_hook = NULL;
+
+void dg_set_ticcmd(const dg_ticcmd_t* cmd)
+{
+    if (cmd == NULL)
+    {
+        host_cmd_set = 0;
+        return;
+    }
+
+    host_cmd = *cmd;
+    host_cmd_set = 1;
+}
+
+void dg_set_ticcmd_hook(dg_ticcmd_hook_t hook)
+{
+    host_hook = hook;
+}
+
+int dg_world_paused(void)
+{
+    // P_Ticker's checks: paused, or in the menu of a single-player game.
+    return paused || (!netgame && menuactive && !demoplayback && players[consoleplayer].viewz != 1);
+}
+
+void DG_HostTiccmd(ticcmd_t *cmd, int tic)
+{
+    dg_ticcmd_t from_hook;
+    const dg_ticcmd_t *host;
+
+    // The hook starts from what the controls produced.
+    from_hook.forwardmove = cmd->forwardmove;
+    from_hook.sidemove = cmd->sidemove;
+    from_hook.angleturn = cmd->angleturn;
+    from_hook.buttons = cmd->buttons;
+    if (host_hook != NULL && host_hook(tic, &from_hook))
+    {
+        host = &from_hook;
+    }
+    else if (host_cmd_set)
+    {
+        host = &host_cmd;
+    }
+    else
+    {
+        return;
+    }
+
+    cmd->forwardmove = host->forwardmove;
+    cmd->sidemove = host->sidemove;
+    cmd->angleturn = host->angleturn;
+    cmd->buttons = host->buttons;
+}
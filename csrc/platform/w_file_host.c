@@ -0,0 +1,79 @@
+// csrc/platform/w_file_host.c
+// wad_file_class_t backed by the Rust host: WADs held in memory, pulled out
+// of zip/pk3 archives or embedded in the binary. Paths the host doesn't know
+// fall through to the stdio class.
+
+#include <stddef.h>
+#include <stdint.h>
+
+#include "w_file.h"
+#include "z_zone.h"
+
+// Implemented in Rust (src/vfs.rs). A handle is >= 0; -1 means "not mine".
+extern int    dg_vfs_open(const char* path, uint64_t* length);
+extern size_t dg_vfs_read(int handle, uint64_t offset, void* buffer, size_t len);
+extern void   dg_vfs_close(int handle);
+
+typedef struct
+{
+    wad_file_t wad;
+    int handle;
+} host_wad_file_t;
+
+extern wad_file_class_t host_wad_file;
+
+static wad_file_t *W_Host_OpenFile(char *path)
+{
+    uint64_t length = 0;
+    int handle = dg_vfs_open(path, &length);
+
+    if (handle < 0)
+    {
+        return NULL;
+    }
+
+    host_wad_file_t *result = Z_Malloc(sizeof(host_wad_file_t), PU_STATIC, 0);
+    result->wad.file_class = &host_wad_file;
+    result->wad.mapped = NULL;
+    result->wad.length = (unsigned int)length;
+    result->handle = handle;
+
+    return &result->wad;
+}
+
+static void W_Host_CloseFile(wad_file_t *wad)
+{
+    host_wad_file_t *host_wad = (host_wad_file_t *) wad;
+
+    dg_vfs_close(host_wad->handle);
+    Z_Free(host_wad);
+}
+
+static size_t W_Host_Read(wad_file_t *wad, unsigned int offset,
+                          void *buffer, size_t buffer_len)
+{
+    host_wad_file_t *host_wad = (host_wad_file_t *) wad;
+
+    return dg_vfs_read(host_wad->handle, offset, buffer, buffer_len);
+}
+
+// Lets M_FileExists accept virtual paths, so -iwad/-file lookups succeed.
+int W_HostFileExists(char *path)
+{
+    uint64_t length;
+    int handle = dg_vfs_open(path, &length);
+
+    if (handle < 0)
+    {
+        return 0;
+    }
+    dg_vfs_close(handle);
+    return 1;
+}
+
+wad_file_class_t host_wad_file =
+{
+    W_Host_OpenFile,
+    W_Host_CloseFile,
+    W_Host_Read,
+};
@@ -1,4 +1,5 @@
 #pragma once
+#include <stddef.h>
 #include <stdint.h>
 #ifdef __cplusplus
 extern "C" {
@@ -7,12 +8,217 @@ extern "C" {
 // Minimal creation: builds a tiny argv; if iwad_path != "", passes "-iwad <path>"
 int  dg_create_simple(const char* iwad_path);
 
-// Advance one engine tick/frame
+// Full command line (argv[0] included); the engine sees exactly these args
+int  dg_create(int argc, const char* const* argv);
+
+// Run on a virtual clock: time moves one tic per dg_tick and engine sleeps
+// return at once, so nothing waits on the wall clock. Call before dg_create.
+void dg_use_virtual_clock(void);
+
+// Advance the game one tic (the host paces it) and redraw
 void dg_tick(void);
 
 // Expose 32-bit framebuffer (default unless you build with CMAP256)
 const uint32_t* dg_framebuffer32(int* w, int* h);
 
+// Last title passed to DG_SetWindowTitle, or NULL if the engine hasn't set one
+const char* dg_window_title(void);
+
+// Start a single-player game at once (call between ticks), leaving the demo
+// loop or any game in progress. Skill is skill_t (0-4); `seed` (0-255) is
+// where the random number table starts, 0 as in vanilla. -1 for a bad skill
+// or a map the loaded WADs don't have.
+int dg_new_game(int skill, int episode, int map, int seed);
+
+// Player input as ticcmd fields.
+typedef struct {
+    int8_t  forwardmove;
+    int8_t  sidemove;
+    int16_t angleturn;
+    uint8_t buttons;    // BT_* in d_event.h
+} dg_ticcmd_t;
+
+// Use `cmd` as the console player's input for every tic from now on,
+// instead of the keyboard and mouse; NULL hands control back.
+void dg_set_ticcmd(const dg_ticcmd_t* cmd);
+
+// Asked for the console player's input as each tic's ticcmd is built; `tic`
+// is the gametic it will run in and `cmd` holds what the controls produced.
+// Fill in `cmd` and return 1 to use it, or return 0 to fall back on
+// dg_set_ticcmd's or the controls'. NULL turns it off.
+typedef int (*dg_ticcmd_hook_t)(int tic, dg_ticcmd_t* cmd);
+void dg_set_ticcmd_hook(dg_ticcmd_hook_t hook);
+
+// 1 if the world stands still this tic (paused, or the menu is open), as
+// of now: the ticcmd hook can ask mid-tic, unlike dg_game_state.
+int dg_world_paused(void);
+
+// Demo state (mirrors g_game.c globals).
+typedef struct {
+    int playback;   // 0/1, demoplayback
+    int recording;  // 0/1, demorecording
+    int finished;   // 0/1, a -record/-playdemo/-timedemo run is over
+    int gametics;   // -timedemo result, once finished
+    int realtics;
+    int tics;       // tics played back or recorded so far
+} dg_demo_status_t;
+
+void dg_demo_status(dg_demo_status_t* out);
+
+// Finish a recording early (the host is quitting) and write the .lmp.
+void dg_demo_end(void);
+
+// End-of-level statistics (statdump.c's capture of the intermission data).
+typedef struct {
+    int in;         // 0/1, player was in the game
+    int kills;
+    int items;
+    int secrets;
+    int frags[4];
+} dg_player_stats_t;
+
+typedef struct {
+    int episode;    // 1-based
+    int map;        // 1-based, the level just finished
+    int next;       // 1-based, the level the intermission leads to
+    int didsecret;  // 0/1, left through the secret exit
+    int maxkills;
+    int maxitems;
+    int maxsecret;
+    int time;       // tics
+    int partime;    // tics
+    int pnum;       // console player
+    dg_player_stats_t players[4];
+} dg_level_stats_t;
+
+// Capture statistics for every level from now on (off by default).
+void dg_stats_capture(int on);
+
+// Take the oldest captured level; 0 if there is none waiting.
+int  dg_stats_take(dg_level_stats_t* out);
+
+// A game event (DG_EV_* in doomgeneric.h for what the fields mean).
+typedef struct {
+    int kind;
+    int tic;        // gametic it happened in
+    int player;     // 0-3, or -1
+    int a;
+    int b;
+    int c;
+    char name[9];   // sprite name or empty
+} dg_event_t;
+
+typedef void (*dg_event_hook_t)(const dg_event_t* ev);
+
+// Called for every event as it happens, inside the tic; NULL turns it off.
+void dg_set_event_hook(dg_event_hook_t hook);
+
+// The game as of the end of the last tic, copied the first time it's asked
+// for after one (the host reads it between tics, and the ticcmd hook runs
+// before the tic does anything).
+typedef struct {
+    int gametic;
+    int mission;        // GameMission_t
+    int mode;           // GameMode_t
+    int gamestate;      // gamestate_t
+    int episode;
+    int map;
+    int skill;          // skill_t
+    int leveltime;
+    int paused;         // 0/1
+    int menu;           // 0/1, menu overlay active
+    int totalkills;     // level totals, as on the intermission screen
+    int totalitems;
+    int totalsecret;
+    // The console player (mirrors player_t and its mobj).
+    int ingame;         // 0/1, in a level
+    int dead;           // 0/1
+    int health;
+    int armor;
+    int armortype;      // 0 none, 1 green, 2 blue
+    int ammo[4];        // ammotype_t order: bullets, shells, cells, rockets
+    int maxammo[4];
+    int weapon;         // weapontype_t, the ready weapon
+    int weaponowned[9]; // 0/1 by weapontype_t
+    int cards[6];       // 0/1 by card_t: blue, yellow, red card, then skulls
+    int x;              // fixed_t, map units << 16
+    int y;
+    int z;
+    uint32_t angle;     // angle_t, 0 east, 0x40000000 north
+    int kills;
+    int items;
+    int secrets;
+    int mobjs;          // map objects in the copy (dg_mobjs)
+} dg_game_state_t;
+
+void dg_game_state(dg_game_state_t* out);
+
+// A map object (mirrors mobj_t).
+typedef struct {
+    int type;           // mobjtype_t
+    int doomednum;      // thing number in map editors, -1 for none
+    int x;              // fixed_t
+    int y;
+    int z;
+    uint32_t angle;     // angle_t
+    int health;
+    int flags;          // MF_* in p_mobj.h
+    char sprite[5];     // sprite name
+} dg_mobj_t;
+
+// Copy up to `max` map objects from the last tic into `out`, in thinker
+// order; returns how many there are in all.
+int dg_mobjs(dg_mobj_t* out, int max);
+
+// Checksum of the game state (RNG, players, map objects) as of the last tic.
+uint64_t dg_state_hash(void);
+
+// Queue a save into `slot` (written on the next tic); -1 if not in a level.
+int dg_save_game(int slot, const char* description);
+
+// Queue loading `slot` from the save directory; -1 if there is no such save.
+int dg_load_game(int slot);
+
+// Serialize the running level (savegame format plus what it leaves out, so
+// play continues exactly as it would have) into a malloc'd buffer; free it with dg_snapshot_free. -1 outside a playable level.
+int  dg_snapshot_save(uint8_t** data, size_t* len);
+void dg_snapshot_free(uint8_t* data);
+
+// Restore a buffer from dg_snapshot_save immediately (call between ticks).
+// -1, with the game as it was, outside a level or for a damaged buffer.
+int  dg_snapshot_load(const uint8_t* data, size_t len);
+
+// Engine configuration (default.cfg, extended.cfg). dg_config_load binds and
+// reads the variables from `dir` without starting the game, for tools; a
+// running game has already done that. Values are formatted as in the file.
+int  dg_config_load(const char* dir);
+// Name of the i-th variable, NULL past the end.
+const char* dg_config_name(int i);
+// -1 for an unknown variable.
+int  dg_config_get(const char* name, char* buf, size_t len);
+int  dg_config_set(const char* name, const char* value);
+// Write both files back.
+void dg_config_save(void);
+
+// DeHackEd tables. Field numbers follow the order the fields appear in the
+// engine structs (and in DeHackEd's own editor).
+enum {
+    DG_DEH_THING,   // mobjinfo[], 23 fields, doomednum .. raisestate
+    DG_DEH_FRAME,   // states[]: sprite, frame, tics, nextstate, misc1, misc2,
+                    // then the action pointer, copied from the frame given as value
+    DG_DEH_SOUND,   // S_sfx[]: priority
+    DG_DEH_WEAPON,  // weaponinfo[]: ammo, up, down, ready, attack, flash
+    DG_DEH_AMMO,    // maxammo[], clipammo[]
+    DG_DEH_MISC,    // deh_* globals in deh_misc.h order (index 0 only)
+};
+
+// Set one DeHackEd field. Returns 0, or -1 for a bad table/index/field.
+int dg_deh_set(int table, int index, int field, int value);
+
+// Replace a string as a patch's Text section does. Later engine calls to
+// DEH_AddStringReplacement don't undo it; both strings are copied.
+void dg_deh_text(const char* from_text, const char* to_text);
+
 #ifdef __cplusplus
 }
 #endif
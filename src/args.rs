@@ -0,0 +1,26 @@
+// args.rs
+//
+// The command line as Doom players type it.
+
+/// Accept old DOOM-style single-dash “long” flags like `-iwad`, `-file`, etc.
+pub fn normalize_doom_args<I, S>(iter: I) -> Vec<String>
+where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+{
+    let mut out = Vec::new();
+    for raw in iter {
+        let s: String = raw.into();
+        // Convert "-word" to "--word", but leave "-" and "--" alone.
+        if s.starts_with('-')
+            && !s.starts_with("--")
+            && s.len() > 2
+            && s.as_bytes()[1].is_ascii_alphabetic()
+        {
+            out.push(format!("-{}", s));
+        } else {
+            out.push(s);
+        }
+    }
+    out
+}
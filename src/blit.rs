@@ -0,0 +1,61 @@
+// blit.rs
+//
+// Scaling the engine's frame into a window's (or any other) pixel buffer.
+use crate::config::Scaler;
+
+/// Nearest-neighbor scaling, sized by `scaler` and centered.
+/// dst: window backbuffer (row-major 0x00RRGGBB), size dw*dh
+/// src: Doom framebuffer (row-major), size sw*sh
+pub fn blit_nn(dst: &mut [u32], dw: usize, dh: usize,
+               src: &[u32], sw: usize, sh: usize, scaler: Scaler) {
+    if dw == 0 || dh == 0 || sw == 0 || sh == 0 { return; }
+
+    let (tw, th) = match scaler {
+        Scaler::Stretch => (dw, dh),
+        // Whole multiples only; a window smaller than 1x falls back to fit.
+        Scaler::Integer if dw >= sw && dh >= sh => {
+            let k = (dw / sw).min(dh / sh);
+            (sw * k, sh * k)
+        }
+        // Choose target size that fits inside window and preserves aspect.
+        // Compare dw/sh vs dh/sw without floats.
+        _ if dw * sh <= dh * sw => {
+            // limited by width
+            let tw = dw;
+            let th = (dw * sh) / sw;
+            (tw, th)
+        }
+        _ => {
+            // limited by height
+            let th = dh;
+            let tw = (dh * sw) / sh;
+            (tw, th)
+        }
+    };
+
+    // A sliver of a window can round the short side down to nothing.
+    let (tw, th) = (tw.max(1), th.max(1));
+
+    // Letterbox offsets
+    let x0 = (dw - tw) / 2;
+    let y0 = (dh - th) / 2;
+
+    // Clear to black
+    dst.fill(0x0000_0000);
+
+    // Source column for each target column, exact so whole multiples
+    // repeat every pixel the same number of times.
+    let cols: Vec<usize> = (0..tw).map(|x| x * sw / tw).collect();
+
+    for y in 0..th {
+        let sy = y * sh / th;
+        let src_row = &src[sy * sw .. (sy + 1) * sw];
+
+        let dst_row_start = (y0 + y) * dw + x0;
+        let dst_row = &mut dst[dst_row_start .. dst_row_start + tw];
+
+        for (dpx, &sx) in dst_row.iter_mut().zip(&cols) {
+            *dpx = src_row[sx];
+        }
+    }
+}
@@ -1,11 +1,23 @@
-use clap::Parser;
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand, ValueEnum};
 
 #[derive(Parser, Debug)]
-#[command(author, version, about)]
+#[command(author, version, about, args_conflicts_with_subcommands = true)]
 pub struct Cli {
-    // DOOM arguments. Only iwad does anything
+    #[command(subcommand)] pub command: Option<Command>,
+
+    // DOOM arguments. Only iwad, file, deh, the merges, savegames and demos reach the engine so far
+    /// IWAD file, directory to search, or .zip/.pk3 containing one
     #[arg(long)] pub iwad: Option<String>,
+    /// PWADs to load; .zip/.pk3 archives load every WAD inside
     #[arg(long, num_args = 1..)] pub file: Vec<String>,
+    /// Merge PWAD sprites and flats into the IWAD, like deutex -merge
+    #[arg(long, num_args = 1..)] pub merge: Vec<String>,
+    /// Replace IWAD sprites and flats in place, like NWT -merge
+    #[arg(long, num_args = 1..)] pub nwtmerge: Vec<String>,
+    /// DeHackEd patches, applied after the IWAD's and before DEHACKED lumps in PWADs
+    #[arg(long, num_args = 1..)] pub deh: Vec<String>,
     #[arg()] pub positional_iwad: Option<String>, // Fallback IWAD if given as bare positional arg
     #[arg(long, num_args = 1..=2)] pub warp: Vec<u8>,
     #[arg(long)] pub skill: Option<u8>,
@@ -13,33 +25,177 @@ pub struct Cli {
     #[arg(long)] pub respawn: bool,
     #[arg(long)] pub fast: bool,
     #[arg(long)] pub nomonsters: bool,
+    /// Directory for savegames (default: ~/.local/share/rustydoom/savegames/<iwad>)
+    #[arg(long)] pub savedir: Option<PathBuf>,
+    /// Load the game saved in this slot (0-5) on startup
+    #[arg(long, value_parser = clap::value_parser!(u8).range(0..6))] pub loadgame: Option<u8>,
+    /// Record a demo to NAME.lmp; ends when you quit or press q
     #[arg(long)] pub record: Option<String>,
+    /// Play back a demo file or lump, then quit
     #[arg(long)] pub playdemo: Option<String>,
+    /// Play back a demo as fast as possible and print the timing as JSON
     #[arg(long)] pub timedemo: Option<String>,
-    #[arg(long, default_value_t = 640)] pub width: u32,
-    #[arg(long, default_value_t = 400)] pub height: u32,
+    /// Write a per-tic state hash for --record/--playdemo to DEMO.hashes next to the demo
+    #[arg(long)] pub hashes: bool,
+    /// Replay a demo without a window and check it against its DEMO.hashes
+    #[arg(long, conflicts_with_all = ["record", "playdemo", "timedemo"])] pub verifydemo: Option<String>,
+    /// Play tool-assisted from a timeline file, created if missing (from --warp and --skill)
+    #[arg(long, conflicts_with_all = ["record", "playdemo", "timedemo", "verifydemo", "loadgame"])] pub tas: Option<PathBuf>,
+    /// Append each finished level's kills, items, secrets and time to this file as JSON lines
+    #[arg(long)] pub stats_out: Option<PathBuf>,
+    /// Write game events (level start/finish, damage, deaths, pickups, kills, secrets, weapon changes) as JSON lines
+    #[arg(long)] pub events_out: Option<PathBuf>,
+    /// Directory for rustydoom.toml, default.cfg and extended.cfg (default: ~/.config/rustydoom)
+    #[arg(long)] pub configdir: Option<PathBuf>,
+    /// Window size, overriding window.width/window.height in rustydoom.toml
+    #[arg(long)] pub width: Option<u32>,
+    #[arg(long)] pub height: Option<u32>,
     #[arg(long)] pub fullscreen: bool,
+    /// Seconds of play to keep for rewinding with Backspace (0 turns it off)
+    #[arg(long, default_value_t = 60)] pub rewind: usize,
+    /// Show game, map, skill and pause state in the window title
+    #[arg(long)] pub title_status: bool,
+    /// Also write every frame that ran a tic to this directory as numbered PNGs, to make a video of
+    #[arg(long)] pub dump_frames: Option<PathBuf>,
+    /// Where to play: a window, the terminal (works over SSH) or the Linux framebuffer; the last two need no display server
+    #[arg(long, value_enum, default_value_t = Frontend::Window)] pub frontend: Frontend,
+    /// Framebuffer device for --frontend fbdev
+    #[arg(long, default_value = "/dev/fb0")] pub fbdev: PathBuf,
+    /// Input devices for --frontend fbdev (default: every readable /dev/input/event*)
+    #[arg(long, num_args = 1..)] pub evdev: Vec<PathBuf>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Frontend {
+    Window,
+    Terminal,
+    Fbdev,
 }
 
-/// Accept old DOOM-style single-dash “long” flags like `-iwad`, `-file`, etc.
-pub fn normalize_doom_args<I, S>(iter: I) -> Vec<String>
-where
-    I: IntoIterator<Item = S>,
-    S: Into<String>,
-{
-    let mut out = Vec::new();
-    for raw in iter {
-        let s: String = raw.into();
-        // Convert "-word" to "--word", but leave "-" and "--" alone.
-        if s.starts_with('-')
-            && !s.starts_with("--")
-            && s.len() > 2
-            && s.as_bytes()[1].is_ascii_alphabetic()
-        {
-            out.push(format!("-{}", s));
-        } else {
-            out.push(s);
-        }
-    }
-    out
-}
\ No newline at end of file
+// Tools that run instead of the game.
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Inspect WAD files and extract or convert their lumps
+    Wad {
+        #[command(subcommand)] action: WadCommand,
+    },
+    /// Inspect savegames
+    Saves {
+        #[command(subcommand)] action: SavesCommand,
+    },
+    /// Read and change settings: host ones (section.name) and engine variables
+    Config {
+        #[command(subcommand)] action: ConfigCommand,
+    },
+    /// Inspect demo (.lmp) files
+    Demo {
+        #[command(subcommand)] action: DemoCommand,
+    },
+    /// Run a Rhai script against the game without a window; exits non-zero if an assert fails
+    Script {
+        script: PathBuf,
+        /// IWAD file, directory to search, or .zip/.pk3 containing one
+        #[arg(long)] iwad: Option<String>,
+        /// PWADs to load; .zip/.pk3 archives load every WAD inside
+        #[arg(long, num_args = 1..)] file: Vec<String>,
+        /// DeHackEd patches
+        #[arg(long, num_args = 1..)] deh: Vec<String>,
+    },
+    /// Run the map regression cases in a manifest, headless and in parallel
+    Test {
+        manifest: PathBuf,
+        /// Cases to run at once (default: one per CPU)
+        #[arg(short, long)] jobs: Option<usize>,
+        /// Also write a JUnit XML report here
+        #[arg(long)] junit: Option<PathBuf>,
+        /// Write the frames the golden PNGs should hold instead of comparing
+        #[arg(long)] update_golden: bool,
+        /// Run just this case, in this process (what each worker does)
+        #[arg(long, hide = true)] case: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum WadCommand {
+    /// List lumps with size, offset and detected type
+    List { wad: PathBuf },
+    /// Write one lump's raw bytes to a file
+    Extract {
+        wad: PathBuf,
+        lump: String,
+        /// Output file (default: <LUMP>.lmp, "-" for stdout)
+        #[arg(short, long)] output: Option<PathBuf>,
+    },
+    /// Convert graphics to PNG, sounds to WAV and music to MIDI
+    Export {
+        wad: PathBuf,
+        /// Only these lumps (default: everything convertible)
+        lumps: Vec<String>,
+        #[arg(short, long, default_value = ".")] out_dir: PathBuf,
+        /// Take PLAYPAL from this WAD (e.g. the IWAD) when exporting a PWAD without one
+        #[arg(long)] palette: Option<PathBuf>,
+    },
+    /// Identify the WAD and show its maps and checksums
+    Info { wad: PathBuf },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SavesCommand {
+    /// Show description, map, skill and version of each saved slot
+    List {
+        /// Save directory (default: the one for the IWAD)
+        #[arg(long)] savedir: Option<PathBuf>,
+        /// IWAD the saves belong to (default: the usual search)
+        #[arg(long)] iwad: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Print one setting's value
+    Get {
+        /// `section.name` for rustydoom.toml, or an engine variable like mouse_sensitivity
+        key: String,
+        #[arg(long)] configdir: Option<PathBuf>,
+    },
+    /// Change one setting
+    Set {
+        key: String,
+        value: String,
+        #[arg(long)] configdir: Option<PathBuf>,
+    },
+    /// Print every setting with its current value
+    List {
+        #[arg(long)] configdir: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum DemoCommand {
+    /// Show version, map, skill, players and length, and check the end marker
+    Info {
+        #[arg(required = true)] demos: Vec<PathBuf>,
+        /// IWAD the demos are for, to name maps ExMy or MAPxx (default: neither)
+        #[arg(long)] iwad: Option<String>,
+    },
+    /// Turn a TAS timeline into a vanilla demo
+    Export {
+        timeline: PathBuf,
+        /// Output demo (default: the timeline's name with .lmp)
+        #[arg(short, long)] output: Option<PathBuf>,
+    },
+    /// Rewrite tics of a TAS timeline, keeping the rest
+    Edit {
+        timeline: PathBuf,
+        /// First tic to rewrite, from 1
+        tic: usize,
+        /// The new input as a timeline line, `forward side turn buttons [xN]`; xN rewrites N tics
+        #[arg(allow_hyphen_values = true)] input: String,
+    },
+    /// Turn a single-player demo into a TAS timeline to edit
+    Import {
+        demo: PathBuf,
+        /// Output timeline (default: the demo's name with .tas)
+        #[arg(short, long)] output: Option<PathBuf>,
+    },
+}
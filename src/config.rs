@@ -0,0 +1,176 @@
+// config.rs
+//
+// rustydoom.toml: settings that belong to the host rather than the engine
+// (window, scaler, audio switches, hotkeys). The engine keeps its own
+// variables in default.cfg and extended.cfg in the same directory; the binary
+// reads and writes those through the engine's m_config.c.
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, bail};
+use serde::{Deserialize, Serialize};
+use toml::{Table, Value};
+
+use crate::constants::{INITIAL_HEIGHT, INITIAL_WIDTH};
+use crate::paths;
+
+pub const FILE_NAME: &str = "rustydoom.toml";
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HostConfig {
+    pub window: Window,
+    pub video: Video,
+    pub audio: Audio,
+    pub bindings: Bindings,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Window {
+    pub width: u32,
+    pub height: u32,
+    pub fullscreen: bool,
+    /// Show game, map, skill and pause state in the title.
+    pub title_status: bool,
+}
+
+impl Default for Window {
+    fn default() -> Self {
+        Window { width: INITIAL_WIDTH, height: INITIAL_HEIGHT, fullscreen: false, title_status: false }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Video {
+    pub scaler: Scaler,
+}
+
+/// How the 320x200 frame is fitted to the window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Scaler {
+    /// As large as fits, aspect ratio kept, black bars around.
+    #[default]
+    Fit,
+    /// Largest whole multiple that fits, so every pixel is the same size.
+    Integer,
+    /// Fill the window, aspect ratio ignored.
+    Stretch,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Audio {
+    pub sfx: bool,
+    pub music: bool,
+}
+
+impl Default for Audio {
+    fn default() -> Self {
+        Audio { sfx: true, music: true }
+    }
+}
+
+/// Host hotkeys, by winit key name ("F6", "Backspace", "Pause", ...). The
+/// engine never sees these keys.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Bindings {
+    pub quicksave: String,
+    pub quickload: String,
+    pub rewind: String,
+    pub fullscreen: String,
+    pub pause: String,
+    /// Advance one tic while paused.
+    pub step: String,
+    /// Cycle 1x, 2x, 4x and max speed.
+    pub fast_forward: String,
+    /// --tas: write the controls into the timeline instead of following it.
+    pub tas_record: String,
+    /// --tas: write the timeline file.
+    pub tas_save: String,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Bindings {
+            quicksave: "F6".into(),
+            quickload: "F9".into(),
+            rewind: "Backspace".into(),
+            fullscreen: "F11".into(),
+            pause: "Pause".into(),
+            step: "F7".into(),
+            fast_forward: "F8".into(),
+            tas_record: "Insert".into(),
+            tas_save: "Home".into(),
+        }
+    }
+}
+
+impl HostConfig {
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        Ok(toml::from_str(text)?)
+    }
+
+    /// Defaults if the file doesn't exist yet.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text).with_context(|| format!("parsing {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("reading {}", path.display())),
+        }
+    }
+
+    /// Writes every setting, so the file doubles as a list of what can be set.
+    /// Comments in an existing file are not kept.
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let text = toml::to_string(self)?;
+        std::fs::write(path, text).with_context(|| format!("writing {}", path.display()))
+    }
+
+    /// Every setting as `section.name`, sorted.
+    pub fn keys() -> Vec<String> {
+        Self::default()
+            .table()
+            .iter()
+            .flat_map(|(section, v)| {
+                v.as_table().into_iter().flat_map(move |t| t.keys().map(move |k| format!("{}.{}", section, k)))
+            })
+            .collect()
+    }
+
+    /// A setting's value as you'd type it to `set`; None for an unknown key.
+    pub fn get(&self, key: &str) -> Option<String> {
+        let (section, name) = key.split_once('.')?;
+        Some(match self.table().get(section)?.get(name)? {
+            Value::String(s) => s.clone(),
+            v => v.to_string(),
+        })
+    }
+
+    /// Set `section.name` from text, checked against the setting's type.
+    pub fn set(&mut self, key: &str, value: &str) -> anyhow::Result<()> {
+        let mut table = self.table();
+        let slot = key
+            .split_once('.')
+            .and_then(|(section, name)| table.get_mut(section)?.as_table_mut()?.get_mut(name));
+        let Some(slot) = slot else { bail!("unknown setting {}", key) };
+        *slot = match slot {
+            Value::Boolean(_) => Value::Boolean(value.parse().with_context(|| format!("{} is true or false", key))?),
+            Value::Integer(_) => Value::Integer(value.parse().with_context(|| format!("{} is a number", key))?),
+            _ => Value::String(value.to_string()),
+        };
+        *self = table.try_into().with_context(|| format!("bad value for {}: {}", key, value))?;
+        Ok(())
+    }
+
+    fn table(&self) -> Table {
+        Table::try_from(self).expect("host config serializes to a table")
+    }
+}
+
+/// `~/.config/rustydoom`, home of rustydoom.toml and the engine's config files.
+pub fn default_dir() -> Option<PathBuf> {
+    Some(paths::config_home()?.join("rustydoom"))
+}
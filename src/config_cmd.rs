@@ -0,0 +1,124 @@
+// config_cmd.rs
+//
+// Settings from the host side: where they live, the engine variables behind
+// m_config.c, and `rustydoom config get/set/list`. Host settings are
+// `section.name` keys in rustydoom.toml; anything without a dot is an engine
+// variable, stored by the engine in default.cfg or extended.cfg.
+use std::ffi::{CStr, CString};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, bail};
+use libc::c_char;
+use rustydoom::config::{self, HostConfig};
+
+use crate::cli::ConfigCommand;
+use crate::dg_io::Hotkeys;
+use crate::sys;
+
+/// `--configdir` if given, else `~/.config/rustydoom`; created if missing.
+pub fn resolve_dir(configdir: Option<&Path>) -> anyhow::Result<PathBuf> {
+    let dir = match configdir {
+        Some(d) => d.to_path_buf(),
+        None => config::default_dir().context("no config directory: pass --configdir or set HOME/XDG_CONFIG_HOME")?,
+    };
+    std::fs::create_dir_all(&dir).with_context(|| format!("creating {}", dir.display()))?;
+    Ok(dir)
+}
+
+pub fn load_host(dir: &Path) -> anyhow::Result<HostConfig> {
+    let host = HostConfig::load(&dir.join(config::FILE_NAME))?;
+    Hotkeys::from_bindings(&host.bindings)?;
+    Ok(host)
+}
+
+/// Write the engine's config files back; a running game calls this on exit.
+pub fn save_engine() {
+    unsafe { sys::raw::dg_config_save() };
+}
+
+fn load_engine(dir: &Path) -> anyhow::Result<()> {
+    let dir = CString::new(dir.to_string_lossy().as_bytes())?;
+    if unsafe { sys::raw::dg_config_load(dir.as_ptr()) } != 0 {
+        bail!("engine config couldn't be loaded");
+    }
+    Ok(())
+}
+
+fn engine_names() -> Vec<String> {
+    (0..)
+        .map_while(|i| {
+            let name = unsafe { sys::raw::dg_config_name(i) };
+            // SAFETY: names point into the engine's static variable tables.
+            (!name.is_null()).then(|| unsafe { CStr::from_ptr(name) }.to_string_lossy().into_owned())
+        })
+        .collect()
+}
+
+fn engine_get(name: &str) -> Option<String> {
+    let name = CString::new(name).ok()?;
+    let mut buf = [0 as c_char; 256];
+    if unsafe { sys::raw::dg_config_get(name.as_ptr(), buf.as_mut_ptr(), buf.len()) } != 0 {
+        return None;
+    }
+    // SAFETY: dg_config_get always NUL-terminates within `buf`.
+    Some(unsafe { CStr::from_ptr(buf.as_ptr()) }.to_string_lossy().into_owned())
+}
+
+fn engine_set(name: &str, value: &str) -> anyhow::Result<()> {
+    let (cname, cvalue) = (CString::new(name)?, CString::new(value)?);
+    if unsafe { sys::raw::dg_config_set(cname.as_ptr(), cvalue.as_ptr()) } != 0 {
+        bail!("unknown setting {}", name);
+    }
+    Ok(())
+}
+
+pub fn run(cmd: ConfigCommand) -> anyhow::Result<()> {
+    match cmd {
+        ConfigCommand::Get { key, configdir } => get(&resolve_dir(configdir.as_deref())?, &key),
+        ConfigCommand::Set { key, value, configdir } => set(&resolve_dir(configdir.as_deref())?, &key, &value),
+        ConfigCommand::List { configdir } => list(&resolve_dir(configdir.as_deref())?),
+    }
+}
+
+fn get(dir: &Path, key: &str) -> anyhow::Result<()> {
+    let value = if key.contains('.') {
+        load_host(dir)?.get(key)
+    } else {
+        load_engine(dir)?;
+        engine_get(key)
+    };
+    let Some(value) = value else { bail!("unknown setting {}", key) };
+    println!("{}", value);
+    Ok(())
+}
+
+fn set(dir: &Path, key: &str, value: &str) -> anyhow::Result<()> {
+    if key.contains('.') {
+        let mut host = load_host(dir)?;
+        host.set(key, value)?;
+        Hotkeys::from_bindings(&host.bindings)?;
+        host.save(&dir.join(config::FILE_NAME))
+    } else {
+        load_engine(dir)?;
+        engine_set(key, value)?;
+        save_engine();
+        Ok(())
+    }
+}
+
+fn list(dir: &Path) -> anyhow::Result<()> {
+    let host = load_host(dir)?;
+    load_engine(dir)?;
+
+    let mut out = io::stdout().lock();
+    writeln!(out, "# {}", dir.join(config::FILE_NAME).display())?;
+    for key in HostConfig::keys() {
+        writeln!(out, "{:<30} {}", key, host.get(&key).unwrap_or_default())?;
+    }
+    writeln!(out, "# engine: {}, {}", dir.join("default.cfg").display(), dir.join("extended.cfg").display())?;
+    for name in engine_names() {
+        writeln!(out, "{:<30} {}", name, engine_get(&name).unwrap_or_default())?;
+    }
+    Ok(())
+}
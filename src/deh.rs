@@ -0,0 +1,270 @@
+// deh.rs
+//
+// DeHackEd patch parser. Turns a .deh file or DEHACKED lump into a list of
+// table writes and string replacements that the binary hands to the engine
+// (csrc/platform/deh_host.c). Follows what Chocolate Doom accepts from
+// vanilla-style patches; anything else (BEX sections, cheats, sprite
+// sections, unknown fields) is collected as a warning rather than dropped.
+use std::fmt;
+
+/// Engine tables a patch can write to. The discriminants are the `DG_DEH_*`
+/// values in dg_bridge.h.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Table {
+    Thing = 0,
+    Frame = 1,
+    Sound = 2,
+    Weapon = 3,
+    Ammo = 4,
+    Misc = 5,
+}
+
+const THING_FIELDS: &[&str] = &[
+    "ID #", "Initial frame", "Hit points", "First moving frame", "Alert sound",
+    "Reaction time", "Attack sound", "Injury frame", "Pain chance", "Pain sound",
+    "Close attack frame", "Far attack frame", "Death frame", "Exploding frame",
+    "Death sound", "Speed", "Width", "Height", "Mass", "Missile damage",
+    "Action sound", "Bits", "Respawn frame",
+];
+const FRAME_FIELDS: &[&str] = &[
+    "Sprite number", "Sprite subnumber", "Duration", "Next frame", "Unknown 1", "Unknown 2",
+];
+/// The frame's code pointer, set from Pointer sections.
+const FRAME_ACTION: usize = 6;
+const SOUND_FIELDS: &[&str] = &["Value"];
+const WEAPON_FIELDS: &[&str] = &[
+    "Ammo type", "Deselect frame", "Select frame", "Bobbing frame", "Shooting frame", "Firing frame",
+];
+const AMMO_FIELDS: &[&str] = &["Max ammo", "Per ammo"];
+const MISC_FIELDS: &[&str] = &[
+    "Initial Health", "Initial Bullets", "Max Health", "Max Armor", "Green Armor Class",
+    "Blue Armor Class", "Max Soulsphere", "Soulsphere Health", "Megasphere Health",
+    "God Mode Health", "IDFA Armor", "IDFA Armor Class", "IDKFA Armor", "IDKFA Armor Class",
+    "BFG Cells/Shot", "Monsters Infight",
+];
+
+impl Table {
+    pub fn name(self) -> &'static str {
+        match self {
+            Table::Thing => "Thing",
+            Table::Frame => "Frame",
+            Table::Sound => "Sound",
+            Table::Weapon => "Weapon",
+            Table::Ammo => "Ammo",
+            Table::Misc => "Misc",
+        }
+    }
+
+    /// Index as written in a patch; things are numbered from 1 there.
+    pub fn patch_index(self, index: i32) -> i32 {
+        if self == Table::Thing { index + 1 } else { index }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// Write `value` to field number `field` of entry `index` (0-based).
+    Set { line: usize, table: Table, index: i32, field: usize, value: i32 },
+    /// Make the engine use `to` wherever it looks up `from`.
+    Text { line: usize, from: String, to: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Patch {
+    pub changes: Vec<Change>,
+    pub warnings: Vec<Warning>,
+}
+
+enum Section {
+    None,
+    Fields { table: Table, index: i32, fields: &'static [&'static str], first_field: usize },
+    /// Recognised but unsupported; its body is skipped (already warned about).
+    Skip,
+    /// Unsupported BEX section, which runs to the next header rather than the
+    /// next blank line.
+    Bex,
+}
+
+/// Parse a patch. Never fails: whatever can't be applied ends up in `warnings`.
+pub fn parse(data: &[u8]) -> Patch {
+    let mut patch = Patch::default();
+    let mut section = Section::None;
+    let mut pos = 0;
+    let mut line_no = 0;
+
+    while pos < data.len() {
+        let end = data[pos..].iter().position(|&b| b == b'\n').map_or(data.len(), |n| pos + n);
+        let raw = String::from_utf8_lossy(&data[pos..end]).into_owned();
+        pos = (end + 1).min(data.len());
+        line_no += 1;
+
+        let line = raw.trim();
+        if line.starts_with('#') {
+            continue;
+        }
+        if line.is_empty() {
+            if !matches!(section, Section::Bex) {
+                section = Section::None;
+            }
+            continue;
+        }
+        if matches!(section, Section::Bex) && line.contains('=') {
+            continue;
+        }
+
+        if let Some((name, value)) = line.split_once('=') {
+            patch.assign(&section, line_no, name.trim(), value.trim());
+            continue;
+        }
+
+        // Anything that isn't an assignment starts a new section.
+        let mut words = line.split_whitespace();
+        let kind = words.next().unwrap_or_default().to_ascii_lowercase();
+        let nums: Vec<i32> = line
+            .split(|c: char| !c.is_ascii_digit() && c != '-')
+            .filter_map(|w| w.parse().ok())
+            .collect();
+        let num = |i: usize| nums.get(i).copied();
+        let mut warnings = Vec::new();
+        let mut warn = |message: String| warnings.push(Warning { line: line_no, message });
+
+        section = match (kind.as_str(), num(0)) {
+            ("patch", _) if line.to_ascii_lowercase().starts_with("patch file for dehacked") => Section::None,
+            ("thing", Some(n)) => fields(Table::Thing, n - 1, THING_FIELDS),
+            ("frame", Some(n)) => fields(Table::Frame, n, FRAME_FIELDS),
+            ("sound", Some(n)) => fields(Table::Sound, n, SOUND_FIELDS),
+            ("weapon", Some(n)) => fields(Table::Weapon, n, WEAPON_FIELDS),
+            ("ammo", Some(n)) => fields(Table::Ammo, n, AMMO_FIELDS),
+            ("misc", _) => fields(Table::Misc, 0, MISC_FIELDS),
+            // "Pointer 12 (Frame 34)": the frame in parentheses is the one changed.
+            ("pointer", _) => match num(1) {
+                Some(frame) => Section::Fields {
+                    table: Table::Frame,
+                    index: frame,
+                    fields: &["Codep Frame"],
+                    first_field: FRAME_ACTION,
+                },
+                None => {
+                    warn(format!("malformed Pointer header '{}'", line));
+                    Section::Skip
+                }
+            },
+            ("text", Some(from_len)) if num(1).is_some_and(|n| n >= 0) && from_len >= 0 => {
+                let to_len = num(1).unwrap_or_default() as usize;
+                let (text, used, lines) = read_text(&data[pos..], from_len as usize + to_len);
+                if text.len() < from_len as usize + to_len {
+                    warn("Text section runs past the end of the patch".to_string());
+                } else {
+                    let (from, to) = text.split_at(from_len as usize);
+                    patch.changes.push(Change::Text {
+                        line: line_no,
+                        from: String::from_utf8_lossy(from).into_owned(),
+                        to: String::from_utf8_lossy(to).into_owned(),
+                    });
+                }
+                pos += used;
+                line_no += lines;
+                Section::Skip
+            }
+            ("cheat", _) => {
+                warn("Cheat sections are not supported".to_string());
+                Section::Skip
+            }
+            ("sprite", _) => {
+                warn("Sprite sections are not supported".to_string());
+                Section::Skip
+            }
+            ("include", _) => {
+                warn("Include is not supported".to_string());
+                Section::Skip
+            }
+            _ if line.starts_with('[') => {
+                warn(format!("BEX section {} is not supported", line));
+                Section::Bex
+            }
+            _ => {
+                warn(format!("unknown section '{}'", line));
+                Section::Skip
+            }
+        };
+        patch.warnings.append(&mut warnings);
+    }
+    patch
+}
+
+fn fields(table: Table, index: i32, fields: &'static [&'static str]) -> Section {
+    Section::Fields { table, index, fields, first_field: 0 }
+}
+
+/// Take `len` bytes of Text section body, ignoring carriage returns. Returns
+/// the text, how many input bytes it used and how many newlines it crossed.
+fn read_text(data: &[u8], len: usize) -> (Vec<u8>, usize, usize) {
+    let mut text = Vec::with_capacity(len);
+    let mut used = 0;
+    for &b in data {
+        if text.len() == len {
+            break;
+        }
+        used += 1;
+        if b != b'\r' {
+            text.push(b);
+        }
+    }
+    let lines = text.iter().filter(|&&b| b == b'\n').count();
+    (text, used, lines)
+}
+
+impl Patch {
+    fn assign(&mut self, section: &Section, line: usize, name: &str, value: &str) {
+        let mut warn = |message: String| self.warnings.push(Warning { line, message });
+        let &Section::Fields { table, index, fields, first_field } = section else {
+            // Header lines before the first section; nothing else belongs here.
+            if let Section::None = section
+                && !name.eq_ignore_ascii_case("Doom version")
+                && !name.eq_ignore_ascii_case("Patch format")
+            {
+                warn(format!("'{}' outside of any section", name));
+            }
+            return;
+        };
+        let label = format!("{} {}", table.name(), table.patch_index(index));
+
+        let Some(field) = fields.iter().position(|f| f.eq_ignore_ascii_case(name)) else {
+            warn(format!("{}: unsupported field '{}'", label, name));
+            return;
+        };
+        let Some(mut value) = parse_number(value) else {
+            warn(format!("{}: '{}' is not a number ('{}')", label, value, name));
+            return;
+        };
+        if table == Table::Misc && fields[field] == "Monsters Infight" {
+            value = match value {
+                202 => 0,
+                221 => 1,
+                _ => {
+                    warn(format!("Misc: Monsters Infight must be 202 or 221, not {}", value));
+                    return;
+                }
+            };
+        }
+        self.changes.push(Change::Set { line, table, index, field: first_field + field, value });
+    }
+}
+
+/// Decimal, possibly negative. Bits values above i32::MAX wrap like the C int they land in.
+fn parse_number(s: &str) -> Option<i32> {
+    let n: i64 = s.parse().ok()?;
+    (i32::MIN as i64..=u32::MAX as i64).contains(&n).then_some(n as i32)
+}
@@ -0,0 +1,79 @@
+// dehacked.rs
+//
+// Applies DeHackEd patches to the engine before it starts. Parsing lives in
+// the library (rustydoom::deh); this side reads the patches in Chocolate
+// Doom's order (IWAD lump, -deh files, PWAD lumps) and writes them through
+// dg_deh_set / dg_deh_text in csrc/platform/deh_host.c.
+use std::ffi::CString;
+
+use anyhow::Context;
+use libc::c_int;
+use rustydoom::deh::{self, Change};
+use rustydoom::iwad::Iwad;
+use rustydoom::wad::Wad;
+
+use crate::{sys, vfs};
+
+pub fn load_all(iwad: &Iwad, deh_files: &[String], pwads: &[String]) -> anyhow::Result<()> {
+    let iwad_path = iwad.path.to_string_lossy();
+    let wad = vfs::read_wad(&iwad_path)?;
+    if let Some(i) = wad.find("DEHACKED") {
+        apply(&format!("{}:DEHACKED", iwad_path), wad.lump(i).unwrap_or_default());
+    }
+
+    for f in deh_files {
+        let data = std::fs::read(f).with_context(|| format!("reading DeHackEd patch {}", f))?;
+        apply(f, &data);
+    }
+
+    for f in pwads {
+        // The engine loads a file that isn't a WAD as a single lump and
+        // reports one it can't open; neither is a reason to stop here.
+        let wad = match vfs::read_wad(f) {
+            Ok(wad) => wad,
+            Err(e) => {
+                eprintln!(" not looking for DeHackEd lumps in {}: {:#}", f, e);
+                continue;
+            }
+        };
+        for i in dehacked_lumps(&wad) {
+            apply(&format!("{}:DEHACKED", f), wad.lump(i).unwrap_or_default());
+        }
+    }
+    Ok(())
+}
+
+fn dehacked_lumps(wad: &Wad) -> Vec<usize> {
+    (0..wad.lumps().len()).filter(|&i| wad.lumps()[i].name == "DEHACKED").collect()
+}
+
+/// Apply one patch. Problems are reported and skipped, never fatal, as a
+/// partly applied patch is what the vanilla loader would have given too.
+fn apply(label: &str, data: &[u8]) {
+    let patch = deh::parse(data);
+    println!(" loading DeHackEd patch {} ({} changes)", label, patch.changes.len());
+
+    let mut warnings: Vec<String> = patch.warnings.iter().map(|w| w.to_string()).collect();
+    for change in &patch.changes {
+        match change {
+            &Change::Set { line, table, index, field, value } => {
+                let rc = unsafe { sys::raw::dg_deh_set(table as c_int, index, field as c_int, value) };
+                if rc != 0 {
+                    warnings.push(format!(
+                        "line {}: {} {} is out of range",
+                        line,
+                        table.name(),
+                        table.patch_index(index)
+                    ));
+                }
+            }
+            Change::Text { line, from, to } => match (CString::new(from.as_str()), CString::new(to.as_str())) {
+                (Ok(from), Ok(to)) => unsafe { sys::raw::dg_deh_text(from.as_ptr(), to.as_ptr()) },
+                _ => warnings.push(format!("line {}: Text contains a NUL byte", line)),
+            },
+        }
+    }
+    for w in warnings {
+        eprintln!("  {}: {}", label, w);
+    }
+}
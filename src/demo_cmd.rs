@@ -0,0 +1,115 @@
+// demo_cmd.rs
+//
+// `rustydoom demo ...`: look inside demo files before playing them, and
+// convert between demos and TAS timelines.
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, bail};
+use rustydoom::iwad;
+use rustydoom::lmp::{Demo, End};
+use rustydoom::timeline::Timeline;
+
+use crate::cli::DemoCommand;
+use crate::title;
+
+const PLAYER_COLORS: [&str; 4] = ["green", "indigo", "brown", "red"];
+
+pub fn run(cmd: DemoCommand) -> anyhow::Result<()> {
+    match cmd {
+        DemoCommand::Info { demos, iwad } => info(&demos, iwad.as_deref()),
+        DemoCommand::Export { timeline, output } => export(&timeline, output),
+        DemoCommand::Edit { timeline, tic, input } => edit(&timeline, tic, &input),
+        DemoCommand::Import { demo, output } => import(&demo, output),
+    }
+}
+
+fn export(path: &Path, output: Option<PathBuf>) -> anyhow::Result<()> {
+    let timeline = Timeline::read(path)?;
+    let output = output.unwrap_or_else(|| path.with_extension("lmp"));
+    std::fs::write(&output, timeline.to_demo().to_bytes()).with_context(|| format!("writing {}", output.display()))?;
+    println!("{} tics written to {}", timeline.tics.len(), output.display());
+    Ok(())
+}
+
+fn edit(path: &Path, tic: usize, input: &str) -> anyhow::Result<()> {
+    let mut timeline = Timeline::read(path)?;
+    let Some(first) = tic.checked_sub(1) else { bail!("tics count from 1") };
+    let count = timeline.set(first, input)?;
+    timeline.write(path)?;
+    println!("{}: tics {}-{} rewritten, {} in all", path.display(), tic, tic + count - 1, timeline.tics.len());
+    Ok(())
+}
+
+fn import(path: &Path, output: Option<PathBuf>) -> anyhow::Result<()> {
+    let timeline = Timeline::from_demo(&Demo::read(path)?).with_context(|| format!("converting {}", path.display()))?;
+    let output = output.unwrap_or_else(|| path.with_extension("tas"));
+    timeline.write(&output)?;
+    println!("{} tics written to {}", timeline.tics.len(), output.display());
+    Ok(())
+}
+
+fn info(paths: &[impl AsRef<Path>], iwad: Option<&str>) -> anyhow::Result<()> {
+    // Only needed to tell ExMy from MAPxx.
+    let commercial = iwad.map(|p| iwad::locate(Some(p), None)).transpose()?.map(|i| i.game.is_commercial());
+
+    let mut out = io::stdout().lock();
+    let mut failed = 0;
+    for (i, path) in paths.iter().enumerate() {
+        let path = path.as_ref();
+        if i > 0 {
+            writeln!(out)?;
+        }
+        writeln!(out, "File:     {}", path.display())?;
+        match Demo::read(path) {
+            Ok(demo) => describe(&mut out, &demo, commercial)?,
+            Err(e) => {
+                writeln!(out, "Error:    {:#}", e)?;
+                failed += 1;
+            }
+        }
+    }
+    if failed > 0 {
+        bail!("{} of {} demos couldn't be read", failed, paths.len());
+    }
+    Ok(())
+}
+
+fn describe(out: &mut impl Write, demo: &Demo, commercial: Option<bool>) -> anyhow::Result<()> {
+    let h = &demo.header;
+    let version = h.version.map_or_else(String::new, |v| format!(" ({})", v));
+    let note = if h.playable() { "" } else { ", won't play here (needs 1.9)" };
+    writeln!(out, "Version:  {}{}{}", h.version_name(), version, note)?;
+    let skill = title::SKILL_NAMES.get(h.skill as usize).copied().unwrap_or("?");
+    writeln!(out, "Map:      {}, skill {} ({})", h.map_name(commercial), h.skill + 1, skill)?;
+
+    let mode = match (h.deathmatch, h.player_count()) {
+        (0, 1) => "single player".to_string(),
+        (0, _) => "cooperative".to_string(),
+        (1, _) => "deathmatch".to_string(),
+        (2, _) => "altdeath".to_string(),
+        (n, _) => format!("deathmatch mode {}", n),
+    };
+    let flags: Vec<&str> = [(h.respawn, "-respawn"), (h.fast, "-fast"), (h.nomonsters, "-nomonsters")]
+        .into_iter()
+        .filter_map(|(on, flag)| on.then_some(flag))
+        .collect();
+    writeln!(out, "Mode:     {}{}{}", mode, if flags.is_empty() { "" } else { " " }, flags.join(" "))?;
+
+    let players: Vec<&str> = (0..4).filter(|&p| h.players[p]).map(|p| PLAYER_COLORS[p]).collect();
+    let view = PLAYER_COLORS.get(h.consoleplayer as usize).copied().unwrap_or("?");
+    writeln!(out, "Players:  {} ({}), viewed as {}", players.len(), players.join(", "), view)?;
+
+    let d = demo.duration();
+    let (secs, centis) = (d.as_secs(), d.subsec_millis() / 10);
+    writeln!(out, "Length:   {} tics, {}:{:02}.{:02}", demo.tics.len(), secs / 60, secs % 60, centis)?;
+    match demo.end {
+        End::Marker { trailing: 0 } => writeln!(out, "End:      marker ok")?,
+        End::Marker { trailing } => writeln!(out, "End:      marker ok, {} more bytes after it", trailing)?,
+        End::Missing { partial: 0 } => writeln!(out, "End:      no end marker (truncated?)")?,
+        End::Missing { partial } => {
+            writeln!(out, "End:      no end marker, last tic cut off after {} bytes (truncated?)", partial)?
+        }
+    }
+    Ok(())
+}
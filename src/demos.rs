@@ -0,0 +1,73 @@
+// demos.rs
+//
+// -record, -playdemo and -timedemo from the host side: the engine arguments,
+// noticing when the run is over, and the timedemo report.
+use serde::Serialize;
+
+use crate::cli::Cli;
+use crate::sys::{self, DgDemoStatus};
+
+const TICRATE: f64 = 35.0;
+
+/// Printed to stdout as one JSON line when a timedemo completes.
+#[derive(Serialize)]
+struct TimedemoReport<'a> {
+    timedemo: &'a str,
+    gametics: i32,
+    realtics: i32,
+    fps: f64,
+}
+
+pub fn engine_args(cli: &Cli) -> Vec<String> {
+    let mut args = Vec::new();
+    // The engine adds .lmp itself when recording.
+    if let Some(name) = &cli.record {
+        args.push("-record".to_string());
+        args.push(name.strip_suffix(".lmp").unwrap_or(name).to_string());
+    }
+    if let Some(name) = &cli.playdemo {
+        args.push("-playdemo".to_string());
+        args.push(name.clone());
+    }
+    if let Some(name) = &cli.timedemo {
+        args.push("-timedemo".to_string());
+        args.push(name.clone());
+    }
+    // Verifying is an ordinary playback, just without the window.
+    if let Some(name) = &cli.verifydemo {
+        args.push("-playdemo".to_string());
+        args.push(name.clone());
+    }
+    args
+}
+
+pub fn status() -> DgDemoStatus {
+    let mut st = DgDemoStatus::default();
+    unsafe { sys::raw::dg_demo_status(&mut st) };
+    st
+}
+
+/// The demo the game was started for has ended; time to quit.
+pub fn finished() -> bool {
+    status().finished != 0
+}
+
+/// On the way out: write a recording still in progress, and report a
+/// completed timedemo.
+pub fn finish(cli: &Cli) -> anyhow::Result<()> {
+    let st = status();
+    if st.recording != 0 {
+        unsafe { sys::raw::dg_demo_end() };
+    }
+    if let Some(name) = &cli.timedemo {
+        if st.finished == 0 {
+            eprintln!("timedemo {} stopped before the end", name);
+            return Ok(());
+        }
+        let fps = if st.realtics > 0 { st.gametics as f64 * TICRATE / st.realtics as f64 } else { 0.0 };
+        let fps = (fps * 1000.0).round() / 1000.0;
+        let report = TimedemoReport { timedemo: name, gametics: st.gametics, realtics: st.realtics, fps };
+        println!("{}", serde_json::to_string(&report)?);
+    }
+    Ok(())
+}
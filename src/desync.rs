@@ -0,0 +1,104 @@
+// desync.rs
+//
+// Per-tic state hashes for demos. With --hashes they are collected after
+// every tic while a demo records or plays and written next to it;
+// --verifydemo replays a demo without a window and reports the first tic
+// whose state differs from the hashes written earlier.
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::bail;
+use rustydoom::lmp::{self, StateHashes};
+
+use crate::cli::Cli;
+use crate::demos;
+use crate::sys;
+
+const TICRATE: usize = 35;
+
+static HASHES: Mutex<Option<Vec<u64>>> = Mutex::new(None);
+
+/// Start collecting if this run wants hashes. Call before the first tick.
+pub fn init(cli: &Cli) {
+    if cli.hashes || cli.verifydemo.is_some() {
+        *HASHES.lock().unwrap() = Some(Vec::new());
+    }
+}
+
+/// Call after each engine tick; hashes the state once per demo tic.
+pub fn after_tick() {
+    let mut hashes = HASHES.lock().unwrap();
+    let Some(hashes) = hashes.as_mut() else { return };
+    let tics = demos::status().tics.max(0) as usize;
+    if tics > hashes.len() {
+        let hash = unsafe { sys::raw::dg_state_hash() };
+        hashes.resize(tics, hash);
+    }
+}
+
+fn collected() -> StateHashes {
+    StateHashes(HASHES.lock().unwrap().clone().unwrap_or_default())
+}
+
+/// The demo file the hashes belong with: NAME.lmp for -record NAME.
+fn demo_path(cli: &Cli) -> Option<PathBuf> {
+    if let Some(name) = &cli.record {
+        return Some(PathBuf::from(format!("{}.lmp", name.strip_suffix(".lmp").unwrap_or(name))));
+    }
+    cli.playdemo.as_ref().or(cli.verifydemo.as_ref()).map(PathBuf::from)
+}
+
+/// On the way out of a --hashes run: write what was collected next to the demo.
+pub fn finish(cli: &Cli) -> anyhow::Result<()> {
+    if !cli.hashes {
+        return Ok(());
+    }
+    let Some(demo) = demo_path(cli) else { bail!("--hashes needs --record or --playdemo") };
+    let path = lmp::hashes_path(&demo);
+    let hashes = collected();
+    hashes.write(&path)?;
+    println!("{} tic hashes written to {}", hashes.0.len(), path.display());
+    Ok(())
+}
+
+/// Play the --verifydemo demo through `tick` (false once it's over) and
+/// compare every tic with the demo's hash file.
+pub fn verify(cli: &Cli, mut tick: impl FnMut() -> bool) -> anyhow::Result<()> {
+    let Some(demo) = demo_path(cli) else { return Ok(()) };
+    let expected = StateHashes::read(&lmp::hashes_path(&demo))?;
+    // Stop at the first difference or once past the end of the hashes,
+    // checking only the tics each tick adds.
+    let mut checked = 0;
+    'play: while tick() {
+        let hashes = HASHES.lock().unwrap();
+        let got = hashes.as_deref().unwrap_or_default();
+        for (i, hash) in got.iter().enumerate().skip(checked) {
+            if expected.0.get(i) != Some(hash) {
+                break 'play;
+            }
+        }
+        checked = got.len();
+    }
+    report(&demo, &expected, &collected())
+}
+
+fn report(demo: &Path, expected: &StateHashes, got: &StateHashes) -> anyhow::Result<()> {
+    let Some(i) = expected.first_mismatch(got) else {
+        println!("{}: all {} tics match", demo.display(), expected.0.len());
+        return Ok(());
+    };
+    let time = |tic: usize| format!("{}:{:02}.{:02}", tic / TICRATE / 60, tic / TICRATE % 60, tic % TICRATE * 100 / TICRATE);
+    match (expected.0.get(i), got.0.get(i)) {
+        (Some(want), Some(have)) => bail!(
+            "{}: desync at tic {} ({}) of {}: expected {:016x}, got {:016x}",
+            demo.display(),
+            i + 1,
+            time(i + 1),
+            expected.0.len(),
+            want,
+            have
+        ),
+        (Some(_), None) => bail!("{}: demo ended after {} tics, expected {}", demo.display(), got.0.len(), expected.0.len()),
+        _ => bail!("{}: demo runs past the {} hashed tics", demo.display(), expected.0.len()),
+    }
+}
@@ -6,15 +6,86 @@ use winit::{
 };
 use std::rc::Rc;
 
+use anyhow::Context;
+use rustydoom::config::Bindings;
+
+/// Host hotkeys from `[bindings]` in rustydoom.toml.
+#[derive(Clone, Copy)]
+pub struct Hotkeys {
+    pub quicksave: KeyCode,
+    pub quickload: KeyCode,
+    pub rewind: KeyCode,
+    pub fullscreen: KeyCode,
+    pub pause: KeyCode,
+    pub step: KeyCode,
+    pub fast_forward: KeyCode,
+    pub tas_record: KeyCode,
+    pub tas_save: KeyCode,
+}
+
+impl Hotkeys {
+    pub fn from_bindings(b: &Bindings) -> anyhow::Result<Self> {
+        let key = |what: &str, name: &str| {
+            key_code(name).with_context(|| format!("bindings.{}: unknown key {:?}", what, name))
+        };
+        Ok(Hotkeys {
+            quicksave: key("quicksave", &b.quicksave)?,
+            quickload: key("quickload", &b.quickload)?,
+            rewind: key("rewind", &b.rewind)?,
+            fullscreen: key("fullscreen", &b.fullscreen)?,
+            pause: key("pause", &b.pause)?,
+            step: key("step", &b.step)?,
+            fast_forward: key("fast_forward", &b.fast_forward)?,
+            tas_record: key("tas_record", &b.tas_record)?,
+            tas_save: key("tas_save", &b.tas_save)?,
+        })
+    }
+
+    /// Run the host action bound to `code`, if any; false when it's a key
+    /// for the engine. Fullscreen is the window's business, not this.
+    pub fn press(&self, host: &impl DoomHost, code: KeyCode) -> bool {
+        match code {
+            c if c == self.quicksave => host.quick_save(),
+            c if c == self.quickload => host.quick_load(),
+            // Held down, key repeat keeps scrubbing further back.
+            c if c == self.rewind => host.rewind(),
+            // Playback controls, for demos as much as for play.
+            c if c == self.pause => host.toggle_pause(),
+            c if c == self.step => host.step(),
+            c if c == self.fast_forward => host.fast_forward(),
+            c if c == self.tas_record => host.tas_record(),
+            c if c == self.tas_save => host.tas_save(),
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// Keys a hotkey can be bound to, by winit name. Keys the engine uses for
+/// play and menus are left out.
+fn key_code(name: &str) -> Option<KeyCode> {
+    use KeyCode::*;
+    const KEYS: &[(&str, KeyCode)] = &[
+        ("F1", F1), ("F2", F2), ("F3", F3), ("F4", F4), ("F5", F5), ("F6", F6),
+        ("F7", F7), ("F8", F8), ("F9", F9), ("F10", F10), ("F11", F11), ("F12", F12),
+        ("Backspace", Backspace), ("Tab", Tab), ("Insert", Insert), ("Delete", Delete),
+        ("Home", Home), ("End", End), ("PageUp", PageUp), ("PageDown", PageDown),
+        ("Pause", Pause), ("PrintScreen", PrintScreen), ("ScrollLock", ScrollLock),
+        ("Backquote", Backquote), ("Minus", Minus), ("Equal", Equal),
+    ];
+    KEYS.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|&(_, k)| k)
+}
+
 pub struct DgIo {
     pub is_fullscreen: bool,
     pub mouse_captured: bool,
     pub mods: ModifiersState,
+    pub hotkeys: Hotkeys,
 }
 
 impl DgIo {
-    pub fn new() -> Self {
-        Self { is_fullscreen: false, mouse_captured: false, mods: ModifiersState::empty() }
+    pub fn new(hotkeys: Hotkeys) -> Self {
+        Self { is_fullscreen: false, mouse_captured: false, mods: ModifiersState::empty(), hotkeys }
     }
 
     // NOTE: take a host now
@@ -26,23 +97,29 @@ impl DgIo {
         }
     }
 
+    #[allow(clippy::collapsible_match)]
     fn handle_window(&mut self, host: &impl DoomHost, window: &Rc<Window>, ev: &WindowEvent) {
         match ev {
             WindowEvent::ModifiersChanged(m) => self.mods = m.state(),
 
             WindowEvent::KeyboardInput { event: KeyEvent { state, physical_key, .. }, .. } => {
                 let pressed = *state == ElementState::Pressed;
-                let alt_enter = matches!(physical_key, PhysicalKey::Code(KeyCode::Enter)) && self.mods.alt_key();
-                let f11       = matches!(physical_key, PhysicalKey::Code(KeyCode::F11));
+                let code = match physical_key { PhysicalKey::Code(c) => Some(*c), _ => None };
+                let alt_enter = code == Some(KeyCode::Enter) && self.mods.alt_key();
+                let fs_key    = code == Some(self.hotkeys.fullscreen);
                 #[cfg(target_os = "macos")]
                 let cmd_ctrl_f = matches!(physical_key, PhysicalKey::Code(KeyCode::KeyF)) && self.mods.super_key() && self.mods.control_key();
                 #[cfg(not(target_os = "macos"))]
                 let cmd_ctrl_f = false;
 
-                if pressed && (alt_enter || f11 || cmd_ctrl_f) {
+                if pressed && (alt_enter || fs_key || cmd_ctrl_f) {
                     self.toggle_fullscreen(window);
                     return;
                 }
+                // Host hotkeys; the engine never sees them.
+                if pressed && let Some(c) = code && self.hotkeys.press(host, c) {
+                    return;
+                }
                 if pressed && matches!(physical_key, PhysicalKey::Code(KeyCode::Escape)) {
                     self.set_mouse_capture(window, false);
                 }
@@ -95,7 +172,7 @@ impl DgIo {
         }
     }
 
-    fn toggle_fullscreen(&mut self, window: &Rc<Window>) {
+    pub fn toggle_fullscreen(&mut self, window: &Rc<Window>) {
         self.is_fullscreen = !self.is_fullscreen;
         if self.is_fullscreen {
             let mon = window.current_monitor();
@@ -151,21 +228,32 @@ pub trait DoomHost {
     fn mouse_move_rel(&self, dx: f32, dy: f32);
     fn mouse_move_abs(&self, x: f32, y: f32);
     fn mouse_wheel(&self, lines: f32);
+    fn quick_save(&self);
+    fn quick_load(&self);
+    fn rewind(&self);
+    fn toggle_pause(&self);
+    fn step(&self);
+    fn fast_forward(&self);
+    fn tas_record(&self);
+    fn tas_save(&self);
 }
 
-// keycodes…
-pub const DG_KEY_UP: i32 = 1;
-pub const DG_KEY_DOWN: i32 = 2;
-pub const DG_KEY_LEFT: i32 = 3;
-pub const DG_KEY_RIGHT: i32 = 4;
-pub const DG_KEY_W: i32 = 5;
-pub const DG_KEY_A: i32 = 6;
-pub const DG_KEY_S: i32 = 7;
-pub const DG_KEY_D: i32 = 8;
-pub const DG_KEY_SPACE: i32 = 9;
-pub const DG_KEY_ENTER: i32 = 10;
-pub const DG_KEY_ESCAPE: i32 = 11;
-pub const DG_KEY_UNKNOWN: i32 = 0;
+// keycodes… (the bridge's old codes; nothing sends these now)
+#[allow(dead_code)]
+pub mod keycodes {
+    pub const DG_KEY_UP: i32 = 1;
+    pub const DG_KEY_DOWN: i32 = 2;
+    pub const DG_KEY_LEFT: i32 = 3;
+    pub const DG_KEY_RIGHT: i32 = 4;
+    pub const DG_KEY_W: i32 = 5;
+    pub const DG_KEY_A: i32 = 6;
+    pub const DG_KEY_S: i32 = 7;
+    pub const DG_KEY_D: i32 = 8;
+    pub const DG_KEY_SPACE: i32 = 9;
+    pub const DG_KEY_ENTER: i32 = 10;
+    pub const DG_KEY_ESCAPE: i32 = 11;
+    pub const DG_KEY_UNKNOWN: i32 = 0;
+}
 
 // Host sentinels (must match the C bridge)
 pub const DGK_ENTER:  i32 = 1000;
@@ -176,4 +264,6 @@ pub const DGK_LEFT:   i32 = 1102;
 pub const DGK_RIGHT:  i32 = 1103;
 pub const DGK_USE:    i32 = 1200; // space -> use (optional)
 pub const DGK_FIRE:   i32 = 1201; // LCtrl -> fire (optional)
+pub const DGK_RUN:    i32 = 1202; // Shift -> run
+pub const DGK_STRAFE: i32 = 1203; // Alt -> strafe
 
@@ -0,0 +1,430 @@
+// env.rs
+//
+// A gym-style environment over the engine for reinforcement learning.
+// `reset` starts a map, `step` holds an action for `frame_skip` tics and
+// returns what the agent sees (the framebuffer, downscaled and optionally
+// grey, plus the player's numbers), the reward earned and whether the
+// episode is over. Input goes in as ticcmds, not key presses, and the engine
+// runs headless on a virtual clock: a step takes as long as the simulation
+// does, and the same map, seed and actions always play out the same game.
+//
+// The engine is a single set of C globals, so a process gets one environment.
+use std::ffi::CString;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use anyhow::{Context, bail};
+use libc::c_int;
+
+use crate::events::Weapon;
+use crate::input;
+use crate::iwad;
+use crate::lmp::Ticcmd;
+use crate::state::{self, GameState};
+use crate::sys;
+
+static STARTED: AtomicBool = AtomicBool::new(false);
+
+// G_BuildTiccmd's speeds: walking, running.
+const FORWARD_MOVE: [i8; 2] = [0x19, 0x32];
+const SIDE_MOVE: [i8; 2] = [0x18, 0x28];
+const ANGLE_TURN: [i16; 2] = [640, 1280];
+
+#[derive(Debug, Clone)]
+pub struct EnvConfig {
+    /// Found the usual way (`DOOMWADDIR`, ...) when None.
+    pub iwad: Option<PathBuf>,
+    /// PWADs loaded on top.
+    pub files: Vec<PathBuf>,
+    /// Where the engine reads default.cfg from. By default a directory of
+    /// its own under the temp dir, so a player's settings (screen size,
+    /// messages) don't change what the agent sees.
+    pub configdir: Option<PathBuf>,
+    /// Tics each step holds its action for.
+    pub frame_skip: u32,
+    pub observation: ObservationConfig,
+    pub rewards: Rewards,
+    /// Cut episodes off after this many tics.
+    pub max_tics: Option<u32>,
+}
+
+impl Default for EnvConfig {
+    fn default() -> Self {
+        EnvConfig {
+            iwad: None,
+            files: Vec::new(),
+            configdir: None,
+            frame_skip: 4,
+            observation: ObservationConfig::default(),
+            rewards: Rewards::default(),
+            max_tics: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ObservationConfig {
+    /// Shrink the 640x400 framebuffer by this factor, averaging each block.
+    pub scale: usize,
+    /// One luma channel instead of RGB.
+    pub greyscale: bool,
+}
+
+impl Default for ObservationConfig {
+    fn default() -> Self {
+        ObservationConfig { scale: 2, greyscale: false }
+    }
+}
+
+/// What each change in the game is worth. Everything is summed per tic.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rewards {
+    pub kill: f32,
+    pub item: f32,
+    pub secret: f32,
+    /// Per point of health gained; health lost counts against.
+    pub health: f32,
+    pub death: f32,
+    pub level_finish: f32,
+    /// Every tic survived; a small negative value hurries the agent along.
+    pub tic: f32,
+}
+
+impl Default for Rewards {
+    fn default() -> Self {
+        Rewards { kill: 1.0, item: 0.1, secret: 1.0, health: 0.01, death: -5.0, level_finish: 10.0, tic: 0.0 }
+    }
+}
+
+impl Rewards {
+    /// The reward for one tic that took the player from `before` to `after`.
+    pub fn score(&self, before: &GameVars, after: &GameVars, level_finished: bool) -> f32 {
+        let mut r = self.tic;
+        r += self.kill * (after.kills - before.kills) as f32;
+        r += self.item * (after.items - before.items) as f32;
+        r += self.secret * (after.secrets - before.secrets) as f32;
+        r += self.health * (after.health.max(0) - before.health.max(0)) as f32;
+        if after.dead && !before.dead {
+            r += self.death;
+        }
+        if level_finished {
+            r += self.level_finish;
+        }
+        r
+    }
+}
+
+/// The console player's numbers, as the status bar shows them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct GameVars {
+    pub health: i32,
+    pub armor: i32,
+    /// Bullets, shells, cells, rockets.
+    pub ammo: [i32; 4],
+    pub weapon: Option<Weapon>,
+    pub kills: i32,
+    pub items: i32,
+    pub secrets: i32,
+    pub dead: bool,
+}
+
+impl GameVars {
+    fn from_state(st: &GameState) -> Self {
+        let p = &st.player;
+        GameVars {
+            health: p.health,
+            armor: p.armor,
+            ammo: [p.ammo.bullets, p.ammo.shells, p.ammo.cells, p.ammo.rockets],
+            weapon: p.weapon,
+            kills: p.kills,
+            items: p.items,
+            secrets: p.secrets,
+            dead: p.dead,
+        }
+    }
+}
+
+/// The framebuffer as the agent gets it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Screen {
+    pub width: usize,
+    pub height: usize,
+    /// 3 for RGB, 1 for greyscale.
+    pub channels: usize,
+    /// Row-major, channels interleaved.
+    pub pixels: Vec<u8>,
+}
+
+impl Screen {
+    /// Shrink a 0x00RRGGBB framebuffer of `w`x`h` as `config` says. Edge
+    /// pixels that don't fill a whole block are dropped.
+    pub fn capture(fb: &[u32], w: usize, h: usize, config: &ObservationConfig) -> Self {
+        let k = config.scale.max(1);
+        let (width, height) = (w / k, h / k);
+        let channels = if config.greyscale { 1 } else { 3 };
+        let mut pixels = Vec::with_capacity(width * height * channels);
+        let area = (k * k) as u32;
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = [0u32; 3];
+                for sy in y * k..y * k + k {
+                    for &px in &fb[sy * w + x * k..sy * w + x * k + k] {
+                        sum[0] += (px >> 16) & 0xff;
+                        sum[1] += (px >> 8) & 0xff;
+                        sum[2] += px & 0xff;
+                    }
+                }
+                let [r, g, b] = sum.map(|c| c / area);
+                if config.greyscale {
+                    // ITU-R BT.601 luma.
+                    pixels.push(((r * 299 + g * 587 + b * 114) / 1000) as u8);
+                } else {
+                    pixels.extend([r as u8, g as u8, b as u8]);
+                }
+            }
+        }
+        Screen { width, height, channels, pixels }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Observation {
+    pub screen: Screen,
+    pub vars: GameVars,
+}
+
+/// Keys held down for a step, turned into the ticcmd the engine would build
+/// for them (without the slow start keyboard turning has).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Controls {
+    pub forward: bool,
+    pub backward: bool,
+    pub turn_left: bool,
+    pub turn_right: bool,
+    pub strafe_left: bool,
+    pub strafe_right: bool,
+    pub run: bool,
+    pub attack: bool,
+    pub use_: bool,
+    pub weapon: Option<Weapon>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Discrete(Controls),
+    /// Movement as fractions of running speed (forward and strafe right
+    /// positive, -1.0..=1.0) and turning in degrees per tic, left positive.
+    Continuous { forward: f32, strafe: f32, turn: f32, attack: bool, use_: bool },
+    /// Exact ticcmd fields.
+    Ticcmd(Ticcmd),
+}
+
+impl Action {
+    pub fn to_ticcmd(&self) -> Ticcmd {
+        match *self {
+            Action::Discrete(c) => c.to_ticcmd(),
+            Action::Continuous { forward, strafe, turn, attack, use_ } => {
+                let scale = |v: f32, max: i8| (v.clamp(-1.0, 1.0) * max as f32).round() as i8;
+                Ticcmd {
+                    forwardmove: scale(forward, FORWARD_MOVE[1]),
+                    sidemove: scale(strafe, SIDE_MOVE[1]),
+                    angleturn: (turn.clamp(-180.0, 180.0) / 360.0 * 65536.0).round().clamp(-32768.0, 32767.0) as i16,
+                    buttons: buttons(attack, use_, None),
+                }
+            }
+            Action::Ticcmd(cmd) => cmd,
+        }
+    }
+}
+
+impl Controls {
+    pub fn to_ticcmd(&self) -> Ticcmd {
+        let speed = self.run as usize;
+        let axis = |plus: bool, minus: bool| plus as i8 - minus as i8;
+        Ticcmd {
+            forwardmove: axis(self.forward, self.backward) * FORWARD_MOVE[speed],
+            sidemove: axis(self.strafe_right, self.strafe_left) * SIDE_MOVE[speed],
+            angleturn: axis(self.turn_left, self.turn_right) as i16 * ANGLE_TURN[speed],
+            buttons: buttons(self.attack, self.use_, self.weapon),
+        }
+    }
+}
+
+fn buttons(attack: bool, use_: bool, weapon: Option<Weapon>) -> u8 {
+    let mut b = 0;
+    if attack {
+        b |= Ticcmd::ATTACK;
+    }
+    if use_ {
+        b |= Ticcmd::USE;
+    }
+    if let Some(weapon) = weapon {
+        // The super shotgun has no slot of its own: P_PlayerThink picks it
+        // for the shotgun slot when the player owns one.
+        let slot = match weapon {
+            Weapon::SuperShotgun => Weapon::Shotgun as u8,
+            w => w as u8,
+        };
+        b |= Ticcmd::CHANGE | (slot << Ticcmd::WEAPON_SHIFT);
+    }
+    b
+}
+
+/// Why the episode's last step ended it, and where things stand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Info {
+    /// Tics played since `reset`.
+    pub tics: u32,
+    pub dead: bool,
+    pub level_finished: bool,
+    /// Stopped by `max_tics` rather than by the game.
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    pub observation: Observation,
+    pub reward: f32,
+    pub done: bool,
+    pub info: Info,
+}
+
+pub struct DoomEnv {
+    config: EnvConfig,
+    commercial: bool,
+    vars: GameVars,
+    info: Info,
+    done: bool,
+}
+
+impl DoomEnv {
+    /// Boot the engine headless. Fails if this process already has.
+    pub fn new(config: EnvConfig) -> anyhow::Result<Self> {
+        if config.frame_skip == 0 {
+            bail!("frame_skip must be at least 1");
+        }
+        let iwad = iwad::locate(config.iwad.as_ref().map(|p| p.to_string_lossy()).as_deref(), None)?;
+        let configdir = config.configdir.clone().unwrap_or_else(|| std::env::temp_dir().join("rustydoom-env"));
+        let mut args = vec![
+            "rustydoom".to_string(),
+            "-iwad".to_string(),
+            iwad.path.to_string_lossy().into_owned(),
+            "-configdir".to_string(),
+            configdir.to_string_lossy().into_owned(),
+            "-nosfx".to_string(),
+            "-nomusic".to_string(),
+        ];
+        if !config.files.is_empty() {
+            args.push("-file".to_string());
+            args.extend(config.files.iter().map(|f| f.to_string_lossy().into_owned()));
+        }
+        let c_args = args.iter().map(|a| CString::new(a.as_str())).collect::<Result<Vec<_>, _>>()?;
+        let argv: Vec<*const libc::c_char> = c_args.iter().map(|a| a.as_ptr()).collect();
+
+        if STARTED.swap(true, Ordering::SeqCst) {
+            bail!("the engine is already running in this process; there can only be one environment");
+        }
+        let rc = unsafe {
+            sys::raw::dg_use_virtual_clock();
+            sys::raw::dg_create(argv.len() as c_int, argv.as_ptr())
+        };
+        if rc != 0 {
+            bail!("dg_create failed: {}", rc);
+        }
+        // The agent is the only source of input from here on.
+        input::hold(Ticcmd::default());
+        Ok(DoomEnv {
+            config,
+            commercial: iwad.game.is_commercial(),
+            vars: GameVars::default(),
+            info: Info::default(),
+            done: true,
+        })
+    }
+
+    /// Start a new episode on `map` ("E1M1", "MAP01") at `skill` 1-5. `seed`
+    /// is where the engine's 256-entry random table starts; 0 is vanilla.
+    pub fn reset(&mut self, map: &str, skill: u8, seed: u8) -> anyhow::Result<Observation> {
+        let (episode, map_num) = parse_map(map, self.commercial)
+            .with_context(|| format!("bad map {:?}, expected {}", map, if self.commercial { "MAPxx" } else { "ExMy" }))?;
+        if !(1..=5).contains(&skill) {
+            bail!("skill must be 1-5, not {}", skill);
+        }
+        let rc = unsafe { sys::raw::dg_new_game(skill as c_int - 1, episode, map_num, seed as c_int) };
+        if rc != 0 {
+            bail!("{} isn't in the loaded WADs", map.to_uppercase());
+        }
+        // One idle tic gets the level running and drawn.
+        self.tick(&Ticcmd::default());
+        self.vars = GameVars::from_state(&state::game_state());
+        self.info = Info::default();
+        self.done = false;
+        Ok(self.observe())
+    }
+
+    /// Hold `action` for `frame_skip` tics, or until the episode ends. Once
+    /// it has, further steps change nothing until the next `reset`.
+    pub fn step(&mut self, action: &Action) -> Step {
+        let cmd = action.to_ticcmd();
+        let mut reward = 0.0;
+        for _ in 0..self.config.frame_skip {
+            if self.done {
+                break;
+            }
+            self.tick(&cmd);
+            let st = state::game_state();
+            let vars = GameVars::from_state(&st);
+            // The engine leaves GS_LEVEL for the intermission.
+            let level_finished = !st.in_level;
+            reward += self.config.rewards.score(&self.vars, &vars, level_finished);
+            self.vars = vars;
+            self.info.tics += 1;
+            self.info.dead = vars.dead;
+            self.info.level_finished = level_finished;
+            self.info.truncated = self.config.max_tics.is_some_and(|max| self.info.tics >= max);
+            self.done = self.info.dead || self.info.level_finished || self.info.truncated;
+        }
+        Step { observation: self.observe(), reward, done: self.done, info: self.info }
+    }
+
+    pub fn config(&self) -> &EnvConfig {
+        &self.config
+    }
+
+    fn tick(&self, cmd: &Ticcmd) {
+        input::hold(*cmd);
+        unsafe { sys::raw::dg_tick() };
+    }
+
+    fn observe(&self) -> Observation {
+        let mut w: c_int = 0;
+        let mut h: c_int = 0;
+        let ptr = unsafe { sys::raw::dg_framebuffer32(&mut w, &mut h) };
+        let (w, h) = (w.max(0) as usize, h.max(0) as usize);
+        let fb: &[u32] = if ptr.is_null() {
+            &[]
+        } else {
+            // SAFETY: the engine's framebuffer is w*h pixels and lives for the whole run.
+            unsafe { std::slice::from_raw_parts(ptr, w * h) }
+        };
+        let screen = if fb.is_empty() {
+            Screen { width: 0, height: 0, channels: 0, pixels: Vec::new() }
+        } else {
+            Screen::capture(fb, w, h, &self.config.observation)
+        };
+        Observation { screen, vars: self.vars }
+    }
+}
+
+/// "E1M1" -> (1, 1) outside Doom II, "MAP07" -> (1, 7) in it; any case.
+pub fn parse_map(name: &str, commercial: bool) -> Option<(i32, i32)> {
+    let name = name.to_ascii_uppercase();
+    if commercial {
+        let map: i32 = name.strip_prefix("MAP")?.parse().ok()?;
+        (1..=99).contains(&map).then_some((1, map))
+    } else {
+        let (episode, map) = name.strip_prefix('E')?.split_once('M')?;
+        let (episode, map): (i32, i32) = (episode.parse().ok()?, map.parse().ok()?);
+        ((1..=9).contains(&episode) && (1..=9).contains(&map)).then_some((episode, map))
+    }
+}
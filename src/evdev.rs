@@ -0,0 +1,157 @@
+// evdev.rs
+//
+// Keyboards and mice read straight from /dev/input/event*, for the fbdev
+// frontend: no X11, Wayland or terminal in between. Devices are opened
+// non-blocking, and keyboards grabbed so keys typed at the game don't also
+// land on the console underneath; mice and the like are left shared. Codes
+// are the kernel's (linux/input-event-codes.h).
+use std::fs::{File, OpenOptions};
+use std::io::{ErrorKind, Read};
+use std::os::fd::AsRawFd;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+
+pub const EV_KEY: u16 = 0x01;
+pub const EV_REL: u16 = 0x02;
+
+pub const REL_X: u16 = 0x00;
+pub const REL_Y: u16 = 0x01;
+pub const REL_WHEEL: u16 = 0x08;
+
+pub const BTN_LEFT: u16 = 0x110;
+pub const BTN_RIGHT: u16 = 0x111;
+pub const BTN_MIDDLE: u16 = 0x112;
+
+// _IOW('E', 0x90, int)
+const EVIOCGRAB: libc::c_ulong = 0x4004_4590;
+// _IOC(_IOC_READ, 'E', 0x20 + EV_KEY, KEY_BITS): which keys a device has.
+const KEY_BITS: usize = 0x300 / 8;
+const EVIOCGBIT_KEY: libc::c_ulong = 0x8000_4521 | (KEY_BITS as libc::c_ulong) << 16;
+// Letters a keyboard has and a mouse, power button or lid switch doesn't.
+const KEY_Q: usize = 16;
+const KEY_A: usize = 30;
+const KEY_Z: usize = 44;
+
+/// One `struct input_event`, without its timestamp.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputEvent {
+    pub kind: u16,
+    pub code: u16,
+    /// For keys: 1 pressed, 0 released, 2 repeated.
+    pub value: i32,
+}
+
+/// `struct input_event`s back to back, as a device read returns them.
+pub fn parse_events(bytes: &[u8]) -> Vec<InputEvent> {
+    let header = size_of::<libc::timeval>();
+    bytes
+        .chunks_exact(size_of::<libc::input_event>())
+        .map(|e| InputEvent {
+            kind: u16::from_ne_bytes([e[header], e[header + 1]]),
+            code: u16::from_ne_bytes([e[header + 2], e[header + 3]]),
+            value: i32::from_ne_bytes(e[header + 4..header + 8].try_into().unwrap()),
+        })
+        .collect()
+}
+
+/// The bytes of one event, as the kernel would write it.
+pub fn encode_event(ev: InputEvent) -> Vec<u8> {
+    let mut out = vec![0; size_of::<libc::timeval>()];
+    out.extend_from_slice(&ev.kind.to_ne_bytes());
+    out.extend_from_slice(&ev.code.to_ne_bytes());
+    out.extend_from_slice(&ev.value.to_ne_bytes());
+    out
+}
+
+#[derive(Debug)]
+pub struct InputDevice {
+    path: PathBuf,
+    file: File,
+    gone: bool,
+}
+
+impl InputDevice {
+    /// Open a device (or a file of events), grabbing it if it's a keyboard.
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(path)
+            .with_context(|| format!("opening {}", path.display()))?;
+        // Keep the keys from the console too. Only keyboards: a grabbed mouse
+        // or power button is lost to everything else. A plain file has no bits.
+        let mut keys = [0u8; KEY_BITS];
+        let asked = unsafe { libc::ioctl(file.as_raw_fd(), EVIOCGBIT_KEY as _, keys.as_mut_ptr()) } > 0;
+        if asked && [KEY_Q, KEY_A, KEY_Z].into_iter().all(|key| keys[key / 8] & (1 << (key % 8)) != 0) {
+            unsafe { libc::ioctl(file.as_raw_fd(), EVIOCGRAB as _, 1 as libc::c_int) };
+        }
+        Ok(InputDevice { path: path.to_path_buf(), file, gone: false })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the device was unplugged; it reads nothing from then on.
+    pub fn gone(&self) -> bool {
+        self.gone
+    }
+
+    /// The events that arrived since last time, without waiting.
+    pub fn read(&mut self) -> anyhow::Result<Vec<InputEvent>> {
+        if self.gone {
+            return Ok(Vec::new());
+        }
+        let mut buf = [0u8; 64 * size_of::<libc::input_event>()];
+        let mut events = Vec::new();
+        loop {
+            match self.file.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => events.extend(parse_events(&buf[..n])),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) if e.raw_os_error() == Some(libc::ENODEV) => {
+                    self.gone = true;
+                    break;
+                }
+                Err(e) => return Err(e).with_context(|| format!("reading {}", self.path.display())),
+            }
+        }
+        Ok(events)
+    }
+}
+
+/// `paths`, or when there are none every /dev/input/event* this user may
+/// read. Devices that can't be opened are skipped, with a warning if they
+/// were asked for.
+pub fn open_devices(paths: &[PathBuf]) -> Vec<InputDevice> {
+    let asked = !paths.is_empty();
+    let paths = if !asked {
+        let mut found: Vec<PathBuf> = std::fs::read_dir("/dev/input")
+            .map(|dir| {
+                dir.flatten()
+                    .map(|e| e.path())
+                    .filter(|p| p.file_name().is_some_and(|n| n.to_string_lossy().starts_with("event")))
+                    .collect()
+            })
+            .unwrap_or_default();
+        found.sort();
+        found
+    } else {
+        paths.to_vec()
+    };
+    paths
+        .iter()
+        .filter_map(|p| match InputDevice::open(p) {
+            Ok(device) => Some(device),
+            Err(e) => {
+                if asked {
+                    eprintln!("warning: {:#}", e);
+                }
+                None
+            }
+        })
+        .collect()
+}
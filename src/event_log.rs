@@ -0,0 +1,28 @@
+// event_log.rs
+//
+// --events-out: every game event (rustydoom::events) written to a file as
+// one JSON object per line while the game runs.
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use anyhow::Context;
+use rustydoom::events;
+
+use crate::cli::Cli;
+
+pub fn init(cli: &Cli) -> anyhow::Result<()> {
+    let Some(path) = &cli.events_out else { return Ok(()) };
+    let file = File::create(path).with_context(|| format!("creating {}", path.display()))?;
+    let mut out = BufWriter::new(file);
+    let mut failed = false;
+    events::set_handler(move |ev| {
+        let line = serde_json::to_string(ev).expect("events serialize");
+        // Flushed per event so the file is complete up to a crash.
+        if let Err(e) = writeln!(out, "{}", line).and_then(|_| out.flush())
+            && !std::mem::replace(&mut failed, true)
+        {
+            eprintln!("events-out: {}", e);
+        }
+    });
+    Ok(())
+}
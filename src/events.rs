@@ -0,0 +1,134 @@
+// events.rs
+//
+// Things that happen in the game, as the engine reports them (DG_Event in
+// csrc/platform/events.c): levels starting and ending, damage, deaths,
+// pickups, kills, secrets and weapon changes. The engine raises them in the
+// middle of a tic; they are queued and handed to the handler by `dispatch`,
+// which the host calls between tics.
+use std::ffi::CStr;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::sys;
+
+/// An event as the engine reports it, which `Event::from_raw` decodes.
+pub use crate::sys::DgEvent;
+
+// DG_EV_* in doomgeneric.h.
+const EV_LEVEL_START: i32 = 1;
+const EV_LEVEL_FINISH: i32 = 2;
+const EV_DAMAGE: i32 = 3;
+const EV_DEATH: i32 = 4;
+const EV_PICKUP: i32 = 5;
+const EV_KILL: i32 = 6;
+const EV_SECRET: i32 = 7;
+const EV_WEAPON: i32 = 8;
+
+/// One event and the tic it happened in. Serializes flat, e.g.
+/// `{"tic":412,"event":"pickup","player":1,"item":"MEDI","thing_type":61}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Event {
+    pub tic: i32,
+    #[serde(flatten)]
+    pub kind: EventKind,
+}
+
+/// Players are numbered 1-4 (green, indigo, brown, red) and skills 1-5, as
+/// in the --stats-out records. Things are named by their sprite ("POSS",
+/// "MEDI"), with the engine's mobj type number alongside.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum EventKind {
+    LevelStart { episode: i32, map: i32, skill: i32 },
+    LevelFinish { episode: i32, map: i32, time_tics: i32 },
+    /// Health actually lost, after armor.
+    Damage { player: i32, amount: i32, health: i32, source: Option<String> },
+    Death { player: i32, killer: Option<String> },
+    Pickup { player: i32, item: String, thing_type: i32 },
+    /// A monster that counts toward the kill total died; `player` is None
+    /// when it wasn't a player's doing.
+    Kill { player: Option<i32>, monster: String, thing_type: i32 },
+    Secret { player: i32, sector: i32 },
+    WeaponChange { player: i32, weapon: Weapon },
+}
+
+/// weapontype_t.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Weapon {
+    Fist,
+    Pistol,
+    Shotgun,
+    Chaingun,
+    RocketLauncher,
+    PlasmaRifle,
+    Bfg9000,
+    Chainsaw,
+    SuperShotgun,
+}
+
+impl Weapon {
+    pub fn from_index(i: i32) -> Option<Self> {
+        use Weapon::*;
+        [Fist, Pistol, Shotgun, Chaingun, RocketLauncher, PlasmaRifle, Bfg9000, Chainsaw, SuperShotgun]
+            .get(usize::try_from(i).ok()?)
+            .copied()
+    }
+}
+
+impl Event {
+    /// None for kinds this build doesn't know.
+    pub fn from_raw(ev: &DgEvent) -> Option<Self> {
+        // SAFETY: the bridge always NUL-terminates `name` within the array.
+        let name = unsafe { CStr::from_ptr(ev.name.as_ptr()) }.to_string_lossy().into_owned();
+        let sprite = || (!name.is_empty()).then(|| name.clone());
+        let player = ev.player + 1;
+        let kind = match ev.kind {
+            EV_LEVEL_START => EventKind::LevelStart { episode: ev.a, map: ev.b, skill: ev.c + 1 },
+            EV_LEVEL_FINISH => EventKind::LevelFinish { episode: ev.a, map: ev.b, time_tics: ev.c },
+            EV_DAMAGE => EventKind::Damage { player, amount: ev.a, health: ev.b, source: sprite() },
+            EV_DEATH => EventKind::Death { player, killer: sprite() },
+            EV_PICKUP => EventKind::Pickup { player, item: name.clone(), thing_type: ev.a },
+            EV_KILL => EventKind::Kill { player: (ev.player >= 0).then_some(player), monster: name.clone(), thing_type: ev.a },
+            EV_SECRET => EventKind::Secret { player, sector: ev.a },
+            EV_WEAPON => EventKind::WeaponChange { player, weapon: Weapon::from_index(ev.a)? },
+            _ => return None,
+        };
+        Some(Event { tic: ev.tic, kind })
+    }
+}
+
+type Handler = Box<dyn FnMut(&Event) + Send>;
+
+static QUEUE: Mutex<Vec<Event>> = Mutex::new(Vec::new());
+static HANDLER: Mutex<Option<Handler>> = Mutex::new(None);
+
+unsafe extern "C" fn hook(ev: *const DgEvent) {
+    // SAFETY: the engine passes a valid event for the duration of the call.
+    if let Some(ev) = unsafe { ev.as_ref() }.and_then(Event::from_raw) {
+        QUEUE.lock().unwrap().push(ev);
+    }
+}
+
+/// Have every event passed to `handler` from now on, replacing any earlier
+/// one. The engine only reports events while a handler is set.
+pub fn set_handler(handler: impl FnMut(&Event) + Send + 'static) {
+    *HANDLER.lock().unwrap() = Some(Box::new(handler));
+    unsafe { sys::raw::dg_set_event_hook(Some(hook)) };
+}
+
+pub fn clear_handler() {
+    unsafe { sys::raw::dg_set_event_hook(None) };
+    *HANDLER.lock().unwrap() = None;
+    QUEUE.lock().unwrap().clear();
+}
+
+/// Hand the events queued during the last tic(s) to the handler, in order.
+/// Call between tics, never from inside the handler.
+pub fn dispatch() {
+    let events = std::mem::take(&mut *QUEUE.lock().unwrap());
+    if let Some(handler) = HANDLER.lock().unwrap().as_mut() {
+        events.iter().for_each(handler);
+    }
+}
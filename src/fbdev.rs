@@ -0,0 +1,207 @@
+// fbdev.rs
+//
+// The Linux framebuffer device (/dev/fb0) as a Presenter, for
+// `--frontend fbdev` on kiosk machines with neither X11 nor Wayland. The
+// screen's size and pixel format come from the device's ioctls; every frame
+// is scaled to the whole screen, packed into that format (16, 24 or 32 bits
+// a pixel) and written row by row at the visible offset. Any file can stand
+// in for the device given a Layout, which is how the tests run.
+use std::fs::{File, OpenOptions};
+use std::os::fd::AsRawFd;
+use std::os::unix::fs::FileExt;
+use std::path::Path;
+
+use anyhow::{Context, bail};
+
+use crate::blit::blit_nn;
+use crate::config::Scaler;
+use crate::present::Presenter;
+
+/// Where one colour's bits are in a pixel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Channel {
+    pub offset: u32,
+    pub length: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PixelFormat {
+    pub bits_per_pixel: u32,
+    pub red: Channel,
+    pub green: Channel,
+    pub blue: Channel,
+}
+
+impl PixelFormat {
+    pub const RGB565: PixelFormat = PixelFormat::new(16, (11, 5), (5, 6), (0, 5));
+    pub const RGB888: PixelFormat = PixelFormat::new(24, (16, 8), (8, 8), (0, 8));
+    pub const XRGB8888: PixelFormat = PixelFormat::new(32, (16, 8), (8, 8), (0, 8));
+
+    /// Channels as (offset, length).
+    pub const fn new(bits_per_pixel: u32, red: (u32, u32), green: (u32, u32), blue: (u32, u32)) -> Self {
+        PixelFormat {
+            bits_per_pixel,
+            red: Channel { offset: red.0, length: red.1 },
+            green: Channel { offset: green.0, length: green.1 },
+            blue: Channel { offset: blue.0, length: blue.1 },
+        }
+    }
+
+    pub fn bytes_per_pixel(&self) -> usize {
+        self.bits_per_pixel as usize / 8
+    }
+
+    /// A 0x00RRGGBB colour as this format's pixel value. Each 8-bit channel
+    /// is scaled to its length, so full intensity is all ones at any width.
+    pub fn pack(&self, rgb: u32) -> u32 {
+        let put = |c: Channel, value: u32| ((value as u64 * ((1u64 << c.length) - 1) / 255) as u32) << c.offset;
+        put(self.red, (rgb >> 16) & 0xff) | put(self.green, (rgb >> 8) & 0xff) | put(self.blue, rgb & 0xff)
+    }
+}
+
+/// The visible screen within the device's memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Layout {
+    pub width: usize,
+    pub height: usize,
+    /// Bytes from one row to the next.
+    pub stride: usize,
+    /// Byte offset of the top left pixel (the panned-to position).
+    pub offset: u64,
+    pub format: PixelFormat,
+}
+
+// linux/fb.h
+const FBIOGET_VSCREENINFO: libc::c_ulong = 0x4600;
+const FBIOGET_FSCREENINFO: libc::c_ulong = 0x4602;
+
+// Laid out for the kernel to fill in; not every field is read.
+#[repr(C)]
+#[derive(Default)]
+#[allow(dead_code)]
+struct Bitfield {
+    offset: u32,
+    length: u32,
+    msb_right: u32,
+}
+
+#[repr(C)]
+#[derive(Default)]
+#[allow(dead_code)]
+struct VarScreeninfo {
+    xres: u32,
+    yres: u32,
+    xres_virtual: u32,
+    yres_virtual: u32,
+    xoffset: u32,
+    yoffset: u32,
+    bits_per_pixel: u32,
+    grayscale: u32,
+    red: Bitfield,
+    green: Bitfield,
+    blue: Bitfield,
+    transp: Bitfield,
+    // nonstd to colorspace, and reserved
+    rest: [u32; 20],
+}
+
+#[repr(C)]
+#[allow(dead_code)]
+struct FixScreeninfo {
+    id: [u8; 16],
+    smem_start: libc::c_ulong,
+    smem_len: u32,
+    kind: u32,
+    type_aux: u32,
+    visual: u32,
+    xpanstep: u16,
+    ypanstep: u16,
+    ywrapstep: u16,
+    line_length: u32,
+    mmio_start: libc::c_ulong,
+    mmio_len: u32,
+    accel: u32,
+    capabilities: u16,
+    reserved: [u16; 2],
+}
+
+/// A framebuffer device, or a file standing in for one.
+#[derive(Debug)]
+pub struct Framebuffer {
+    file: File,
+    layout: Layout,
+    scaler: Scaler,
+    pixels: Vec<u32>,
+    bytes: Vec<u8>,
+}
+
+impl Framebuffer {
+    /// Open a framebuffer device and ask it for its layout.
+    pub fn open(path: &Path, scaler: Scaler) -> anyhow::Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).open(path)
+            .with_context(|| format!("opening {}", path.display()))?;
+        let mut var = VarScreeninfo::default();
+        let mut fix: FixScreeninfo = unsafe { std::mem::zeroed() };
+        unsafe {
+            if libc::ioctl(file.as_raw_fd(), FBIOGET_VSCREENINFO as _, &mut var) != 0
+                || libc::ioctl(file.as_raw_fd(), FBIOGET_FSCREENINFO as _, &mut fix) != 0
+            {
+                return Err(std::io::Error::last_os_error())
+                    .with_context(|| format!("{} is not a framebuffer device", path.display()));
+            }
+        }
+        let channel = |b: &Bitfield| (b.offset, b.length);
+        let format = PixelFormat::new(var.bits_per_pixel, channel(&var.red), channel(&var.green), channel(&var.blue));
+        let stride = fix.line_length as usize;
+        let layout = Layout {
+            width: var.xres as usize,
+            height: var.yres as usize,
+            stride,
+            offset: var.yoffset as u64 * stride as u64 + var.xoffset as u64 * format.bytes_per_pixel() as u64,
+            format,
+        };
+        Self::with_layout(file, layout, scaler).with_context(|| path.display().to_string())
+    }
+
+    /// Any file, taken to be laid out like a framebuffer.
+    pub fn with_layout(file: File, layout: Layout, scaler: Scaler) -> anyhow::Result<Self> {
+        let format = layout.format;
+        if !matches!(format.bits_per_pixel, 16 | 24 | 32) {
+            bail!("{}-bit pixels are not supported (16, 24 or 32 are)", format.bits_per_pixel);
+        }
+        if [format.red, format.green, format.blue].iter().any(|c| c.length == 0 || c.offset + c.length > format.bits_per_pixel) {
+            bail!("not an RGB pixel format: {:?}", format);
+        }
+        if layout.stride < layout.width * format.bytes_per_pixel() {
+            bail!("rows of {} bytes can't hold {} pixels", layout.stride, layout.width);
+        }
+        Ok(Framebuffer { file, layout, scaler, pixels: Vec::new(), bytes: Vec::new() })
+    }
+
+    pub fn layout(&self) -> &Layout {
+        &self.layout
+    }
+}
+
+impl Presenter for Framebuffer {
+    /// The screen is the size it is.
+    fn resize(&mut self, _width: u32, _height: u32) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn present(&mut self, frame: &[u32], width: usize, height: usize) -> anyhow::Result<()> {
+        let Layout { width: w, height: h, stride, offset, format } = self.layout;
+        self.pixels.resize(w * h, 0);
+        blit_nn(&mut self.pixels, w, h, frame, width, height, self.scaler);
+        // A write a row: the bytes past each row's pixels aren't ours to clear.
+        let bpp = format.bytes_per_pixel();
+        self.bytes.resize(w * bpp, 0);
+        for (y, row) in self.pixels.chunks_exact(w.max(1)).enumerate() {
+            for (px, &rgb) in self.bytes.chunks_exact_mut(bpp).zip(row) {
+                px.copy_from_slice(&format.pack(rgb).to_le_bytes()[..bpp]);
+            }
+            self.file.write_all_at(&self.bytes, offset + (y * stride) as u64).context("writing to the framebuffer")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,139 @@
+// fbdev_frontend.rs
+//
+// `--frontend fbdev`: play straight on the Linux framebuffer with keyboard
+// and mouse from evdev, bypassing winit, for kiosks without X11 or Wayland.
+// The picture is rustydoom::fbdev's, the input rustydoom::evdev's, mapped
+// to the same engine keys and hotkeys the window uses.
+use std::path::{Path, PathBuf};
+
+use rustydoom::config::HostConfig;
+use rustydoom::evdev::{self, BTN_LEFT, BTN_MIDDLE, BTN_RIGHT, EV_KEY, EV_REL, InputEvent, REL_WHEEL, REL_X, REL_Y};
+use rustydoom::fbdev::Framebuffer;
+use rustydoom::present::{FileSink, Presenter};
+use winit::keyboard::KeyCode;
+
+use crate::dg_io::{
+    DGK_DOWN, DGK_ENTER, DGK_ESCAPE, DGK_FIRE, DGK_LEFT, DGK_RIGHT, DGK_RUN, DGK_STRAFE, DGK_UP, DGK_USE, DoomHost, Hotkeys,
+};
+use crate::{DgHost, demos, playback, sys};
+
+pub fn run(
+    settings: &HostConfig,
+    hotkeys: Hotkeys,
+    mut dump_frames: Option<FileSink>,
+    device: &Path,
+    inputs: &[PathBuf],
+) -> anyhow::Result<()> {
+    let mut presenter = Framebuffer::open(device, settings.video.scaler)?;
+    let layout = *presenter.layout();
+    println!("fbdev: {} {}x{}, {}-bit", device.display(), layout.width, layout.height, layout.format.bits_per_pixel);
+    let mut devices = evdev::open_devices(inputs);
+    if devices.is_empty() {
+        eprintln!("warning: no input devices could be opened (is this user in the input group?)");
+    }
+    for d in &devices {
+        println!("input: {}", d.path().display());
+    }
+
+    let host = DgHost;
+    loop {
+        for device in &mut devices {
+            for ev in device.read()? {
+                handle(&host, &hotkeys, ev);
+            }
+        }
+        devices.retain(|d| {
+            if d.gone() {
+                eprintln!("warning: {} is gone; carrying on without it", d.path().display());
+            }
+            !d.gone()
+        });
+
+        unsafe { sys::raw::dg_pump() };
+        let tics = playback::run_frame(crate::tick);
+        if demos::finished() {
+            return Ok(());
+        }
+        let Some((fb, fb_w, fb_h)) = crate::fetch_doom_fb() else { continue };
+        presenter.present(fb, fb_w, fb_h)?;
+        // Skip the dump when no tic ran (paused), or it fills with copies.
+        if let Some(sink) = dump_frames.as_mut().filter(|_| tics > 0) {
+            sink.present(fb, fb_w, fb_h)?;
+        }
+    }
+}
+
+fn handle(host: &impl DoomHost, hotkeys: &Hotkeys, ev: InputEvent) {
+    match (ev.kind, ev.code) {
+        (EV_KEY, BTN_LEFT | BTN_RIGHT | BTN_MIDDLE) if ev.value != 2 => {
+            host.mouse_button((ev.code - BTN_LEFT) as i32, ev.value == 1);
+        }
+        (EV_KEY, code) => {
+            // Hotkeys on presses and repeats, like the window's key repeat.
+            if ev.value != 0 && hotkey(code).is_some_and(|c| hotkeys.press(host, c)) {
+                return;
+            }
+            let Some(dk) = doom_key(code) else { return };
+            match ev.value {
+                1 => host.key_down(dk),
+                0 => host.key_up(dk),
+                _ => {}
+            }
+        }
+        (EV_REL, REL_X) => host.mouse_move_rel(ev.value as f32, 0.0),
+        (EV_REL, REL_Y) => host.mouse_move_rel(0.0, ev.value as f32),
+        (EV_REL, REL_WHEEL) => host.mouse_wheel(ev.value as f32),
+        _ => {}
+    }
+}
+
+/// Letter and punctuation keys in kernel code order, from KEY_1 (2).
+const ASCII_KEYS: &[u8; 52] = b"1234567890-=\0\0qwertyuiop[]\0\0asdfghjkl;'`\0\\zxcvbnm,./";
+
+/// What the engine gets for a kernel key code, as for the window: ASCII, or
+/// one of the bridge's sentinels.
+fn doom_key(code: u16) -> Option<i32> {
+    Some(match code {
+        1 => DGK_ESCAPE,
+        28 | 96 => DGK_ENTER,    // KEY_ENTER, KEY_KPENTER
+        29 | 97 => DGK_FIRE,     // KEY_LEFTCTRL, KEY_RIGHTCTRL
+        42 | 54 => DGK_RUN,      // KEY_LEFTSHIFT, KEY_RIGHTSHIFT
+        56 | 100 => DGK_STRAFE,  // KEY_LEFTALT, KEY_RIGHTALT
+        57 => DGK_USE,           // KEY_SPACE
+        103 => DGK_UP,
+        108 => DGK_DOWN,
+        105 => DGK_LEFT,
+        106 => DGK_RIGHT,
+        2..=53 => match ASCII_KEYS[code as usize - 2] {
+            0 => return None,
+            c => c as i32,
+        },
+        _ => return None,
+    })
+}
+
+/// The key a hotkey could be bound to, by winit's name for it.
+fn hotkey(code: u16) -> Option<KeyCode> {
+    use KeyCode::*;
+    const F: [KeyCode; 10] = [F1, F2, F3, F4, F5, F6, F7, F8, F9, F10];
+    Some(match code {
+        59..=68 => F[code as usize - 59],
+        87 => F11,
+        88 => F12,
+        14 => Backspace,
+        15 => Tab,
+        110 => Insert,
+        111 => Delete,
+        102 => Home,
+        107 => End,
+        104 => PageUp,
+        109 => PageDown,
+        119 => Pause,
+        99 => PrintScreen,
+        70 => ScrollLock,
+        41 => Backquote,
+        12 => Minus,
+        13 => Equal,
+        _ => return None,
+    })
+}
@@ -0,0 +1,65 @@
+// input.rs
+//
+// The console player's input given straight to the engine as ticcmds,
+// bypassing key bindings and G_BuildTiccmd's keyboard and mouse handling
+// (csrc/platform/ticcmd.c), so bots, TAS tools and test scripts get exactly
+// the movement, turning and buttons they ask for. Either hold one ticcmd for
+// every tic, or have a source asked for each tic's (given what the controls
+// made of it, to record or adjust); when the source has one it wins. Pause
+// and save requests from the keyboard still go through.
+use std::sync::Mutex;
+
+use libc::c_int;
+
+use crate::lmp::Ticcmd;
+use crate::sys::{self, DgTiccmd};
+
+type Source = Box<dyn FnMut(i32, Ticcmd) -> Option<Ticcmd> + Send>;
+
+static SOURCE: Mutex<Option<Source>> = Mutex::new(None);
+
+impl From<Ticcmd> for DgTiccmd {
+    fn from(cmd: Ticcmd) -> Self {
+        DgTiccmd { forwardmove: cmd.forwardmove, sidemove: cmd.sidemove, angleturn: cmd.angleturn, buttons: cmd.buttons }
+    }
+}
+
+impl From<DgTiccmd> for Ticcmd {
+    fn from(cmd: DgTiccmd) -> Self {
+        Ticcmd { forwardmove: cmd.forwardmove, sidemove: cmd.sidemove, angleturn: cmd.angleturn, buttons: cmd.buttons }
+    }
+}
+
+/// Use `cmd` for every tic from now on, until `release`.
+pub fn hold(cmd: Ticcmd) {
+    unsafe { sys::raw::dg_set_ticcmd(&DgTiccmd::from(cmd)) };
+}
+
+/// Hand input back to the controls (a source stays in place).
+pub fn release() {
+    unsafe { sys::raw::dg_set_ticcmd(std::ptr::null()) };
+}
+
+unsafe extern "C" fn hook(tic: c_int, cmd: *mut DgTiccmd) -> c_int {
+    let mut source = SOURCE.lock().unwrap();
+    let Some(source) = source.as_mut() else { return 0 };
+    // SAFETY: the engine passes its own ticcmd to fill for the length of the call.
+    let controls = unsafe { *cmd }.into();
+    let Some(next) = source(tic, controls) else { return 0 };
+    unsafe { *cmd = next.into() };
+    1
+}
+
+/// Ask `source` for the ticcmd of every tic as the engine builds it, with
+/// the gametic it will run in and the ticcmd the keyboard and mouse made;
+/// None leaves that tic to `hold` or the controls. It runs inside the tic,
+/// so it mustn't tick the engine itself.
+pub fn set_source(source: impl FnMut(i32, Ticcmd) -> Option<Ticcmd> + Send + 'static) {
+    *SOURCE.lock().unwrap() = Some(Box::new(source));
+    unsafe { sys::raw::dg_set_ticcmd_hook(Some(hook)) };
+}
+
+pub fn clear_source() {
+    unsafe { sys::raw::dg_set_ticcmd_hook(None) };
+    *SOURCE.lock().unwrap() = None;
+}
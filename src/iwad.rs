@@ -0,0 +1,268 @@
+// iwad.rs
+//
+// Host-side IWAD discovery. The engine's own search in d_iwad.c is geared
+// towards the Windows registry, Steam and DOS-era paths; here we look in the
+// places people actually keep WADs on a modern system, identify what we find
+// and hand the engine a concrete path.
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, bail};
+
+use crate::paths;
+use crate::wad::{Wad, WadKind};
+
+/// Known IWAD file names, in the order we prefer them when several are present.
+pub const IWAD_NAMES: &[&str] = &[
+    "doom2.wad", "plutonia.wad", "tnt.wad", "doom.wad", "doom1.wad",
+    "freedoom2.wad", "freedoom1.wad", "freedm.wad",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Game {
+    DoomShareware,
+    DoomRegistered,
+    UltimateDoom,
+    Doom2,
+    Tnt,
+    Plutonia,
+    Freedoom1,
+    Freedoom2,
+    FreeDm,
+}
+
+impl Game {
+    pub fn name(self) -> &'static str {
+        match self {
+            Game::DoomShareware  => "Doom Shareware",
+            Game::DoomRegistered => "Doom Registered",
+            Game::UltimateDoom   => "The Ultimate Doom",
+            Game::Doom2          => "Doom II",
+            Game::Tnt            => "Final Doom: TNT: Evilution",
+            Game::Plutonia       => "Final Doom: The Plutonia Experiment",
+            Game::Freedoom1      => "Freedoom: Phase 1",
+            Game::Freedoom2      => "Freedoom: Phase 2",
+            Game::FreeDm         => "FreeDM",
+        }
+    }
+
+    /// Doom II-style games with MAPxx levels rather than ExMy.
+    pub fn is_commercial(self) -> bool {
+        matches!(self, Game::Doom2 | Game::Tnt | Game::Plutonia | Game::Freedoom2 | Game::FreeDm)
+    }
+}
+
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// MD5 sums of released IWADs: (md5, game, release).
+const KNOWN_IWADS: &[(&str, Game, &str)] = &[
+    ("f0cefca49926d00903cf57551d901abe", Game::DoomShareware,  "v1.9"),
+    ("1cd63c5ddff1bf8ce844237f580e9cf3", Game::DoomRegistered, "v1.9"),
+    ("c4fe9fd920207691a9f5f5d1ffb87d2d", Game::UltimateDoom,   "v1.9"),
+    ("25e1459ca71d321525f84628f45ca8cd", Game::Doom2,          "v1.9"),
+    ("4e158d9953c79ccf97bd0663244cc6b6", Game::Tnt,            "v1.9"),
+    ("1d39e405bf6ee3df69a8d2646c8d5c49", Game::Tnt,            "v1.9 (id Anthology)"),
+    ("75c8cf89566741fa9d22447604053bd7", Game::Plutonia,       "v1.9"),
+    ("3493be7e1e2588bc9c8b31eab2587a04", Game::Plutonia,       "v1.9 (id Anthology)"),
+];
+
+/// The released IWAD with this MD5 sum (lower-case hex), if it is one.
+pub fn known_release(md5: &str) -> Option<(Game, &'static str)> {
+    KNOWN_IWADS.iter().find(|(sum, ..)| *sum == md5).map(|&(_, game, release)| (game, release))
+}
+
+/// An IWAD we found and identified.
+#[derive(Debug, Clone)]
+pub struct Iwad {
+    pub path: PathBuf,
+    pub game: Game,
+    pub md5: String,
+    /// Release name if the checksum matched a known IWAD.
+    pub release: Option<&'static str>,
+}
+
+impl fmt::Display for Iwad {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.game)?;
+        match self.release {
+            Some(r) => write!(f, " {}", r)?,
+            None => write!(f, " (unrecognised md5 {})", self.md5)?,
+        }
+        write!(f, " at {}", self.path.display())
+    }
+}
+
+/// Find the IWAD to run.
+///
+/// Explicit paths (`--iwad`, then the positional fallback) win and must be
+/// valid. Otherwise we walk `DOOMWADDIR`, `DOOMWADPATH`, the XDG data dirs
+/// and the current directory, taking the first file that identifies as an
+/// IWAD. On failure the error lists everywhere we looked.
+pub fn locate(iwad: Option<&str>, positional: Option<&str>) -> anyhow::Result<Iwad> {
+    for explicit in [iwad, positional].into_iter().flatten() {
+        if !explicit.is_empty() {
+            return locate_explicit(Path::new(explicit));
+        }
+    }
+
+    let dirs = search_dirs();
+    let mut rejected = Vec::new();
+    for dir in &dirs {
+        for name in IWAD_NAMES {
+            let Some(path) = find_in_dir(dir, name) else { continue };
+            match identify(&path) {
+                Ok(found) => return Ok(found),
+                Err(e) => rejected.push(format!("{}: {:#}", path.display(), e)),
+            }
+        }
+    }
+
+    let mut msg = String::from("no IWAD found. Searched:\n");
+    for dir in &dirs {
+        msg.push_str(&format!("  {}\n", dir.display()));
+    }
+    if !rejected.is_empty() {
+        msg.push_str("Rejected candidates:\n");
+        for r in &rejected {
+            msg.push_str(&format!("  {}\n", r));
+        }
+    }
+    msg.push_str(&format!(
+        "Looked for: {}\nPass one with --iwad <path> or set DOOMWADDIR.",
+        IWAD_NAMES.join(", ")
+    ));
+    bail!(msg)
+}
+
+/// A user-supplied path: either a WAD file, or a directory to search.
+fn locate_explicit(path: &Path) -> anyhow::Result<Iwad> {
+    if path.is_dir() {
+        for name in IWAD_NAMES {
+            if let Some(p) = find_in_dir(path, name) {
+                return identify(&p);
+            }
+        }
+        bail!("no IWAD in directory {} (looked for {})", path.display(), IWAD_NAMES.join(", "));
+    }
+    if !path.exists() {
+        // Accept bare names like "doom2.wad" and resolve them through the search path.
+        if path.components().count() == 1 {
+            let name = path.to_string_lossy().to_lowercase();
+            for dir in search_dirs() {
+                if let Some(p) = find_in_dir(&dir, &name) {
+                    return identify(&p);
+                }
+            }
+        }
+        bail!("IWAD {} does not exist", path.display());
+    }
+    identify(path)
+}
+
+/// Directories to scan, in priority order, without duplicates.
+pub fn search_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    let mut add = |p: PathBuf| {
+        if !p.as_os_str().is_empty() && !dirs.contains(&p) {
+            dirs.push(p);
+        }
+    };
+
+    if let Some(d) = std::env::var_os("DOOMWADDIR") {
+        add(PathBuf::from(d));
+    }
+    if let Some(path) = std::env::var_os("DOOMWADPATH") {
+        for d in std::env::split_paths(&path) {
+            add(d);
+        }
+    }
+
+    // XDG: $XDG_DATA_HOME (default ~/.local/share), then $XDG_DATA_DIRS.
+    if let Some(home) = paths::data_home() {
+        add(home.join("doom"));
+        add(home.join("games/doom"));
+    }
+    let data_dirs = std::env::var_os("XDG_DATA_DIRS")
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".into());
+    for d in std::env::split_paths(&data_dirs) {
+        add(d.join("doom"));
+        add(d.join("games/doom"));
+    }
+
+    if let Ok(cwd) = std::env::current_dir() {
+        add(cwd);
+    }
+    dirs
+}
+
+/// Case-insensitive lookup of `name` in `dir` (WADs are often shipped upper-case).
+fn find_in_dir(dir: &Path, name: &str) -> Option<PathBuf> {
+    let exact = dir.join(name);
+    if exact.is_file() {
+        return Some(exact);
+    }
+    std::fs::read_dir(dir).ok()?
+        .flatten()
+        .map(|e| e.path())
+        .find(|p| p.is_file() && p.file_name().is_some_and(|f| f.to_string_lossy().eq_ignore_ascii_case(name)))
+}
+
+/// Identify a file as one of the supported IWADs.
+pub fn identify(path: &Path) -> anyhow::Result<Iwad> {
+    let data = std::fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+    identify_bytes(path, data)
+}
+
+/// Identify an in-memory WAD image; `path` is only used for naming.
+pub fn identify_bytes(path: &Path, data: Vec<u8>) -> anyhow::Result<Iwad> {
+    let wad = Wad::from_bytes(data).with_context(|| format!("parsing {}", path.display()))?;
+    if wad.kind() == WadKind::Pwad {
+        bail!("{} is a PWAD; load it with --file on top of an IWAD", path.display());
+    }
+    let md5 = format!("{:x}", md5::compute(wad.bytes()));
+
+    if let Some((game, release)) = known_release(&md5) {
+        return Ok(Iwad { path: path.to_path_buf(), game, md5, release: Some(release) });
+    }
+
+    let file_name = path.file_name().map(|f| f.to_string_lossy().to_lowercase()).unwrap_or_default();
+    let game = game_from_lumps(&wad, &file_name)
+        .with_context(|| format!("{} doesn't look like a Doom IWAD", path.display()))?;
+    Ok(Iwad { path: path.to_path_buf(), game, md5, release: None })
+}
+
+/// Guess the game from the lump directory, using the file name only to tell
+/// apart the Doom II-format IWADs that share a map layout.
+fn game_from_lumps(wad: &Wad, file_name: &str) -> Option<Game> {
+    let has = |n: &str| wad.contains(n);
+
+    if has("FREEDM") {
+        return Some(Game::FreeDm);
+    }
+    if has("FREEDOOM") {
+        return Some(if has("MAP01") { Game::Freedoom2 } else { Game::Freedoom1 });
+    }
+    if has("MAP01") {
+        return Some(if file_name.starts_with("tnt") {
+            Game::Tnt
+        } else if file_name.starts_with("plutonia") {
+            Game::Plutonia
+        } else {
+            Game::Doom2
+        });
+    }
+    if has("E4M1") {
+        return Some(Game::UltimateDoom);
+    }
+    if has("E2M1") || has("E3M1") {
+        return Some(Game::DoomRegistered);
+    }
+    if has("E1M1") {
+        return Some(Game::DoomShareware);
+    }
+    None
+}
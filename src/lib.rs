@@ -0,0 +1,41 @@
+//! Host-side tooling shared by the `rustydoom` binary: its name and window size (`constants`), WAD parsing, IWAD discovery,
+//! DeHackEd patches, demos and TAS timelines, savegames and host configuration, the Doom-style
+//! command line (`args`), frame scaling (`blit`), where frames are shown (`present`) and
+//! drawing them in a terminal (`terminal`) or on the Linux framebuffer (`fbdev`, with input
+//! from `evdev`); plus the game events the engine reports (`events`), player input given as ticcmds (`input`),
+//! a read-only view of the game's state (`state`), map regression manifests
+//! (`regression`), and a headless reinforcement-learning environment over it (`env`).
+pub mod args;
+pub mod blit;
+pub mod config;
+pub mod constants;
+pub mod deh;
+pub mod env;
+pub mod evdev;
+pub mod events;
+pub mod fbdev;
+pub mod input;
+pub mod iwad;
+pub mod lmp;
+pub mod paths;
+pub mod present;
+pub mod regression;
+pub mod savegame;
+pub mod state;
+mod sys;
+pub mod terminal;
+pub mod timeline;
+mod vfs;
+pub mod wad;
+
+/// The engine's FFI surface and the virtual files it reads WADs from, for the
+/// `rustydoom` binary; not part of the library's API.
+#[doc(hidden)]
+pub mod bridge {
+    pub mod sys {
+        pub use crate::sys::*;
+    }
+    pub mod vfs {
+        pub use crate::vfs::{expand_files, is_archive, mount, mount_archive, mount_archive_iwad, mount_embedded_iwad, mount_merged_iwad, read_wad};
+    }
+}
@@ -0,0 +1,275 @@
+// lmp.rs
+//
+// Vanilla demo files (.lmp, or DEMOn lumps) as read by G_DoPlayDemo and
+// G_ReadDemoTiccmd: a header with the game settings, then one ticcmd per
+// player in the game for every tic, then the 0x80 end marker.
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, bail};
+
+/// Ends the tic stream where the next tic would start.
+pub const MARKER: u8 = 0x80;
+/// Version written by Doom 1.9, the one the engine plays.
+pub const DOOM_VERSION: u8 = 109;
+/// cph's "v1.91" exe: same as 1.9 but with 16-bit turning ("longtics").
+pub const LONGTICS_VERSION: u8 = 111;
+
+const MAX_PLAYERS: usize = 4;
+const TICRATE: u32 = 35;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DemoHeader {
+    /// None for v1.0-v1.2 demos, which start straight with the skill.
+    pub version: Option<u8>,
+    pub skill: u8,
+    pub episode: u8,
+    pub map: u8,
+    /// 0 cooperative/single player, 1 deathmatch, 2 altdeath.
+    pub deathmatch: u8,
+    pub respawn: bool,
+    pub fast: bool,
+    pub nomonsters: bool,
+    /// Whose view the demo was recorded from.
+    pub consoleplayer: u8,
+    pub players: [bool; MAX_PLAYERS],
+}
+
+/// One player's input for one tic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Ticcmd {
+    pub forwardmove: i8,
+    pub sidemove: i8,
+    /// Full 16-bit angle; only the high byte survives outside longtics demos.
+    pub angleturn: i16,
+    pub buttons: u8,
+}
+
+/// How the tic stream stops.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum End {
+    /// The end marker, followed by `trailing` bytes (often a source port's footer).
+    Marker { trailing: usize },
+    /// The data stops without one; the last `partial` bytes are an incomplete tic.
+    Missing { partial: usize },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Demo {
+    pub header: DemoHeader,
+    /// Per tic, a ticcmd for each player in the game, in player order.
+    pub tics: Vec<Vec<Ticcmd>>,
+    pub end: End,
+}
+
+impl DemoHeader {
+    fn parse(data: &[u8]) -> anyhow::Result<(Self, usize)> {
+        let Some(&first) = data.first() else { bail!("empty demo") };
+        // G_DoPlayDemo's own check: versions 0-4 are old demos where the
+        // first byte is the skill.
+        let (header, len) = if first <= 4 {
+            let Some(b) = data.get(..7) else { bail!("truncated demo header") };
+            let header = DemoHeader {
+                version: None,
+                skill: b[0],
+                episode: b[1],
+                map: b[2],
+                deathmatch: 0,
+                respawn: false,
+                fast: false,
+                nomonsters: false,
+                consoleplayer: 0,
+                players: [b[3] != 0, b[4] != 0, b[5] != 0, b[6] != 0],
+            };
+            (header, 7)
+        } else {
+            if !(104..=DOOM_VERSION).contains(&first) && first != LONGTICS_VERSION {
+                bail!("not a vanilla demo (version {}; Boom, MBF or a later port?)", first);
+            }
+            let Some(b) = data.get(..13) else { bail!("truncated demo header") };
+            let header = DemoHeader {
+                version: Some(b[0]),
+                skill: b[1],
+                episode: b[2],
+                map: b[3],
+                deathmatch: b[4],
+                respawn: b[5] != 0,
+                fast: b[6] != 0,
+                nomonsters: b[7] != 0,
+                consoleplayer: b[8],
+                players: [b[9] != 0, b[10] != 0, b[11] != 0, b[12] != 0],
+            };
+            (header, 13)
+        };
+        if header.skill > 4 {
+            bail!("bad skill {} in demo header", header.skill);
+        }
+        if header.player_count() == 0 {
+            bail!("no players in demo header");
+        }
+        Ok((header, len))
+    }
+
+    pub fn longtics(&self) -> bool {
+        self.version == Some(LONGTICS_VERSION)
+    }
+
+    pub fn player_count(&self) -> usize {
+        self.players.iter().filter(|&&p| p).count()
+    }
+
+    /// "v1.9", "v1.91 (longtics)", ... after the engine's DemoVersionDescription.
+    pub fn version_name(&self) -> &'static str {
+        match self.version {
+            None => "v1.0/v1.1/v1.2",
+            Some(104) => "v1.4",
+            Some(105) => "v1.5",
+            Some(106) => "v1.6/v1.666",
+            Some(107) => "v1.7/v1.7a",
+            Some(108) => "v1.8",
+            Some(DOOM_VERSION) => "v1.9",
+            Some(_) => "v1.91 (longtics)",
+        }
+    }
+
+    /// Whether the engine will play it; it only knows 1.9 and longtics.
+    pub fn playable(&self) -> bool {
+        matches!(self.version, Some(DOOM_VERSION | LONGTICS_VERSION))
+    }
+
+    /// `commercial` is None when we don't know which game the demo is for.
+    pub fn map_name(&self, commercial: Option<bool>) -> String {
+        match commercial {
+            Some(true) => format!("MAP{:02}", self.map),
+            Some(false) => format!("E{}M{}", self.episode, self.map),
+            None => format!("episode {} map {}", self.episode, self.map),
+        }
+    }
+}
+
+impl Demo {
+    pub fn parse(data: &[u8]) -> anyhow::Result<Self> {
+        let (header, mut pos) = DemoHeader::parse(data)?;
+        let players = header.player_count();
+        let cmd_size = if header.longtics() { 5 } else { 4 };
+
+        let mut tics = Vec::new();
+        let end = loop {
+            if data.get(pos) == Some(&MARKER) {
+                break End::Marker { trailing: data.len() - pos - 1 };
+            }
+            let Some(tic) = data.get(pos..pos + players * cmd_size) else {
+                break End::Missing { partial: data.len() - pos };
+            };
+            tics.push(tic.chunks(cmd_size).map(|c| Ticcmd::parse(c, header.longtics())).collect());
+            pos += players * cmd_size;
+        };
+        Ok(Demo { header, tics, end })
+    }
+
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        let data = std::fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+        Self::parse(&data).with_context(|| format!("parsing {}", path.display()))
+    }
+
+    /// Playing time at 35 tics a second.
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis(self.tics.len() as u64 * 1000 / TICRATE as u64)
+    }
+
+    /// The file G_WriteDemoTiccmd would have written: header, tics, end
+    /// marker. Turns are rounded to a byte unless the header is longtics.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let h = &self.header;
+        let mut out = match h.version {
+            None => vec![h.skill, h.episode, h.map],
+            Some(v) => vec![v, h.skill, h.episode, h.map, h.deathmatch, h.respawn as u8, h.fast as u8, h.nomonsters as u8, h.consoleplayer],
+        };
+        out.extend(h.players.map(u8::from));
+        for cmd in self.tics.iter().flatten() {
+            out.extend([cmd.forwardmove as u8, cmd.sidemove as u8]);
+            if h.longtics() {
+                out.extend(cmd.angleturn.to_le_bytes());
+            } else {
+                out.push(((cmd.angleturn as i32 + 128) >> 8) as u8);
+            }
+            out.push(cmd.buttons);
+        }
+        out.push(MARKER);
+        out
+    }
+}
+
+impl Ticcmd {
+    /// `buttons` bits (d_event.h).
+    pub const ATTACK: u8 = 1;
+    pub const USE: u8 = 2;
+    /// Switch to the weapon slot in the `WEAPON_MASK` bits: 0 fist .. 7 chainsaw.
+    pub const CHANGE: u8 = 4;
+    pub const WEAPON_MASK: u8 = 0x38;
+    pub const WEAPON_SHIFT: u8 = 3;
+
+    /// The ticcmd as it comes back out of a demo without longtics: the turn
+    /// rounded to the nearest multiple of 256.
+    pub fn lowres(self) -> Self {
+        Ticcmd { angleturn: i16::from_be_bytes([((self.angleturn as i32 + 128) >> 8) as u8, 0]), ..self }
+    }
+
+    fn parse(b: &[u8], longtics: bool) -> Self {
+        let angleturn = if longtics { i16::from_le_bytes([b[2], b[3]]) } else { i16::from_be_bytes([b[2], 0]) };
+        Ticcmd { forwardmove: b[0] as i8, sidemove: b[1] as i8, angleturn, buttons: b[b.len() - 1] }
+    }
+}
+
+/// Per-tic game state checksums kept alongside a demo (`NAME.hashes`), one
+/// for the state after each tic; written and compared by the binary.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct StateHashes(pub Vec<u64>);
+
+const HASHES_HEADER: &str = "# rustydoom state hashes: tic, hash";
+
+impl StateHashes {
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let mut hashes = Vec::new();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parsed = line.split_once(' ').and_then(|(tic, hash)| {
+                Some((tic.parse::<usize>().ok()?, u64::from_str_radix(hash.trim(), 16).ok()?))
+            });
+            let Some((tic, hash)) = parsed else { bail!("line {}: expected `tic hash`", n + 1) };
+            if tic != hashes.len() + 1 {
+                bail!("line {}: tic {} out of order", n + 1, tic);
+            }
+            hashes.push(hash);
+        }
+        Ok(StateHashes(hashes))
+    }
+
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        let text = std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("parsing {}", path.display()))
+    }
+
+    pub fn write(&self, path: &Path) -> anyhow::Result<()> {
+        let mut text = format!("{}\n", HASHES_HEADER);
+        for (i, hash) in self.0.iter().enumerate() {
+            text += &format!("{} {:016x}\n", i + 1, hash);
+        }
+        std::fs::write(path, text).with_context(|| format!("writing {}", path.display()))
+    }
+
+    /// Index of the first tic where the two runs disagree, counting one
+    /// that stops early as disagreeing; None if they match throughout.
+    pub fn first_mismatch(&self, other: &StateHashes) -> Option<usize> {
+        let common = self.0.iter().zip(&other.0).position(|(a, b)| a != b);
+        common.or((self.0.len() != other.0.len()).then(|| self.0.len().min(other.0.len())))
+    }
+}
+
+/// Where a demo's hashes live by default: `demo.lmp` -> `demo.hashes`.
+pub fn hashes_path(demo: &Path) -> PathBuf {
+    demo.with_extension("hashes")
+}
@@ -1,24 +1,65 @@
-mod sys;
 mod winit_app;
 
-mod constants;
-use crate::constants::{APP_NAME, APP_VERSION, INITIAL_WIDTH, INITIAL_HEIGHT};
-
 mod cli;
-use cli:: { Cli, normalize_doom_args} ;
+use cli:: { Cli, Command, Frontend } ;
 use clap::Parser;
 use std::env;
 
 mod dg_io;
-use dg_io::{ DgIo, DoomHost };
+use dg_io::{ DgIo, DoomHost, Hotkeys };
+
+mod title;
+
+use rustydoom::args::normalize_doom_args;
+use rustydoom::config::{HostConfig, Scaler};
+use rustydoom::present::{FileSink, Presenter};
+use softbuffer_presenter::SoftbufferPresenter;
+use rustydoom::iwad::{self, Iwad};
+use rustydoom::bridge::{sys, vfs};
+use rustydoom::events;
+use rustydoom::constants::{INITIAL_HEIGHT, INITIAL_WIDTH};
+use std::path::Path;
+
+mod dehacked;
+
+mod saves;
+
+mod rewind;
+
+mod tas;
+
+mod config_cmd;
+
+mod playback;
+
+mod demos;
+
+mod desync;
+
+mod stats;
+
+mod event_log;
+
+mod wad_cmd;
+
+mod demo_cmd;
+
+mod script;
+
+mod softbuffer_presenter;
+
+mod test_cmd;
+
+mod terminal_frontend;
+
+mod fbdev_frontend;
 
 use std::ffi::CString;
-use std::num::NonZeroU32;
 use std::rc::Rc;
 use libc::{c_int, c_uint};
 
 use winit::dpi::LogicalSize;
-use winit::window:: {Window, WindowAttributes };
+use winit::window::WindowAttributes;
 use winit::event::{Event, KeyEvent, WindowEvent};
 use winit::event_loop::{EventLoop, ActiveEventLoop};
 use winit::keyboard::{Key, NamedKey};
@@ -27,23 +68,59 @@ struct State {
     window: Rc<winit::window::Window>,
     context: softbuffer::Context<Rc<winit::window::Window>>,
     io: DgIo,
+    title: String,        // last title pushed to the window
+    title_status: bool,   // append live game status to the title
+    scaler: Scaler,
+    dump_frames: Option<FileSink>, // also write every frame that ran tics here
 }
 
 fn main() -> anyhow::Result<()> {
     let cli = Cli::parse_from(normalize_doom_args(env::args()));
+
+    // Tool subcommands; `script` and `test` start the engine, without a window.
+    if let Some(cmd) = cli.command {
+        return match cmd {
+            Command::Wad { action } => wad_cmd::run(action),
+            Command::Saves { action } => saves::run(action),
+            Command::Config { action } => config_cmd::run(action),
+            Command::Demo { action } => demo_cmd::run(action),
+            Command::Script { script, iwad, file, deh } => script::run(&script, iwad.as_deref(), &file, &deh),
+            Command::Test { manifest, jobs, junit, update_golden, case } => {
+                test_cmd::run(&manifest, jobs, junit.as_deref(), update_golden, case.as_deref())
+            }
+        };
+    }
     
-    // Optional IWAD path; many builds will find it via DOOMWADDIR/cwd.
-    let iwad_path = cli.iwad.unwrap_or_default();
-    let c_iwad = CString::new(iwad_path).unwrap();
-    
+    // Resolve the IWAD ourselves so a missing one fails with a useful listing.
+    let iwad = resolve_iwad(cli.iwad.as_deref(), cli.positional_iwad.as_deref())?;
+    println!("IWAD: {}", iwad);
+    let iwad = vfs::mount_merged_iwad(&iwad, &cli.merge, &cli.nwtmerge)?;
+    let files = vfs::expand_files(&cli.file)?;
+    dehacked::load_all(&iwad, &cli.deh, &files)?;
+    let savedir = saves::resolve_dir(cli.savedir.as_deref(), &iwad)?;
+    let configdir = config_cmd::resolve_dir(cli.configdir.as_deref())?;
+    let settings = host_settings(&cli, &configdir)?;
+    let hotkeys = Hotkeys::from_bindings(&settings.bindings)?;
+
+    // What this run records about the game, from the first tic on.
+    desync::init(&cli);
+    stats::init(&cli, &iwad)?;
+    event_log::init(&cli)?;
+
     // Boot DoomGeneric and do two warmup ticks.
-    // TODO: Create and use an argc, argbv entry point for doomgeneric_Create(argc, argv)
-    let rc = unsafe { sys::raw::dg_create_simple(c_iwad.as_ptr()) };
-    if rc != 0 {
-        anyhow::bail!("dg_create_simple failed: {}", rc);
+    let mut args = engine_args(&iwad, &files, &savedir, &configdir, &settings, cli.loadgame);
+    args.extend(demos::engine_args(&cli));
+    boot(&args)?;
+    // Starting a demo runs its first tic already.
+    desync::after_tick();
+    tick();
+    tick();
+    // TAS mode rewinds tic by tic on its own, by replaying the timeline.
+    rewind::init(if cli.tas.is_some() { 0 } else { cli.rewind });
+    tas::start(&cli)?;
+    if cli.timedemo.is_some() {
+        playback::set_speed(playback::Speed::Max);
     }
-    unsafe { sys::raw::dg_tick() };
-    unsafe { sys::raw::dg_tick() };
 
     // Quick sanity check: read the framebuffer once and print some pixels.
     {
@@ -57,11 +134,106 @@ fn main() -> anyhow::Result<()> {
         );
     }
 
-    // Create window + run the app.
-    entry(EventLoop::new().unwrap());
+    if cli.verifydemo.is_some() {
+        return desync::verify(&cli, tick);
+    }
+
+    // Create window + run the app, or play in the terminal or on the framebuffer.
+    let dump_frames = cli.dump_frames.as_deref().map(FileSink::new).transpose()?;
+    match cli.frontend {
+        Frontend::Window => entry(EventLoop::new().unwrap(), settings, hotkeys, dump_frames),
+        Frontend::Terminal => terminal_frontend::run(&settings, hotkeys, dump_frames)?,
+        Frontend::Fbdev => fbdev_frontend::run(&settings, hotkeys, dump_frames, &cli.fbdev, &cli.evdev)?,
+    }
+    demos::finish(&cli)?;
+    tas::finish();
+    desync::finish(&cli)?;
+    config_cmd::save_engine();
+    Ok(())
+}
+
+/// Start the engine with exactly these arguments.
+fn boot(args: &[String]) -> anyhow::Result<()> {
+    let c_args = args.iter().map(|a| CString::new(a.as_str())).collect::<Result<Vec<_>, _>>()?;
+    let argv: Vec<*const libc::c_char> = c_args.iter().map(|a| a.as_ptr()).collect();
+    let rc = unsafe { sys::raw::dg_create(argv.len() as c_int, argv.as_ptr()) };
+    if rc != 0 {
+        anyhow::bail!("dg_create failed: {}", rc);
+    }
     Ok(())
 }
 
+/// One engine tic and everything that watches it; false once the demo the
+/// game was started for is over.
+fn tick() -> bool {
+    unsafe { sys::raw::dg_tick() };
+    rewind::after_tick();
+    desync::after_tick();
+    stats::after_tick();
+    events::dispatch();
+    test_cmd::after_tick();
+    !demos::finished()
+}
+
+/// rustydoom.toml, with command-line flags taking precedence.
+fn host_settings(cli: &Cli, configdir: &Path) -> anyhow::Result<HostConfig> {
+    let mut settings = config_cmd::load_host(configdir)?;
+    let window = &mut settings.window;
+    window.width = cli.width.unwrap_or(window.width);
+    window.height = cli.height.unwrap_or(window.height);
+    // 0 (from the file or the flags) leaves the size to us.
+    if window.width == 0 || window.height == 0 {
+        window.width = INITIAL_WIDTH;
+        window.height = INITIAL_HEIGHT;
+    }
+    window.fullscreen |= cli.fullscreen;
+    window.title_status |= cli.title_status;
+    Ok(settings)
+}
+
+/// Pick the IWAD: an explicit path or archive, then one baked into the binary,
+/// then the usual search.
+fn resolve_iwad(iwad: Option<&str>, positional: Option<&str>) -> anyhow::Result<Iwad> {
+    match iwad.or(positional) {
+        Some(p) if vfs::is_archive(Path::new(p)) => vfs::mount_archive_iwad(Path::new(p)),
+        Some(_) => iwad::locate(iwad, positional),
+        None => vfs::mount_embedded_iwad()
+            .unwrap_or_else(|| iwad::locate(None, None)),
+    }
+}
+
+/// The command line the engine sees. Everything is resolved on the Rust side,
+/// so paths here are either real files or VFS mounts.
+fn engine_args(
+    iwad: &Iwad,
+    files: &[String],
+    savedir: &Path,
+    configdir: &Path,
+    settings: &HostConfig,
+    loadgame: Option<u8>,
+) -> Vec<String> {
+    let mut args = vec!["rustydoom".to_string(), "-iwad".to_string(), iwad.path.to_string_lossy().into_owned()];
+    if !files.is_empty() {
+        args.push("-file".to_string());
+        args.extend(files.iter().cloned());
+    }
+    args.push("-savedir".to_string());
+    args.push(savedir.to_string_lossy().into_owned());
+    args.push("-configdir".to_string());
+    args.push(configdir.to_string_lossy().into_owned());
+    if !settings.audio.sfx {
+        args.push("-nosfx".to_string());
+    }
+    if !settings.audio.music {
+        args.push("-nomusic".to_string());
+    }
+    if let Some(slot) = loadgame {
+        args.push("-loadgame".to_string());
+        args.push(slot.to_string());
+    }
+    args
+}
+
 impl DoomHost for State {
     fn key_down(&self, _code: i32) {}
     fn key_up(&self, _code: i32) {}
@@ -69,6 +241,14 @@ impl DoomHost for State {
     fn mouse_move_rel(&self, _dx: f32, _dy: f32) {}
     fn mouse_move_abs(&self, _x: f32, _y: f32) {}
     fn mouse_wheel(&self, _lines: f32) {}
+    fn quick_save(&self) {}
+    fn quick_load(&self) {}
+    fn rewind(&self) {}
+    fn toggle_pause(&self) {}
+    fn step(&self) {}
+    fn fast_forward(&self) {}
+    fn tas_record(&self) {}
+    fn tas_save(&self) {}
 }
 
 struct DgHost;
@@ -80,10 +260,15 @@ impl DoomHost for DgHost {
     fn mouse_move_rel(&self, dx: f32, dy: f32) { unsafe { sys::raw::dg_mouse_move_rel(dx, dy) }; }
     fn mouse_move_abs(&self, x: f32, y: f32)   { unsafe { sys::raw::dg_mouse_move_abs(x, y) }; }
     fn mouse_wheel(&self, lines: f32)          { unsafe { sys::raw::dg_mouse_wheel(lines) }; }
-}
-
-fn app_title() -> String {
-    format!("{} v{}", APP_NAME, APP_VERSION)
+    // In TAS mode these work on the timeline instead.
+    fn quick_save(&self)                       { if tas::active() { tas::save_state() } else { saves::quick_save() } }
+    fn quick_load(&self)                       { if tas::active() { tas::load_state() } else { saves::quick_load() } }
+    fn rewind(&self)                           { if tas::active() { tas::step_back() } else { rewind::step_back() } }
+    fn toggle_pause(&self)                     { playback::toggle_pause(); }
+    fn step(&self)                             { playback::step(); }
+    fn fast_forward(&self)                     { playback::cycle_speed(); }
+    fn tas_record(&self)                       { tas::toggle_record(); }
+    fn tas_save(&self)                         { tas::save(); }
 }
 
 /// SAFETY NOTE:
@@ -104,86 +289,47 @@ fn fetch_doom_fb() -> Option<(&'static [u32], usize, usize)> {
     Some((fb, w, h))
 }
 
-/// Nearest-neighbor fit with aspect ratio preserved.
-/// dst: window backbuffer (row-major 0x00RRGGBB), size dw*dh
-/// src: Doom framebuffer (row-major), size sw*sh
-pub fn blit_nn_fit(dst: &mut [u32], dw: usize, dh: usize,
-                   src: &[u32], sw: usize, sh: usize) {
-    if dw == 0 || dh == 0 || sw == 0 || sh == 0 { return; }
-
-    // Choose target size that fits inside window and preserves aspect.
-    // Compare dw/sh vs dh/sw without floats.
-    let (tw, th) = if dw * sh <= dh * sw {
-        // limited by width
-        let tw = dw;
-        let th = (dw * sh) / sw;
-        (tw, th)
-    } else {
-        // limited by height
-        let th = dh;
-        let tw = (dh * sw) / sh;
-        (tw, th)
-    };
-
-    // Letterbox offsets
-    let x0 = (dw - tw) / 2;
-    let y0 = (dh - th) / 2;
-
-    // Clear to black
-    dst.fill(0x0000_0000);
-
-    // Fixed-point 16.16 stepping for nearest-neighbor
-    let x_step = ((sw as u32) << 16) / (tw as u32);
-    let y_step = ((sh as u32) << 16) / (th as u32);
-
-    for y in 0..th {
-        let sy = ((y as u32 * y_step) >> 16) as usize;
-        let src_row = &src[sy * sw .. (sy + 1) * sw];
-
-        let dst_row_start = (y0 + y) * dw + x0;
-        let dst_row = &mut dst[dst_row_start .. dst_row_start + tw];
-
-        let mut sx_fp: u32 = 0;
-        for dpx in dst_row.iter_mut() {
-            let sx = (sx_fp >> 16) as usize;
-            *dpx = src_row[sx];
-            sx_fp = sx_fp.wrapping_add(x_step);
-        }
-    }
-}
-
-
-pub(crate) fn entry(event_loop: EventLoop<()>) {
+pub(crate) fn entry(event_loop: EventLoop<()>, settings: HostConfig, hotkeys: Hotkeys, mut dump_frames: Option<FileSink>) {
     let app = winit_app::WinitAppBuilder::with_init(
-        |elwt| {
+        move |elwt| {
            // 1) Create window with an explicit initial size (logical, DPI-aware)
             let window = winit_app::make_window(elwt, |attrs: WindowAttributes| {
                 attrs
-                    .with_title(app_title())
+                    .with_title(title::app_title())
                     .with_inner_size(LogicalSize::new(
-                            INITIAL_WIDTH as f64,
-                            INITIAL_HEIGHT as f64,
+                            settings.window.width as f64,
+                            settings.window.height as f64,
                     ))
             });
             
             // 2) Create softbuffer context
             let context = softbuffer::Context::new(window.clone()).unwrap();
             
+            let mut io = DgIo::new(hotkeys);
+            if settings.window.fullscreen {
+                io.toggle_fullscreen(&window);
+            }
+
             //(window, context)
-            State { window, context, io: DgIo::new() }
+            State {
+                window,
+                context,
+                io,
+                title: String::new(),
+                title_status: settings.window.title_status,
+                scaler: settings.video.scaler,
+                dump_frames: dump_frames.take(),
+            }
         },
-        // 3) Create the surface AND perform an initial resize once
-       //|_elwt, (window, context)| softbuffer::Surface::new(context, window.clone()).unwrap(),
-       // Surface borrows the Context. Pass a reference.
-    |_, st: &mut State| {
-            softbuffer::Surface::new(&st.context, st.window.clone()).unwrap()
+        // 3) Create the presenter: the window's surface.
+        |_, st: &mut State| -> Box<dyn Presenter> {
+            Box::new(SoftbufferPresenter::new(&st.context, st.window.clone(), st.scaler).unwrap())
         },
     )
-    //.with_event_handler(|(window, _context), surface, event, elwt| {
     .with_event_handler(
-        |state: &mut State, 
-        surface: Option<&mut softbuffer::Surface<Rc<Window>, Rc<Window>>>, 
-        event: Event<()>, 
+        |state: &mut State,
+        presenter: Option<&mut Box<dyn Presenter>>,
+        event: Event<()>,
         elwt: &ActiveEventLoop| {
         let host = DgHost;                    // separate, stateless
         state.io.handle(&host, &state.window, &event);
@@ -192,51 +338,58 @@ pub(crate) fn entry(event_loop: EventLoop<()>) {
         //elwt.set_control_flow(ControlFlow::Wait);
         
         match event {
-            // Keep surface size in sync.
+            // Keep the presenter's size in sync.
             Event::WindowEvent { window_id, event: WindowEvent::Resized(size) }
                 if window_id == state.window.id() =>
             {
-                let Some(surface) = surface else {
+                let Some(presenter) = presenter else {
                     eprintln!("Resized fired before Resumed or after Suspended");
                     return;
                 };
-                if let (Some(w), Some(h)) = (NonZeroU32::new(size.width), NonZeroU32::new(size.height)) {
-                    surface.resize(w, h).unwrap();
-                }
+                presenter.resize(size.width, size.height).unwrap();
             }
 
-            // One frame: tick Doom -> fetch fb -> map buffer -> clear -> blit -> present -> request next redraw.
+            // One frame: tick Doom -> fetch fb -> present -> request next redraw.
             Event::WindowEvent { window_id, event: WindowEvent::RedrawRequested }
                 if window_id == state.window.id() =>
             {
-                let Some(surface) = surface else {
+                let Some(presenter) = presenter else {
                     eprintln!("RedrawRequested fired before Resumed or after Suspended");
                     return;
                 };
 
-                // 1) Advance one tic.
-                unsafe {
-                    sys::raw::dg_pump();  // flush input into Doom
-                    sys::raw::dg_tick();  // advance one tic
+                // 1) Advance the tics due this frame (one, at normal speed).
+                unsafe { sys::raw::dg_pump() };  // flush input into Doom
+                let tics = playback::run_frame(tick);
+                if demos::finished() {
+                    elwt.exit();
+                    return;
+                }
+
+                // Follow the engine's title (and game status) when it changes.
+                let title = title::window_title(state.title_status);
+                if title != state.title {
+                    state.window.set_title(&title);
+                    state.title = title;
                 }
 
                 // 2) Get Doom’s framebuffer for this tic.
                 let Some((fb, fb_w, fb_h)) = fetch_doom_fb() else { return; };
 
-                // 3) Map the backbuffer sized to the current window.
-                let size = state.window.inner_size();
-                let (Some(w), Some(h)) = (NonZeroU32::new(size.width), NonZeroU32::new(size.height)) else { return; };
-                // Note: we already resize on Resized; no need to call here unless you prefer to.
-                let mut backbuf = surface.buffer_mut().unwrap();
-                let dst_w = w.get() as usize;
-                let dst_h = h.get() as usize;
-                let dst: &mut [u32] = &mut backbuf;
-
-                // 4) Scale + letterbox into the backbuffer
-                blit_nn_fit(dst, dst_w, dst_h, fb, fb_w, fb_h);
-
-                // 5) Present, then request another redraw to keep things animating.
-                backbuf.present().unwrap();
+                // 3) Hand it over (scaled into the window), then request another redraw to keep things animating.
+                if let Err(e) = presenter.present(fb, fb_w, fb_h) {
+                    eprintln!("present: {:#}", e);
+                    elwt.exit();
+                    return;
+                }
+                // Redraws while paused show the same tic again; only dump new ones.
+                if let Some(sink) = state.dump_frames.as_mut().filter(|_| tics > 0)
+                    && let Err(e) = sink.present(fb, fb_w, fb_h)
+                {
+                    eprintln!("dump frames: {:#}", e);
+                    elwt.exit();
+                    return;
+                }
                 state.window.request_redraw();
             }
 
@@ -0,0 +1,160 @@
+// playback.rs
+//
+// Host-side pacing. The engine runs exactly one tic per dg_tick (singletics),
+// so the host decides how many tics each frame gets: 35 a second normally,
+// more when fast-forwarding, none while paused apart from single steps.
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const TICRATE: u64 = 35;
+/// At max speed, how long one frame may spend running tics.
+const MAX_FRAME: Duration = Duration::from_millis(25);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Speed {
+    Normal,
+    Double,
+    Quad,
+    /// As many tics as fit in a frame.
+    Max,
+}
+
+impl Speed {
+    fn multiplier(self) -> Option<u64> {
+        match self {
+            Speed::Normal => Some(1),
+            Speed::Double => Some(2),
+            Speed::Quad => Some(4),
+            Speed::Max => None,
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Speed::Normal => Speed::Double,
+            Speed::Double => Speed::Quad,
+            Speed::Quad => Speed::Max,
+            Speed::Max => Speed::Normal,
+        }
+    }
+}
+
+struct Pacer {
+    paused: bool,
+    /// Single tics requested while paused.
+    steps: u32,
+    speed: Speed,
+    // Tics run since `since`; the speed's rate is kept relative to it.
+    since: Option<Instant>,
+    tics: u64,
+}
+
+impl Pacer {
+    fn restart(&mut self) {
+        self.since = None;
+        self.tics = 0;
+    }
+}
+
+static PACER: Mutex<Pacer> = Mutex::new(Pacer { paused: false, steps: 0, speed: Speed::Normal, since: None, tics: 0 });
+
+pub fn set_speed(speed: Speed) {
+    let mut p = PACER.lock().unwrap();
+    p.speed = speed;
+    p.restart();
+}
+
+pub fn cycle_speed() {
+    let next = PACER.lock().unwrap().speed.next();
+    set_speed(next);
+}
+
+pub fn toggle_pause() {
+    let mut p = PACER.lock().unwrap();
+    p.paused = !p.paused;
+    p.steps = 0;
+    p.restart();
+}
+
+pub fn pause() {
+    let mut p = PACER.lock().unwrap();
+    p.paused = true;
+    p.steps = 0;
+}
+
+/// Advance a single tic; pauses first if running.
+pub fn step() {
+    let mut p = PACER.lock().unwrap();
+    if p.paused {
+        p.steps += 1;
+    } else {
+        p.paused = true;
+    }
+}
+
+/// "Paused", "2x", ... for the window title; None at normal speed.
+pub fn label() -> Option<String> {
+    let p = PACER.lock().unwrap();
+    match (p.paused, p.speed) {
+        (true, _) => Some("Paused".to_string()),
+        (false, Speed::Normal) => None,
+        (false, Speed::Max) => Some("Max".to_string()),
+        (false, s) => s.multiplier().map(|m| format!("{}x", m)),
+    }
+}
+
+/// Run the tics due this frame through `tick`, stopping early if it
+/// returns false, and say how many ran. At normal and 2x/4x speed, waits
+/// for the next tic when none is due yet; paused with nothing to step,
+/// waits a tic's time, so the frontends' loops check for input at the tic
+/// rate instead of spinning.
+pub fn run_frame(mut tick: impl FnMut() -> bool) -> u64 {
+    let mut p = PACER.lock().unwrap();
+    if p.paused {
+        let steps = std::mem::take(&mut p.steps);
+        drop(p);
+        if steps == 0 {
+            std::thread::sleep(Duration::from_micros(1_000_000 / TICRATE));
+        }
+        return run(steps.into(), tick);
+    }
+
+    let now = Instant::now();
+    let since = *p.since.get_or_insert(now);
+    let Some(mult) = p.speed.multiplier() else {
+        drop(p);
+        let mut ran = 1;
+        while tick() && now.elapsed() < MAX_FRAME {
+            ran += 1;
+        }
+        return ran;
+    };
+
+    let rate = TICRATE * mult;
+    let target = (now - since).as_micros() as u64 * rate / 1_000_000;
+    let mut due = target.saturating_sub(p.tics);
+    if due == 0 {
+        let next = since + Duration::from_micros((p.tics + 1) * 1_000_000 / rate);
+        std::thread::sleep(next.saturating_duration_since(now));
+        due = 1;
+    } else if due > 10 * mult {
+        // Fell behind (a screen wipe blocks for a second); carry on from
+        // here rather than racing to catch up.
+        p.restart();
+        p.since = Some(now);
+        due = 1;
+    }
+    p.tics += due;
+    drop(p);
+    run(due, tick)
+}
+
+/// Up to `tics` tics, fewer if `tick` says stop after one; how many ran.
+fn run(tics: u64, mut tick: impl FnMut() -> bool) -> u64 {
+    for ran in 1..=tics {
+        if !tick() {
+            return ran;
+        }
+    }
+    tics
+}
@@ -0,0 +1,137 @@
+// present.rs
+//
+// Where finished frames go. The window's event loop hands every frame to a
+// `Presenter` and doesn't care what it does with it: the binary's puts it
+// in a softbuffer window, `Offscreen` keeps it in memory for tests and
+// headless tools, and `FileSink` writes PNGs for video export. Frames are
+// the engine's 0x00RRGGBB pixels, row-major.
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+
+use crate::blit::blit_nn;
+use crate::config::Scaler;
+use crate::wad::graphics::{Image, write_png};
+
+pub trait Presenter {
+    /// The output is now `width` x `height` pixels.
+    fn resize(&mut self, width: u32, height: u32) -> anyhow::Result<()>;
+    /// Show one `width` x `height` frame.
+    fn present(&mut self, frame: &[u32], width: usize, height: usize) -> anyhow::Result<()>;
+}
+
+impl<P: Presenter + ?Sized> Presenter for Box<P> {
+    fn resize(&mut self, width: u32, height: u32) -> anyhow::Result<()> {
+        (**self).resize(width, height)
+    }
+
+    fn present(&mut self, frame: &[u32], width: usize, height: usize) -> anyhow::Result<()> {
+        (**self).present(frame, width, height)
+    }
+}
+
+/// Both, first one then the other: a window and a recording of it, say.
+impl<A: Presenter, B: Presenter> Presenter for (A, B) {
+    fn resize(&mut self, width: u32, height: u32) -> anyhow::Result<()> {
+        self.0.resize(width, height)?;
+        self.1.resize(width, height)
+    }
+
+    fn present(&mut self, frame: &[u32], width: usize, height: usize) -> anyhow::Result<()> {
+        self.0.present(frame, width, height)?;
+        self.1.present(frame, width, height)
+    }
+}
+
+/// Keeps the last frame in memory, scaled the way a window of its size
+/// would show it.
+#[derive(Debug, Clone)]
+pub struct Offscreen {
+    /// None: the frame's own size.
+    target: Option<(usize, usize)>,
+    scaler: Scaler,
+    pixels: Vec<u32>,
+    size: (usize, usize),
+    frames: u64,
+}
+
+impl Offscreen {
+    /// At the frame's own size until resized.
+    pub fn new(scaler: Scaler) -> Self {
+        Offscreen { target: None, scaler, pixels: Vec::new(), size: (0, 0), frames: 0 }
+    }
+
+    /// The last frame, `size()` pixels.
+    pub fn pixels(&self) -> &[u32] {
+        &self.pixels
+    }
+
+    /// Of the last frame; (0, 0) before the first.
+    pub fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    /// Frames presented so far.
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    pub fn image(&self) -> Image {
+        Image::from_xrgb(&self.pixels, self.size.0, self.size.1)
+    }
+}
+
+impl Presenter for Offscreen {
+    fn resize(&mut self, width: u32, height: u32) -> anyhow::Result<()> {
+        self.target = Some((width as usize, height as usize));
+        Ok(())
+    }
+
+    fn present(&mut self, frame: &[u32], width: usize, height: usize) -> anyhow::Result<()> {
+        let (w, h) = self.target.unwrap_or((width, height));
+        self.pixels.resize(w * h, 0);
+        blit_nn(&mut self.pixels, w, h, frame, width, height, self.scaler);
+        self.size = (w, h);
+        self.frames += 1;
+        Ok(())
+    }
+}
+
+/// Writes every frame at the engine's own size, whatever the window's, as
+/// `frame-000001.png`, `frame-000002.png`, ... in a directory; ffmpeg makes
+/// a video of them with `-framerate 35 -i frame-%06d.png`.
+#[derive(Debug)]
+pub struct FileSink {
+    dir: PathBuf,
+    frames: u64,
+}
+
+impl FileSink {
+    /// Creates `dir` if need be.
+    pub fn new(dir: &Path) -> anyhow::Result<Self> {
+        std::fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+        Ok(FileSink { dir: dir.to_path_buf(), frames: 0 })
+    }
+
+    /// Frames written so far.
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+}
+
+impl Presenter for FileSink {
+    fn resize(&mut self, _width: u32, _height: u32) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn present(&mut self, frame: &[u32], width: usize, height: usize) -> anyhow::Result<()> {
+        let path = self.dir.join(format!("frame-{:06}.png", self.frames + 1));
+        let file = File::create(&path).with_context(|| format!("writing {}", path.display()))?;
+        write_png(&Image::from_xrgb(frame, width, height), BufWriter::new(file))
+            .with_context(|| format!("writing {}", path.display()))?;
+        self.frames += 1;
+        Ok(())
+    }
+}
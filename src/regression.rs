@@ -0,0 +1,283 @@
+// regression.rs
+//
+// `rustydoom test` manifests: map regression cases, each naming what to
+// load, how to play it and what should come out; the comparison of
+// rendered frames against golden images; and the JUnit XML report CI
+// reads. Paths in a manifest are relative to the manifest.
+//
+//     iwad = "freedoom2.wad"      # defaults for every case
+//     files = ["mymap.wad"]
+//     timeout = 60                # seconds before a case is stopped and failed (default 300)
+//
+//     [[case]]
+//     name = "map01-exit"
+//     map = "MAP01"
+//     skill = 4
+//     script = "map01.rhai"       # or demo = "map01.lmp"; neither: the player stands still
+//     exit_by = 2100              # the level is finished within 2100 tics
+//
+//     [[case.frames]]
+//     tic = 35
+//     golden = "golden/map01-35.png"
+//     tolerance = 0.5             # mean difference per colour channel, 0-255
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, bail};
+use serde::{Deserialize, Serialize};
+
+use crate::wad::graphics::Image;
+
+/// How long a case may run when the manifest doesn't say.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Manifest {
+    pub cases: Vec<Case>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Case {
+    pub name: String,
+    pub iwad: Option<String>,
+    pub files: Vec<String>,
+    pub deh: Vec<String>,
+    /// Warped to before the input starts; None for demos, which say themselves.
+    pub map: Option<String>,
+    /// 1-5.
+    pub skill: u8,
+    pub input: Input,
+    /// Stop a demo or idle case after this many tics; by default a demo
+    /// runs to its end and an idle case to the last tic a check needs.
+    /// Scripts decide for themselves.
+    pub tics: Option<u32>,
+    /// The level must be finished within this many tics.
+    pub exit_by: Option<u32>,
+    pub frames: Vec<FrameCheck>,
+    /// Wall-clock time the case's process gets before it is killed.
+    pub timeout: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Input {
+    /// Nobody touches the controls.
+    Idle,
+    /// A Rhai script, as for `rustydoom script`.
+    Script(PathBuf),
+    Demo(PathBuf),
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FrameCheck {
+    /// Tics into the case (into the demo, for demos).
+    pub tic: u32,
+    pub golden: PathBuf,
+    /// Largest mean difference per colour channel that still passes.
+    #[serde(default)]
+    pub tolerance: f64,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestFile {
+    iwad: Option<String>,
+    #[serde(default)]
+    files: Vec<String>,
+    #[serde(default)]
+    deh: Vec<String>,
+    skill: Option<u8>,
+    timeout: Option<f64>,
+    #[serde(default, rename = "case")]
+    cases: Vec<CaseFile>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CaseFile {
+    name: String,
+    iwad: Option<String>,
+    files: Option<Vec<String>>,
+    deh: Option<Vec<String>>,
+    map: Option<String>,
+    skill: Option<u8>,
+    script: Option<PathBuf>,
+    demo: Option<PathBuf>,
+    tics: Option<u32>,
+    exit_by: Option<u32>,
+    timeout: Option<f64>,
+    #[serde(default)]
+    frames: Vec<FrameCheck>,
+}
+
+impl Manifest {
+    /// `base` is where relative paths start from.
+    pub fn parse(text: &str, base: &Path) -> anyhow::Result<Self> {
+        let file: ManifestFile = toml::from_str(text)?;
+        let rel = |p: &Path| base.join(p);
+        let rel_str = |s: &String| rel(Path::new(s)).to_string_lossy().into_owned();
+        let mut names = HashSet::new();
+        let mut cases = Vec::new();
+        for c in file.cases {
+            let name = c.name;
+            if !names.insert(name.clone()) {
+                bail!("case {:?} appears twice", name);
+            }
+            let input = match (c.script, c.demo) {
+                (Some(_), Some(_)) => bail!("case {}: script and demo both given", name),
+                (Some(s), None) => Input::Script(rel(&s)),
+                (None, Some(d)) => Input::Demo(rel(&d)),
+                (None, None) => Input::Idle,
+            };
+            match (&input, &c.map) {
+                (Input::Demo(_), Some(_)) => bail!("case {}: a demo starts on its own map", name),
+                (Input::Idle, None) => bail!("case {}: needs a map, script or demo", name),
+                _ => {}
+            }
+            if matches!(input, Input::Script(_)) && c.tics.is_some() {
+                bail!("case {}: a script runs to its own end, without tics", name);
+            }
+            if input == Input::Idle && c.tics.is_none() && c.exit_by.is_none() && c.frames.is_empty() {
+                bail!("case {}: nothing to check (give tics, exit_by or frames)", name);
+            }
+            let skill = c.skill.or(file.skill).unwrap_or(3);
+            if !(1..=5).contains(&skill) {
+                bail!("case {}: skill must be 1-5, not {}", name, skill);
+            }
+            let timeout = match c.timeout.or(file.timeout) {
+                None => DEFAULT_TIMEOUT,
+                Some(secs) => Duration::try_from_secs_f64(secs)
+                    .ok()
+                    .filter(|t| !t.is_zero())
+                    .with_context(|| format!("case {}: timeout must be a positive number of seconds, not {}", name, secs))?,
+            };
+            let frames = c.frames.into_iter().map(|f| FrameCheck { golden: rel(&f.golden), ..f }).collect();
+            cases.push(Case {
+                name,
+                iwad: c.iwad.or(file.iwad.clone()).map(|i| rel_str(&i)),
+                files: c.files.unwrap_or_else(|| file.files.clone()).iter().map(rel_str).collect(),
+                deh: c.deh.unwrap_or_else(|| file.deh.clone()).iter().map(rel_str).collect(),
+                map: c.map,
+                skill,
+                input,
+                tics: c.tics,
+                exit_by: c.exit_by,
+                frames,
+                timeout,
+            });
+        }
+        if cases.is_empty() {
+            bail!("no [[case]] entries");
+        }
+        Ok(Manifest { cases })
+    }
+
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        let text = std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        let base = path.parent().unwrap_or(Path::new(""));
+        Self::parse(&text, base).with_context(|| format!("parsing {}", path.display()))
+    }
+}
+
+impl Case {
+    /// Where a demo or idle case stops, if nothing ends it first.
+    pub fn last_tic(&self) -> Option<u32> {
+        self.tics.or_else(|| {
+            let needed = self.exit_by.into_iter().chain(self.frames.iter().map(|f| f.tic)).max();
+            if self.input == Input::Idle { needed } else { None }
+        })
+    }
+}
+
+/// How a case went, as the worker process reports it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CaseResult {
+    pub name: String,
+    /// Empty when the case passed.
+    pub failures: Vec<String>,
+    pub tics: u32,
+    pub seconds: f64,
+}
+
+impl CaseResult {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Mean absolute difference per colour channel (alpha ignored), 0-255;
+/// None when the sizes differ.
+pub fn frame_difference(expected: &Image, actual: &Image) -> Option<f64> {
+    if (expected.width, expected.height) != (actual.width, actual.height) {
+        return None;
+    }
+    let pixels = expected.width * expected.height;
+    if pixels == 0 {
+        return Some(0.0);
+    }
+    let total: u64 = expected
+        .rgba
+        .chunks_exact(4)
+        .zip(actual.rgba.chunks_exact(4))
+        .map(|(a, b)| (0..3).map(|c| a[c].abs_diff(b[c]) as u64).sum::<u64>())
+        .sum();
+    Some(total as f64 / (pixels * 3) as f64)
+}
+
+/// A JUnit XML report of one suite.
+pub fn junit_xml(suite: &str, results: &[CaseResult]) -> String {
+    let failures = results.iter().filter(|r| !r.passed()).count();
+    let time: f64 = results.iter().map(|r| r.seconds).sum();
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(out, "<testsuites tests=\"{}\" failures=\"{}\" time=\"{:.3}\">", results.len(), failures, time);
+    let _ = writeln!(
+        out,
+        "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" time=\"{:.3}\">",
+        escape(suite),
+        results.len(),
+        failures,
+        time
+    );
+    for r in results {
+        let _ = write!(
+            out,
+            "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+            escape(&r.name),
+            escape(suite),
+            r.seconds
+        );
+        if r.passed() {
+            out.push_str("/>\n");
+            continue;
+        }
+        out.push_str(">\n");
+        let _ = writeln!(
+            out,
+            "      <failure message=\"{}\">{}</failure>",
+            escape(&r.failures[0]),
+            escape(&r.failures.join("\n"))
+        );
+        out.push_str("    </testcase>\n");
+    }
+    out.push_str("  </testsuite>\n</testsuites>\n");
+    out
+}
+
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            // XML 1.0 has no way to write most control characters.
+            c if c.is_control() && !matches!(c, '\n' | '\t' | '\r') => out.push('?'),
+            c => out.push(c),
+        }
+    }
+    out
+}
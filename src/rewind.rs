@@ -0,0 +1,112 @@
+// rewind.rs
+//
+// Level snapshots held in Rust memory (csrc/platform/snapshot.c does the
+// serializing) and the rewind ring built on top: one snapshot per second of
+// play, the oldest dropped once the ring is full, Backspace to step back.
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use anyhow::bail;
+use libc::size_t;
+
+use rustydoom::state;
+
+use crate::sys;
+
+const TICRATE: i32 = 35;
+
+/// Serialize the running level. None outside a level you're playing
+/// (menus, intermission, demos).
+pub fn capture() -> Option<Vec<u8>> {
+    let mut data: *mut u8 = std::ptr::null_mut();
+    let mut len: size_t = 0;
+    if unsafe { sys::raw::dg_snapshot_save(&mut data, &mut len) } != 0 {
+        return None;
+    }
+    // SAFETY: on success the bridge hands us a malloc'd buffer of `len` bytes.
+    let out = unsafe { std::slice::from_raw_parts(data, len) }.to_vec();
+    unsafe { sys::raw::dg_snapshot_free(data) };
+    Some(out)
+}
+
+/// Put the engine back in the state `capture` saw. Call between ticks. A
+/// snapshot that is damaged, or from another build, is refused and leaves
+/// the game as it was.
+pub fn restore(data: &[u8]) -> anyhow::Result<()> {
+    if unsafe { sys::raw::dg_snapshot_load(data.as_ptr(), data.len()) } != 0 {
+        bail!("snapshot can't be restored (outside a level, or not a whole snapshot)");
+    }
+    Ok(())
+}
+
+struct Snapshot {
+    level: (i32, i32), // (episode, map)
+    leveltime: i32,
+    data: Vec<u8>,
+}
+
+struct Ring {
+    snapshots: VecDeque<Snapshot>,
+    capacity: usize,
+    // Where the last capture (or restore) happened; the next one is due a second later.
+    last: Option<((i32, i32), i32)>,
+}
+
+static RING: Mutex<Ring> = Mutex::new(Ring { snapshots: VecDeque::new(), capacity: 0, last: None });
+
+/// Keep the last `seconds` of play. 0 turns rewind off.
+pub fn init(seconds: usize) {
+    RING.lock().unwrap().capacity = seconds;
+}
+
+/// Call after each engine tick; takes a snapshot when one is due.
+pub fn after_tick() {
+    let mut ring = RING.lock().unwrap();
+    if ring.capacity == 0 {
+        return;
+    }
+    let st = state::game_state();
+    if !st.in_level || st.paused || st.menu {
+        return;
+    }
+    let level = (st.episode, st.map);
+    let due = match ring.last {
+        Some((l, t)) => l != level || st.level_time < t || st.level_time - t >= TICRATE,
+        None => true,
+    };
+    if !due {
+        return;
+    }
+    let Some(data) = capture() else { return };
+    if ring.snapshots.len() == ring.capacity {
+        ring.snapshots.pop_front();
+    }
+    ring.snapshots.push_back(Snapshot { level, leveltime: st.level_time, data });
+    ring.last = Some((level, st.level_time));
+}
+
+/// Go back to the newest snapshot, skipping one taken only a moment ago so
+/// each press goes back a noticeable amount.
+pub fn step_back() {
+    let mut ring = RING.lock().unwrap();
+    let st = state::game_state();
+    let level = (st.episode, st.map);
+    if ring.snapshots.len() > 1
+        && let Some(s) = ring.snapshots.back()
+        && s.level == level
+        && st.level_time - s.leveltime < TICRATE / 2
+    {
+        ring.snapshots.pop_back();
+    }
+    let Some(snap) = ring.snapshots.pop_back() else {
+        eprintln!("rewind: nothing to go back to");
+        return;
+    };
+    match restore(&snap.data) {
+        Ok(()) => ring.last = Some((snap.level, snap.leveltime)),
+        Err(e) => {
+            eprintln!("rewind: {:#}", e);
+            ring.snapshots.push_back(snap);
+        }
+    }
+}
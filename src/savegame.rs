@@ -0,0 +1,103 @@
+// savegame.rs
+//
+// Vanilla savegame files as written by p_saveg.c. The host only ever needs the
+// header (description, version, skill and map) to list saves; the rest is the
+// engine's business.
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, bail};
+
+use crate::paths;
+
+/// Slots shown by the engine's load/save menus.
+pub const SLOTS: u8 = 6;
+
+/// The host's quicksave: the slot just past the menus', so it never
+/// overwrites one of the player's saves.
+pub const QUICK_SLOT: u8 = SLOTS;
+
+const DESCRIPTION_SIZE: usize = 24;
+const VERSION_SIZE: usize = 16;
+const MAX_PLAYERS: usize = 4;
+const HEADER_SIZE: usize = DESCRIPTION_SIZE + VERSION_SIZE + 3 + MAX_PLAYERS + 3;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SaveHeader {
+    pub description: String,
+    /// e.g. "version 109"
+    pub version: String,
+    pub skill: u8,
+    pub episode: u8,
+    pub map: u8,
+    pub players: [bool; MAX_PLAYERS],
+    /// Tics into the level.
+    pub leveltime: u32,
+}
+
+impl SaveHeader {
+    pub fn parse(data: &[u8]) -> anyhow::Result<Self> {
+        if data.len() < HEADER_SIZE {
+            bail!("truncated savegame ({} bytes)", data.len());
+        }
+        let text = |b: &[u8]| {
+            let end = b.iter().position(|&c| c == 0).unwrap_or(b.len());
+            String::from_utf8_lossy(&b[..end]).into_owned()
+        };
+        let version = text(&data[DESCRIPTION_SIZE..DESCRIPTION_SIZE + VERSION_SIZE]);
+        if !version.starts_with("version ") {
+            bail!("not a savegame (no version string)");
+        }
+        let rest = &data[DESCRIPTION_SIZE + VERSION_SIZE..HEADER_SIZE];
+        Ok(SaveHeader {
+            description: text(&data[..DESCRIPTION_SIZE]),
+            version,
+            skill: rest[0],
+            episode: rest[1],
+            map: rest[2],
+            players: [rest[3] != 0, rest[4] != 0, rest[5] != 0, rest[6] != 0],
+            leveltime: u32::from_be_bytes([0, rest[7], rest[8], rest[9]]),
+        })
+    }
+
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        let data = std::fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+        Self::parse(&data).with_context(|| format!("parsing {}", path.display()))
+    }
+
+    /// `commercial` is None when we don't know which game the save is for.
+    pub fn map_name(&self, commercial: Option<bool>) -> String {
+        match commercial {
+            Some(true) => format!("MAP{:02}", self.map),
+            Some(false) => format!("E{}M{}", self.episode, self.map),
+            None => format!("episode {} map {}", self.episode, self.map),
+        }
+    }
+
+    /// Level time as m:ss.
+    pub fn time(&self) -> String {
+        let secs = self.leveltime / 35;
+        format!("{}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+/// The file the engine uses for `slot` (P_SaveGameFile).
+pub fn slot_path(dir: &Path, slot: u8) -> PathBuf {
+    dir.join(format!("doomsav{}.dsg", slot))
+}
+
+/// Per-IWAD save directory under the XDG data dir, e.g.
+/// `~/.local/share/rustydoom/savegames/doom2.wad`.
+pub fn default_dir(iwad: &Path) -> Option<PathBuf> {
+    let name = iwad.file_name()?.to_string_lossy().to_ascii_lowercase();
+    Some(paths::data_home()?.join("rustydoom/savegames").join(name))
+}
+
+/// Every slot that has a file, the quicksave last, with its header or why it
+/// couldn't be read.
+pub fn list(dir: &Path) -> Vec<(u8, anyhow::Result<SaveHeader>)> {
+    (0..=QUICK_SLOT)
+        .map(|slot| (slot, slot_path(dir, slot)))
+        .filter(|(_, path)| path.is_file())
+        .map(|(slot, path)| (slot, SaveHeader::read(&path)))
+        .collect()
+}
@@ -0,0 +1,94 @@
+// saves.rs
+//
+// Savegames from the host side: where they live, the F6/F9 quicksave and
+// quickload hotkeys, and `rustydoom saves list`.
+use std::ffi::CString;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use rustydoom::iwad::{self, Iwad};
+use rustydoom::savegame::{self, QUICK_SLOT, SaveHeader};
+
+use crate::cli::SavesCommand;
+use rustydoom::state;
+
+use crate::sys;
+use crate::title;
+
+/// `--savedir` if given, else the per-IWAD directory; created if missing.
+pub fn resolve_dir(savedir: Option<&Path>, iwad: &Iwad) -> anyhow::Result<PathBuf> {
+    let dir = match savedir {
+        Some(d) => d.to_path_buf(),
+        None => savegame::default_dir(&iwad.path)
+            .context("no save directory: pass --savedir or set HOME/XDG_DATA_HOME")?,
+    };
+    std::fs::create_dir_all(&dir).with_context(|| format!("creating {}", dir.display()))?;
+    Ok(dir)
+}
+
+pub fn quick_save() {
+    let description = CString::new(format!("QUICKSAVE {}", state::game_state().map_name())).unwrap_or_default();
+    if unsafe { sys::raw::dg_save_game(QUICK_SLOT.into(), description.as_ptr()) } != 0 {
+        eprintln!("quicksave: you can only save during a level");
+    }
+}
+
+pub fn quick_load() {
+    if unsafe { sys::raw::dg_load_game(QUICK_SLOT.into()) } != 0 {
+        eprintln!("quickload: nothing quicksaved yet");
+    }
+}
+
+pub fn run(cmd: SavesCommand) -> anyhow::Result<()> {
+    match cmd {
+        SavesCommand::List { savedir, iwad } => list(savedir.as_deref(), iwad.as_deref()),
+    }
+}
+
+fn list(savedir: Option<&Path>, iwad: Option<&str>) -> anyhow::Result<()> {
+    // The IWAD picks the default directory and tells ExMy from MAPxx.
+    let (dir, commercial) = match savedir {
+        Some(d) => {
+            let found = iwad.map(|p| iwad::locate(Some(p), None)).transpose()?;
+            (d.to_path_buf(), found.map(|i| i.game.is_commercial()))
+        }
+        None => {
+            let found = iwad::locate(iwad, None)?;
+            let dir = savegame::default_dir(&found.path).context("no save directory: pass --savedir")?;
+            (dir, Some(found.game.is_commercial()))
+        }
+    };
+
+    let saves = savegame::list(&dir);
+    let mut out = io::stdout().lock();
+    if saves.is_empty() {
+        writeln!(out, "no savegames in {}", dir.display())?;
+        return Ok(());
+    }
+    writeln!(out, "{}", dir.display())?;
+    writeln!(out, "{:>5}  {:<24}  {:<8}  {:<20}  {:>6}  VERSION", "SLOT", "DESCRIPTION", "MAP", "SKILL", "TIME")?;
+    for (slot, header) in saves {
+        let slot = if slot == QUICK_SLOT { "quick".to_string() } else { slot.to_string() };
+        match header {
+            Ok(h) => writeln!(
+                out,
+                "{:>5}  {:<24}  {:<8}  {:<20}  {:>6}  {}",
+                slot,
+                h.description,
+                h.map_name(commercial),
+                skill_name(&h),
+                h.time(),
+                h.version
+            )?,
+            Err(e) => writeln!(out, "{:>5}  unreadable: {:#}", slot, e)?,
+        }
+    }
+    Ok(())
+}
+
+fn skill_name(h: &SaveHeader) -> String {
+    title::SKILL_NAMES
+        .get(h.skill as usize)
+        .map_or_else(|| format!("skill {}", h.skill), |s| s.to_string())
+}
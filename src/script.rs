@@ -0,0 +1,173 @@
+// script.rs
+//
+// `rustydoom script FILE.rhai`: drive the game from a Rhai script, for
+// tests that would otherwise mean playing the same stretch by hand. The
+// engine runs without a window on the virtual clock, so a script goes as
+// fast as the game simulates; a failed `assert`, or any other script error,
+// ends the run with a non-zero exit.
+//
+//     warp("E1M1", 4);                   // map, skill 1-5, optional RNG seed
+//     hold(#{ forward: 1.0, turn: 2.5 }); // until release(); turn is degrees a tic
+//     tick(35);
+//     let s = state();                   // rustydoom::state as maps
+//     assert(s.player.position.y > 100.0, "didn't get anywhere");
+//     let here = save();                 // an in-memory snapshot
+//     screenshot("e1m1.png");
+//     load(here);
+//
+// `mobjs()` lists the map objects the same way, and `hash()` is the
+// checksum of the game state that `--hashes` writes for each tic. Scripts get their own
+// engine settings in the temp dir, so the player's don't change the result.
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, anyhow, bail};
+use rhai::{Blob, Dynamic, Engine, EvalAltResult, Map};
+use rustydoom::config::{Audio, HostConfig};
+use rustydoom::env::{Action, parse_map};
+use rustydoom::wad::graphics::{Image, write_png};
+use rustydoom::{input, state};
+
+use crate::sys;
+use crate::{dehacked, rewind, vfs};
+
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+pub fn run(script: &Path, iwad: Option<&str>, files: &[String], deh: &[String]) -> anyhow::Result<()> {
+    let _engine = boot_headless(iwad, files, deh, &[])?;
+    run_file(script)
+}
+
+/// The engine settings directory of a headless run, removed when dropped.
+pub struct Headless {
+    dir: PathBuf,
+}
+
+impl Drop for Headless {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Start the engine without a window, on the virtual clock and with engine
+/// settings of its own in a temp dir for this process, there until the
+/// returned guard goes; `extra` engine arguments go last.
+pub fn boot_headless(iwad: Option<&str>, files: &[String], deh: &[String], extra: &[String]) -> anyhow::Result<Headless> {
+    let iwad = crate::resolve_iwad(iwad, None)?;
+    let files = vfs::expand_files(files)?;
+    dehacked::load_all(&iwad, deh, &files)?;
+    let dir = std::env::temp_dir().join(format!("rustydoom-script-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).with_context(|| format!("creating {}", dir.display()))?;
+    let headless = Headless { dir };
+    let settings = HostConfig { audio: Audio { sfx: false, music: false }, ..Default::default() };
+
+    let mut args = crate::engine_args(&iwad, &files, &headless.dir, &headless.dir, &settings, None);
+    args.extend_from_slice(extra);
+    unsafe { sys::raw::dg_use_virtual_clock() };
+    crate::boot(&args)?;
+    crate::tick();
+    crate::tick();
+    Ok(headless)
+}
+
+/// Run a script against the engine `boot_headless` started.
+pub fn run_file(script: &Path) -> anyhow::Result<()> {
+    let source = std::fs::read_to_string(script).with_context(|| format!("reading {}", script.display()))?;
+    let engine = engine();
+    let ast = engine.compile(&source).map_err(|e| anyhow!("{}: {}", script.display(), e))?;
+    engine.run_ast(&ast).map_err(|e| anyhow!("{}: {}", script.display(), e))
+}
+
+fn engine() -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .register_fn("tick", || tick(1))
+        .register_fn("tick", tick)
+        .register_fn("warp", |map: &str| warp(map, 3, 0))
+        .register_fn("warp", |map: &str, skill: i64| warp(map, skill, 0))
+        .register_fn("warp", warp)
+        .register_fn("hold", hold)
+        .register_fn("release", input::release)
+        .register_fn("state", || rhai::serde::to_dynamic(state::game_state()))
+        .register_fn("mobjs", || rhai::serde::to_dynamic(state::mobjs()))
+        .register_fn("hash", || format!("{:016x}", unsafe { sys::raw::dg_state_hash() }))
+        .register_fn("screenshot", screenshot)
+        .register_fn("save", save)
+        .register_fn("load", load)
+        .register_fn("assert", |ok: bool| check(ok, "assertion failed"))
+        .register_fn("assert", |ok: bool, message: &str| check(ok, &format!("assertion failed: {}", message)));
+    engine
+}
+
+fn check(ok: bool, message: &str) -> ScriptResult<()> {
+    if ok { Ok(()) } else { Err(message.into()) }
+}
+
+fn tick(tics: i64) {
+    for _ in 0..tics {
+        crate::tick();
+    }
+}
+
+fn warp(map: &str, skill: i64, seed: i64) -> ScriptResult<()> {
+    new_game(map, skill, seed).map_err(|e| e.to_string().into())
+}
+
+/// Start `map` ("E1M1", "MAP01") at `skill` 1-5; `seed` is where the
+/// random number table starts, 0 as in vanilla.
+pub fn new_game(map: &str, skill: i64, seed: i64) -> anyhow::Result<()> {
+    let commercial = state::game_state().commercial;
+    let Some((episode, map_num)) = parse_map(map, commercial) else {
+        bail!("bad map {:?}, expected {}", map, if commercial { "MAPxx" } else { "ExMy" });
+    };
+    if !(1..=5).contains(&skill) || !(0..=255).contains(&seed) {
+        bail!("skill must be 1-5 and seed 0-255, not {} and {}", skill, seed);
+    }
+    if unsafe { sys::raw::dg_new_game(skill as i32 - 1, episode, map_num, seed as i32) } != 0 {
+        bail!("{} isn't in the loaded WADs", map.to_uppercase());
+    }
+    Ok(())
+}
+
+/// Hold movement for every tic from now on: `forward` and `strafe` from -1
+/// to 1 (full running speed), `turn` in degrees a tic (left positive), and
+/// `attack`/`use` buttons.
+fn hold(controls: Map) -> ScriptResult<()> {
+    let (mut forward, mut strafe, mut turn, mut attack, mut use_) = (0.0, 0.0, 0.0, false, false);
+    for (key, value) in controls {
+        let number = || value.as_float().or_else(|_| value.as_int().map(|i| i as f64));
+        let flag = || value.as_bool();
+        let bad = |_| format!("hold: {} has the wrong type ({})", key, value.type_name());
+        match key.as_str() {
+            "forward" => forward = number().map_err(bad)?,
+            "strafe" => strafe = number().map_err(bad)?,
+            "turn" => turn = number().map_err(bad)?,
+            "attack" => attack = flag().map_err(bad)?,
+            "use" => use_ = flag().map_err(bad)?,
+            _ => return Err(format!("hold: unknown control {:?}, expected forward, strafe, turn, attack or use", key).into()),
+        }
+    }
+    let action = Action::Continuous { forward: forward as f32, strafe: strafe as f32, turn: turn as f32, attack, use_ };
+    input::hold(action.to_ticcmd());
+    Ok(())
+}
+
+/// Write the current frame to a PNG.
+fn screenshot(path: &str) -> ScriptResult<()> {
+    let (fb, width, height) = crate::fetch_doom_fb().ok_or("screenshot: no frame yet")?;
+    let img = Image::from_xrgb(fb, width, height);
+    File::create(path)
+        .map_err(anyhow::Error::from)
+        .and_then(|f| write_png(&img, BufWriter::new(f)))
+        .map_err(|e| format!("screenshot {}: {:#}", path, e).into())
+}
+
+fn save() -> ScriptResult<Dynamic> {
+    let data = rewind::capture().ok_or("save: only possible in a level")?;
+    Ok(Dynamic::from_blob(data))
+}
+
+fn load(snapshot: Blob) -> ScriptResult<()> {
+    rewind::restore(&snapshot).map_err(|e| format!("load: {:#}", e).into())
+}
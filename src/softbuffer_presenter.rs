@@ -0,0 +1,50 @@
+// softbuffer_presenter.rs
+//
+// The default presenter: frames scaled into a winit window through a
+// softbuffer surface.
+use std::num::NonZeroU32;
+use std::rc::Rc;
+
+use anyhow::anyhow;
+use rustydoom::blit::blit_nn;
+use rustydoom::config::Scaler;
+use rustydoom::present::Presenter;
+use winit::window::Window;
+
+pub struct SoftbufferPresenter {
+    window: Rc<Window>,
+    surface: softbuffer::Surface<Rc<Window>, Rc<Window>>,
+    scaler: Scaler,
+}
+
+impl SoftbufferPresenter {
+    pub fn new(context: &softbuffer::Context<Rc<Window>>, window: Rc<Window>, scaler: Scaler) -> anyhow::Result<Self> {
+        let surface = softbuffer::Surface::new(context, window.clone()).map_err(err)?;
+        Ok(SoftbufferPresenter { window, surface, scaler })
+    }
+}
+
+impl Presenter for SoftbufferPresenter {
+    fn resize(&mut self, width: u32, height: u32) -> anyhow::Result<()> {
+        if let (Some(w), Some(h)) = (NonZeroU32::new(width), NonZeroU32::new(height)) {
+            self.surface.resize(w, h).map_err(err)?;
+        }
+        Ok(())
+    }
+
+    fn present(&mut self, frame: &[u32], width: usize, height: usize) -> anyhow::Result<()> {
+        // Map the backbuffer sized to the current window; a minimized one has none.
+        let size = self.window.inner_size();
+        if size.width == 0 || size.height == 0 {
+            return Ok(());
+        }
+        let mut backbuf = self.surface.buffer_mut().map_err(err)?;
+        blit_nn(&mut backbuf, size.width as usize, size.height as usize, frame, width, height, self.scaler);
+        backbuf.present().map_err(err)
+    }
+}
+
+/// softbuffer's errors can hold raw window handles, which aren't Send.
+fn err(e: softbuffer::SoftBufferError) -> anyhow::Error {
+    anyhow!("{}", e)
+}
@@ -0,0 +1,240 @@
+// state.rs
+//
+// A read-only view of the running game: what is being played, the console
+// player, the level's totals and its map objects, as of the end of the last
+// tic. The engine copies them the first time they're asked for after a tic
+// (csrc/platform/game_state.c), so ticking costs nothing when nobody asks,
+// and reading never touches the live game.
+use std::ffi::CStr;
+
+use serde::Serialize;
+
+use crate::events::Weapon;
+use crate::sys;
+
+/// The engine's copies, as `GameState::from_raw` and `Mobj::from_raw` take them.
+pub use crate::sys::{DgGameState, DgMobj};
+
+// GameMode_t, gamestate_t and mobjflag_t values.
+const MODE_COMMERCIAL: i32 = 2;
+const GS_LEVEL: i32 = 0;
+const GS_DEMOSCREEN: i32 = 3;
+const MF_COUNTKILL: i32 = 0x400000;
+const MF_COUNTITEM: i32 = 0x800000;
+
+/// Map units per fixed_t unit.
+const FRACUNIT: f64 = 65536.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GameState {
+    /// Tics run since the engine started (gametic).
+    pub tic: i32,
+    /// GameMission_t: 0 Doom, 1 Doom II, 2 TNT: Evilution, 3 Plutonia.
+    pub mission: i32,
+    /// Maps are MAPxx (Doom II and Final Doom) rather than ExMy.
+    pub commercial: bool,
+    /// In a level, rather than on the title screen, intermission or finale.
+    pub in_level: bool,
+    /// On the title pages between the attract loop's demos.
+    pub title_screen: bool,
+    /// Paused in the game (the pause key), not by the host.
+    pub paused: bool,
+    /// The menu is up.
+    pub menu: bool,
+    pub episode: i32,
+    pub map: i32,
+    /// 1-5.
+    pub skill: i32,
+    /// Tics since the level started.
+    pub level_time: i32,
+    /// What the level has to find, as on the intermission screen.
+    pub totals: Totals,
+    pub player: Player,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Totals {
+    pub kills: i32,
+    pub items: i32,
+    pub secrets: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Player {
+    pub dead: bool,
+    pub health: i32,
+    pub armor: i32,
+    /// 0 none, 1 green, 2 blue.
+    pub armor_type: i32,
+    pub ammo: Ammo,
+    pub max_ammo: Ammo,
+    /// The weapon in hand.
+    pub weapon: Option<Weapon>,
+    pub weapons: Vec<Weapon>,
+    pub keys: Vec<Key>,
+    pub position: Position,
+    /// Degrees counterclockwise from east.
+    pub angle: f64,
+    pub kills: i32,
+    pub items: i32,
+    pub secrets: i32,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Ammo {
+    pub bullets: i32,
+    pub shells: i32,
+    pub cells: i32,
+    pub rockets: i32,
+}
+
+impl Ammo {
+    fn from_raw(ammo: [i32; 4]) -> Self {
+        let [bullets, shells, cells, rockets] = ammo;
+        Ammo { bullets, shells, cells, rockets }
+    }
+}
+
+/// In map units.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Position {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl Position {
+    fn from_fixed(x: i32, y: i32, z: i32) -> Self {
+        Position { x: x as f64 / FRACUNIT, y: y as f64 / FRACUNIT, z: z as f64 / FRACUNIT }
+    }
+}
+
+/// card_t.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Key {
+    BlueCard,
+    YellowCard,
+    RedCard,
+    BlueSkull,
+    YellowSkull,
+    RedSkull,
+}
+
+const KEYS: [Key; 6] = [Key::BlueCard, Key::YellowCard, Key::RedCard, Key::BlueSkull, Key::YellowSkull, Key::RedSkull];
+
+/// A thing on the map: monster, item, decoration, projectile, or the
+/// players themselves.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Mobj {
+    /// mobjtype_t, the engine's own numbering.
+    pub kind: i32,
+    /// The thing number map editors use; None for things only spawned in
+    /// play (projectiles, puffs, ...).
+    pub doomednum: Option<i32>,
+    /// Sprite name, like "POSS" or "MEDI".
+    pub sprite: String,
+    pub position: Position,
+    /// Degrees counterclockwise from east.
+    pub angle: f64,
+    pub health: i32,
+    /// MF_* in p_mobj.h.
+    pub flags: i32,
+}
+
+impl Mobj {
+    pub fn from_raw(mo: &DgMobj) -> Self {
+        // SAFETY: the bridge always NUL-terminates `sprite` within the array.
+        let sprite = unsafe { CStr::from_ptr(mo.sprite.as_ptr()) }.to_string_lossy().into_owned();
+        Mobj {
+            kind: mo.kind,
+            doomednum: (mo.doomednum >= 0).then_some(mo.doomednum),
+            sprite,
+            position: Position::from_fixed(mo.x, mo.y, mo.z),
+            angle: degrees(mo.angle),
+            health: mo.health,
+            flags: mo.flags,
+        }
+    }
+
+    /// Counts towards the level's kills.
+    pub fn is_monster(&self) -> bool {
+        self.flags & MF_COUNTKILL != 0
+    }
+
+    /// Counts towards the level's items.
+    pub fn is_item(&self) -> bool {
+        self.flags & MF_COUNTITEM != 0
+    }
+
+    /// A monster that hasn't been killed yet.
+    pub fn is_live_monster(&self) -> bool {
+        self.is_monster() && self.health > 0
+    }
+}
+
+fn degrees(angle: u32) -> f64 {
+    angle as f64 * 360.0 / 4294967296.0
+}
+
+impl GameState {
+    /// "E1M3", or "MAP07" in Doom II and Final Doom.
+    pub fn map_name(&self) -> String {
+        if self.commercial { format!("MAP{:02}", self.map) } else { format!("E{}M{}", self.episode, self.map) }
+    }
+
+    pub fn from_raw(st: &DgGameState) -> Self {
+        let weapons = (0..st.weaponowned.len() as i32)
+            .filter(|&i| st.weaponowned[i as usize] != 0)
+            .filter_map(Weapon::from_index)
+            .collect();
+        let keys = KEYS.iter().zip(st.cards).filter(|&(_, owned)| owned != 0).map(|(&k, _)| k).collect();
+        GameState {
+            tic: st.gametic,
+            mission: st.mission,
+            commercial: st.mode == MODE_COMMERCIAL,
+            in_level: st.gamestate == GS_LEVEL,
+            title_screen: st.gamestate == GS_DEMOSCREEN,
+            paused: st.paused != 0,
+            menu: st.menu != 0,
+            episode: st.episode,
+            map: st.map,
+            skill: st.skill + 1,
+            level_time: st.leveltime,
+            totals: Totals { kills: st.totalkills, items: st.totalitems, secrets: st.totalsecret },
+            player: Player {
+                dead: st.dead != 0,
+                health: st.health,
+                armor: st.armor,
+                armor_type: st.armortype,
+                ammo: Ammo::from_raw(st.ammo),
+                max_ammo: Ammo::from_raw(st.maxammo),
+                weapon: Weapon::from_index(st.weapon),
+                weapons,
+                keys,
+                position: Position::from_fixed(st.x, st.y, st.z),
+                angle: degrees(st.angle),
+                kills: st.kills,
+                items: st.items,
+                secrets: st.secrets,
+            },
+        }
+    }
+}
+
+/// The game as of the end of the last tic.
+pub fn game_state() -> GameState {
+    let mut st = DgGameState::default();
+    unsafe { sys::raw::dg_game_state(&mut st) };
+    GameState::from_raw(&st)
+}
+
+/// Every map object as of the end of the last tic, in the engine's thinker
+/// order; empty outside a level.
+pub fn mobjs() -> Vec<Mobj> {
+    let count = unsafe { sys::raw::dg_mobjs(std::ptr::null_mut(), 0) };
+    let mut raw = vec![DgMobj::default(); count.max(0) as usize];
+    let copied = unsafe { sys::raw::dg_mobjs(raw.as_mut_ptr(), raw.len() as i32) };
+    raw.truncate(copied.max(0) as usize);
+    raw.iter().map(Mobj::from_raw).collect()
+}
@@ -0,0 +1,122 @@
+// stats.rs
+//
+// --stats-out: the intermission screen's numbers for every finished level
+// (captured by statdump.c) appended to a file, one JSON object per line, so
+// results from many runs and demos can be collected in one place.
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+
+use anyhow::Context;
+use rustydoom::iwad::Iwad;
+use rustydoom::state::{self, GameState};
+use serde::Serialize;
+
+use crate::cli::Cli;
+use crate::sys::{self, DgLevelStats};
+
+const TICRATE: i32 = 35;
+
+struct Output {
+    file: File,
+    iwad: String,
+    demo: Option<String>,
+}
+
+static OUTPUT: Mutex<Option<Output>> = Mutex::new(None);
+
+#[derive(Serialize)]
+struct LevelRecord<'a> {
+    iwad: &'a str,
+    /// The demo being recorded or played, if any.
+    demo: Option<&'a str>,
+    level: String,
+    episode: i32,
+    map: i32,
+    skill: i32,
+    next: String,
+    secret_exit: bool,
+    time_tics: i32,
+    time: String,
+    par_tics: i32,
+    par: String,
+    totals: Totals,
+    players: Vec<PlayerRecord>,
+}
+
+#[derive(Serialize)]
+struct Totals {
+    kills: i32,
+    items: i32,
+    secrets: i32,
+}
+
+#[derive(Serialize)]
+struct PlayerRecord {
+    /// 1-4, green, indigo, brown, red.
+    player: usize,
+    kills: i32,
+    items: i32,
+    secrets: i32,
+    frags: [i32; 4],
+}
+
+/// Open --stats-out (appending) and have the engine start capturing.
+pub fn init(cli: &Cli, iwad: &Iwad) -> anyhow::Result<()> {
+    let Some(path) = &cli.stats_out else { return Ok(()) };
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("opening {}", path.display()))?;
+    let iwad = iwad.path.file_name().unwrap_or(iwad.path.as_os_str()).to_string_lossy().into_owned();
+    let demo = [&cli.record, &cli.playdemo, &cli.timedemo, &cli.verifydemo]
+        .into_iter()
+        .find_map(|d| d.as_deref())
+        .map(|d| Path::new(d).file_name().map_or(d.into(), |f| f.to_string_lossy().into_owned()));
+    *OUTPUT.lock().unwrap() = Some(Output { file, iwad, demo });
+    unsafe { sys::raw::dg_stats_capture(1) };
+    Ok(())
+}
+
+/// Call after each engine tick; writes out any level finished in it.
+pub fn after_tick() {
+    let mut output = OUTPUT.lock().unwrap();
+    let Some(out) = output.as_mut() else { return };
+    let mut level = DgLevelStats::default();
+    while unsafe { sys::raw::dg_stats_take(&mut level) } != 0 {
+        let line = serde_json::to_string(&record(out, &level)).expect("level stats serialize");
+        if let Err(e) = writeln!(out.file, "{}", line).and_then(|_| out.file.flush()) {
+            eprintln!("stats-out: {}", e);
+        }
+    }
+}
+
+fn record<'a>(out: &'a Output, level: &DgLevelStats) -> LevelRecord<'a> {
+    let st = state::game_state();
+    let name = |map| GameState { episode: level.episode, map, ..st.clone() }.map_name();
+    let time = |tics: i32| format!("{}:{:02}", tics / TICRATE / 60, tics / TICRATE % 60);
+    LevelRecord {
+        iwad: &out.iwad,
+        demo: out.demo.as_deref(),
+        level: name(level.map),
+        episode: level.episode,
+        map: level.map,
+        skill: st.skill,
+        next: name(level.next),
+        secret_exit: level.didsecret != 0,
+        time_tics: level.time,
+        time: time(level.time),
+        par_tics: level.partime,
+        par: time(level.partime),
+        totals: Totals { kills: level.maxkills, items: level.maxitems, secrets: level.maxsecret },
+        players: (0..4)
+            .filter(|&i| level.players[i].in_game != 0)
+            .map(|i| {
+                let p = &level.players[i];
+                PlayerRecord { player: i + 1, kills: p.kills, items: p.items, secrets: p.secrets, frags: p.frags }
+            })
+            .collect(),
+    }
+}
@@ -1,9 +1,127 @@
-use libc::{c_char, c_int, c_uint, c_float};
+use libc::{c_char, c_int, c_uint, c_float, size_t};
+
+/// Mirrors `dg_demo_status_t` in dg_bridge.h.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DgDemoStatus {
+    pub playback: c_int,
+    pub recording: c_int,
+    pub finished: c_int,
+    pub gametics: c_int,
+    pub realtics: c_int,
+    pub tics: c_int,
+}
+
+/// Mirrors `dg_ticcmd_t` in dg_bridge.h.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DgTiccmd {
+    pub forwardmove: i8,
+    pub sidemove: i8,
+    pub angleturn: i16,
+    pub buttons: u8,
+}
+
+/// Mirrors `dg_game_state_t` in dg_bridge.h.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DgGameState {
+    pub gametic: c_int,
+    pub mission: c_int,
+    pub mode: c_int,
+    pub gamestate: c_int,
+    pub episode: c_int,
+    pub map: c_int,
+    pub skill: c_int,
+    pub leveltime: c_int,
+    pub paused: c_int,
+    pub menu: c_int,
+    pub totalkills: c_int,
+    pub totalitems: c_int,
+    pub totalsecret: c_int,
+    pub ingame: c_int,
+    pub dead: c_int,
+    pub health: c_int,
+    pub armor: c_int,
+    pub armortype: c_int,
+    pub ammo: [c_int; 4],
+    pub maxammo: [c_int; 4],
+    pub weapon: c_int,
+    pub weaponowned: [c_int; 9],
+    pub cards: [c_int; 6],
+    pub x: c_int,
+    pub y: c_int,
+    pub z: c_int,
+    pub angle: u32,
+    pub kills: c_int,
+    pub items: c_int,
+    pub secrets: c_int,
+    pub mobjs: c_int,
+}
+
+/// Mirrors `dg_mobj_t` in dg_bridge.h.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DgMobj {
+    pub kind: c_int,
+    pub doomednum: c_int,
+    pub x: c_int,
+    pub y: c_int,
+    pub z: c_int,
+    pub angle: u32,
+    pub health: c_int,
+    pub flags: c_int,
+    pub sprite: [c_char; 5],
+}
+
+/// Mirrors `dg_player_stats_t` in dg_bridge.h.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DgPlayerStats {
+    pub in_game: c_int,
+    pub kills: c_int,
+    pub items: c_int,
+    pub secrets: c_int,
+    pub frags: [c_int; 4],
+}
+
+/// Mirrors `dg_level_stats_t` in dg_bridge.h.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DgLevelStats {
+    pub episode: c_int,
+    pub map: c_int,
+    pub next: c_int,
+    pub didsecret: c_int,
+    pub maxkills: c_int,
+    pub maxitems: c_int,
+    pub maxsecret: c_int,
+    pub time: c_int,
+    pub partime: c_int,
+    pub pnum: c_int,
+    pub players: [DgPlayerStats; 4],
+}
+
+/// Mirrors `dg_event_t` in dg_bridge.h; `kind` is a `DG_EV_*` value.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DgEvent {
+    pub kind: c_int,
+    pub tic: c_int,
+    pub player: c_int,
+    pub a: c_int,
+    pub b: c_int,
+    pub c: c_int,
+    pub name: [c_char; 9],
+}
 
 pub mod raw {
     use super::*;
     unsafe extern "C" {
-        pub fn dg_create_simple(iwad_path: *const c_char) -> c_int;
+        pub fn dg_create(argc: c_int, argv: *const *const c_char) -> c_int;
+        /// Count time in tics rather than on the wall clock. Before dg_create only.
+        pub fn dg_use_virtual_clock();
+        /// Run one game tic and redraw. The host decides how often.
         pub fn dg_tick();
         pub fn dg_framebuffer32(w: *mut c_int, h: *mut c_int) -> *const c_uint;
         pub fn dg_key_down(code: c_int);
@@ -14,5 +132,53 @@ pub mod raw {
         pub fn dg_mouse_wheel(lines: c_float);
         /// Flush queued input into Doom’s event system. Call once per frame before dg_tick().
         pub fn dg_pump();
+        /// Last title the engine passed to DG_SetWindowTitle, or null.
+        pub fn dg_window_title() -> *const c_char;
+        pub fn dg_demo_status(out: *mut DgDemoStatus);
+        /// Start a game at once, `skill` 0-4; -1 if the map isn't there.
+        pub fn dg_new_game(skill: c_int, episode: c_int, map: c_int, seed: c_int) -> c_int;
+        /// Console player input for every tic from now on; null hands it back to the controls.
+        pub fn dg_set_ticcmd(cmd: *const DgTiccmd);
+        /// Asked for each tic's input first; returning 0 falls back to dg_set_ticcmd's.
+        pub fn dg_set_ticcmd_hook(hook: Option<unsafe extern "C" fn(tic: c_int, cmd: *mut DgTiccmd) -> c_int>);
+        /// Whether the world stands still this tic (paused, or in the menu), read live.
+        pub fn dg_world_paused() -> c_int;
+        /// Write out a recording in progress.
+        pub fn dg_demo_end();
+        /// The game as of the end of the last tic.
+        pub fn dg_game_state(out: *mut DgGameState);
+        /// Copy up to `max` of the last tic's map objects; returns how many there are.
+        pub fn dg_mobjs(out: *mut DgMobj, max: c_int) -> c_int;
+        /// Checksum of the game state as of the last tic, for desync checks.
+        pub fn dg_state_hash() -> u64;
+        /// Called for every game event inside the tic; None turns it off.
+        pub fn dg_set_event_hook(hook: Option<unsafe extern "C" fn(ev: *const DgEvent)>);
+        /// Capture end-of-level statistics from now on.
+        pub fn dg_stats_capture(on: c_int);
+        /// Take the oldest captured level; 0 if none is waiting.
+        pub fn dg_stats_take(out: *mut DgLevelStats) -> c_int;
+        /// Queue a save into `slot`; -1 if the game can't be saved right now.
+        pub fn dg_save_game(slot: c_int, description: *const c_char) -> c_int;
+        /// Queue loading `slot`; -1 if there is no save there.
+        pub fn dg_load_game(slot: c_int) -> c_int;
+        /// Serialize the running level into a malloc'd buffer; -1 outside a level.
+        pub fn dg_snapshot_save(data: *mut *mut u8, len: *mut size_t) -> c_int;
+        pub fn dg_snapshot_free(data: *mut u8);
+        /// Restore a dg_snapshot_save buffer right away. Call between ticks.
+        pub fn dg_snapshot_load(data: *const u8, len: size_t) -> c_int;
+        /// Bind and read the engine's config files in `dir` without starting the game.
+        pub fn dg_config_load(dir: *const c_char) -> c_int;
+        /// Name of the i-th engine variable, null past the end.
+        pub fn dg_config_name(i: c_int) -> *const c_char;
+        /// Value as written in the config file; -1 for an unknown variable.
+        pub fn dg_config_get(name: *const c_char, buf: *mut c_char, len: size_t) -> c_int;
+        pub fn dg_config_set(name: *const c_char, value: *const c_char) -> c_int;
+        /// Write default.cfg and extended.cfg back.
+        pub fn dg_config_save();
+        /// Set one DeHackEd table field; `table` is a `DG_DEH_*` value. 0 on success.
+        pub fn dg_deh_set(table: c_int, index: c_int, field: c_int, value: c_int) -> c_int;
+        /// A Text replacement; copies both strings. Later patches win, the
+        /// engine's own `DEH_AddStringReplacement` calls don't.
+        pub fn dg_deh_text(from_text: *const c_char, to_text: *const c_char);
     }
 }
@@ -0,0 +1,217 @@
+// tas.rs
+//
+// --tas: tool-assisted play against a timeline file (rustydoom::timeline).
+// The game starts paused on the timeline's map and each step runs one tic,
+// taking its ticcmd from the timeline; past the end, or with recording on,
+// the controls' ticcmd is written in instead and whatever followed is
+// dropped. Rewind goes back a tic by starting the level over and replaying
+// the timeline up to there, which plays out exactly as the exported demo
+// will; quicksave and quickload keep a savestate (the tic and the timeline
+// up to it) to branch from. Tics with the menu up or the game paused don't
+// count, since the world doesn't move in them.
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use anyhow::{Context, bail};
+use libc::c_int;
+use rustydoom::input;
+use rustydoom::lmp::Ticcmd;
+use rustydoom::state;
+use rustydoom::timeline::Timeline;
+
+use crate::cli::Cli;
+use crate::sys;
+use crate::playback;
+
+struct Tas {
+    path: PathBuf,
+    timeline: Timeline,
+    /// Tics run since the game started; the next one is `timeline.tics[cursor]`.
+    cursor: usize,
+    recording: bool,
+    /// Seeking: follow the timeline whatever `recording` says.
+    replaying: bool,
+    /// Unsaved changes to the timeline.
+    dirty: bool,
+    savestate: Option<(usize, Vec<Ticcmd>)>,
+}
+
+static TAS: Mutex<Option<Tas>> = Mutex::new(None);
+
+pub fn active() -> bool {
+    TAS.lock().unwrap().is_some()
+}
+
+/// Load (or create) the timeline, start its game and pause on the first tic.
+pub fn start(cli: &Cli) -> anyhow::Result<()> {
+    let Some(path) = &cli.tas else { return Ok(()) };
+    let timeline = if path.exists() {
+        Timeline::read(path)?
+    } else {
+        let skill = cli.skill.unwrap_or(3);
+        if !(1..=5).contains(&skill) {
+            bail!("--skill must be 1-5, not {}", skill);
+        }
+        let (episode, map) = match cli.warp[..] {
+            [] => (1, 1),
+            [map] => (1, map),
+            [episode, map, ..] => (episode, map),
+        };
+        Timeline::new(skill, episode, map)
+    };
+    new_game(&timeline)?;
+    println!("TAS: {} ({} tics)", path.display(), timeline.tics.len());
+    let dirty = !path.exists();
+    *TAS.lock().unwrap() = Some(Tas {
+        path: path.clone(),
+        timeline,
+        cursor: 0,
+        recording: false,
+        replaying: false,
+        dirty,
+        savestate: None,
+    });
+    input::set_source(|_, controls| next_cmd(controls));
+    playback::pause();
+    Ok(())
+}
+
+fn new_game(timeline: &Timeline) -> anyhow::Result<()> {
+    let rc = unsafe {
+        sys::raw::dg_new_game(timeline.skill as c_int - 1, timeline.episode as c_int, timeline.map as c_int, 0)
+    };
+    if rc != 0 {
+        bail!("episode {} map {} isn't in the loaded WADs", timeline.episode, timeline.map);
+    }
+    Ok(())
+}
+
+/// The ticcmd source: the timeline's tic, or the controls' written into it.
+fn next_cmd(controls: Ticcmd) -> Option<Ticcmd> {
+    // Live, not game_state(): that may be the last tic's, from before the
+    // menu opened or closed in this one.
+    if unsafe { sys::raw::dg_world_paused() } != 0 {
+        return None;
+    }
+    let mut guard = TAS.lock().unwrap();
+    let tas = guard.as_mut()?;
+    let i = tas.cursor;
+    tas.cursor += 1;
+    if i < tas.timeline.tics.len() && (tas.replaying || !tas.recording) {
+        return tas.timeline.engine_cmd(i);
+    }
+    let cmd = if tas.timeline.longtics { controls } else { controls.lowres() };
+    tas.timeline.tics.truncate(i);
+    tas.timeline.tics.push(cmd);
+    tas.dirty = true;
+    Some(cmd)
+}
+
+/// Put the game at tic `target` of the timeline: start the level over and
+/// replay the timeline up to there.
+fn seek(target: usize) -> anyhow::Result<()> {
+    if state::game_state().menu {
+        bail!("close the menu first");
+    }
+    let target = {
+        let mut guard = TAS.lock().unwrap();
+        let tas = guard.as_mut().context("not in TAS mode")?;
+        new_game(&tas.timeline)?;
+        tas.cursor = 0;
+        tas.replaying = true;
+        target.min(tas.timeline.tics.len())
+    };
+    // Tics ask next_cmd for their input, so the lock can't be held here.
+    while TAS.lock().unwrap().as_ref().is_some_and(|tas| tas.cursor < target) {
+        if !crate::tick() {
+            break;
+        }
+    }
+    if let Some(tas) = TAS.lock().unwrap().as_mut() {
+        tas.replaying = false;
+    }
+    Ok(())
+}
+
+fn cursor() -> usize {
+    TAS.lock().unwrap().as_ref().map_or(0, |tas| tas.cursor)
+}
+
+/// Go back one tic.
+pub fn step_back() {
+    playback::pause();
+    let Some(target) = cursor().checked_sub(1) else {
+        eprintln!("TAS: already at the first tic");
+        return;
+    };
+    if let Err(e) = seek(target) {
+        eprintln!("TAS: {:#}", e);
+    }
+}
+
+pub fn save_state() {
+    let mut guard = TAS.lock().unwrap();
+    let Some(tas) = guard.as_mut() else { return };
+    tas.savestate = Some((tas.cursor, tas.timeline.tics[..tas.cursor].to_vec()));
+    println!("TAS: savestate at tic {}", tas.cursor);
+}
+
+/// Back to the savestate, with the timeline as it was up to there; what
+/// was recorded after it stays, to replay or overwrite.
+pub fn load_state() {
+    playback::pause();
+    let target = {
+        let mut guard = TAS.lock().unwrap();
+        let Some(tas) = guard.as_mut() else { return };
+        let Some((at, tics)) = tas.savestate.clone() else {
+            eprintln!("TAS: no savestate");
+            return;
+        };
+        let same = tas.timeline.tics.iter().zip(&tics).take_while(|(a, b)| a == b).count();
+        if same < tics.len() {
+            let rest = tas.timeline.tics.split_off(tics.len().min(tas.timeline.tics.len()));
+            tas.timeline.tics = tics;
+            tas.timeline.tics.extend(rest);
+            tas.dirty = true;
+        }
+        at
+    };
+    if let Err(e) = seek(target) {
+        eprintln!("TAS: {:#}", e);
+    }
+}
+
+pub fn toggle_record() {
+    if let Some(tas) = TAS.lock().unwrap().as_mut() {
+        tas.recording = !tas.recording;
+    }
+}
+
+/// Write the timeline back to its file.
+pub fn save() {
+    let mut guard = TAS.lock().unwrap();
+    let Some(tas) = guard.as_mut() else { return };
+    match tas.timeline.write(&tas.path) {
+        Ok(()) => {
+            tas.dirty = false;
+            println!("TAS: {} tics written to {}", tas.timeline.tics.len(), tas.path.display());
+        }
+        Err(e) => eprintln!("TAS: {:#}", e),
+    }
+}
+
+/// "TAS 120/450 REC" for the window title.
+pub fn label() -> Option<String> {
+    let guard = TAS.lock().unwrap();
+    let tas = guard.as_ref()?;
+    let rec = if tas.recording { " REC" } else { "" };
+    Some(format!("TAS {}/{}{}", tas.cursor, tas.timeline.tics.len(), rec))
+}
+
+/// On the way out: keep unsaved changes.
+pub fn finish() {
+    input::clear_source();
+    if TAS.lock().unwrap().as_ref().is_some_and(|tas| tas.dirty) {
+        save();
+    }
+}
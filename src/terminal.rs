@@ -0,0 +1,244 @@
+// terminal.rs
+//
+// Drawing frames as text and reading keys from a terminal in raw mode, for
+// `--frontend terminal`. Each character cell is an upper half block (▀)
+// whose foreground is one pixel and background the one below it, so a
+// terminal of C x R cells shows C x 2R pixels. Only cells that changed since
+// the last frame are sent, which is what makes it usable over SSH.
+use std::fmt::Write as _;
+
+use crate::blit::blit_nn;
+use crate::config::Scaler;
+
+/// What the terminal can show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    /// 24-bit `38;2;r;g;b` colours.
+    TrueColor,
+    /// The xterm 256-colour palette.
+    Ansi256,
+}
+
+impl ColorMode {
+    /// From the COLORTERM environment variable, which terminals with 24-bit
+    /// colour set to "truecolor" or "24bit".
+    pub fn detect(colorterm: Option<&str>) -> Self {
+        match colorterm {
+            Some(v) if v.eq_ignore_ascii_case("truecolor") || v.eq_ignore_ascii_case("24bit") => ColorMode::TrueColor,
+            _ => ColorMode::Ansi256,
+        }
+    }
+
+    /// How the colour is sent: the pixel itself, or its palette index.
+    fn quantize(self, rgb: u32) -> u32 {
+        match self {
+            ColorMode::TrueColor => rgb & 0x00ff_ffff,
+            ColorMode::Ansi256 => ansi256(rgb) as u32,
+        }
+    }
+
+    fn sgr(self, out: &mut String, layer: u8, color: u32) {
+        let _ = match self {
+            ColorMode::TrueColor => {
+                write!(out, "\x1b[{}8;2;{};{};{}m", layer, color >> 16, (color >> 8) & 0xff, color & 0xff)
+            }
+            ColorMode::Ansi256 => write!(out, "\x1b[{}8;5;{}m", layer, color),
+        };
+    }
+}
+
+const CUBE: [u32; 6] = [0, 95, 135, 175, 215, 255];
+
+/// The nearest xterm 256-colour index to a 0x00RRGGBB colour, from the
+/// 6x6x6 cube (16-231) or the grey ramp (232-255).
+pub fn ansi256(rgb: u32) -> u8 {
+    let [r, g, b] = [(rgb >> 16) & 0xff, (rgb >> 8) & 0xff, rgb & 0xff];
+    let level = |c: u32| (0..6).min_by_key(|&i| CUBE[i].abs_diff(c)).unwrap();
+    let (lr, lg, lb) = (level(r), level(g), level(b));
+    let dist = |x: u32, y: u32, z: u32| {
+        let d = |a: u32, b: u32| a.abs_diff(b).pow(2);
+        d(x, r) + d(y, g) + d(z, b)
+    };
+    let cube = dist(CUBE[lr], CUBE[lg], CUBE[lb]);
+    let grey_step = ((((r + g + b) / 3).saturating_sub(8) + 5) / 10).min(23);
+    let grey = 8 + 10 * grey_step;
+    if dist(grey, grey, grey) < cube {
+        232 + grey_step as u8
+    } else {
+        16 + (36 * lr + 6 * lg + lb) as u8
+    }
+}
+
+/// A terminal screen's worth of half-block cells, remembering what was
+/// last sent so the next frame only updates what changed.
+#[derive(Debug, Clone)]
+pub struct Canvas {
+    mode: ColorMode,
+    scaler: Scaler,
+    cols: usize,
+    rows: usize,
+    pixels: Vec<u32>,
+    /// (top, bottom) colours as sent; empty after a resize, forcing a full redraw.
+    shown: Vec<(u32, u32)>,
+}
+
+impl Canvas {
+    pub fn new(mode: ColorMode, scaler: Scaler) -> Self {
+        Canvas { mode, scaler, cols: 0, rows: 0, pixels: Vec::new(), shown: Vec::new() }
+    }
+
+    /// The frame now goes in `cols` x `rows` cells at the top left; the
+    /// next one is drawn in full.
+    pub fn resize(&mut self, cols: usize, rows: usize) {
+        (self.cols, self.rows) = (cols, rows);
+        self.shown.clear();
+    }
+
+    /// The escape sequences that bring the screen up to date with `frame`.
+    pub fn draw(&mut self, frame: &[u32], width: usize, height: usize) -> String {
+        let (cols, rows) = (self.cols, self.rows);
+        let mut out = String::new();
+        if cols == 0 || rows == 0 {
+            return out;
+        }
+        self.pixels.resize(cols * rows * 2, 0);
+        blit_nn(&mut self.pixels, cols, rows * 2, frame, width, height, self.scaler);
+        let full = self.shown.is_empty();
+        if full {
+            out.push_str("\x1b[0m\x1b[2J");
+            self.shown = vec![(u32::MAX, u32::MAX); cols * rows];
+        }
+
+        // Where the cursor is, and the colours in effect.
+        let mut cursor = None;
+        let (mut fg, mut bg) = (None, None);
+        for row in 0..rows {
+            for col in 0..cols {
+                let top = self.mode.quantize(self.pixels[row * 2 * cols + col]);
+                let bottom = self.mode.quantize(self.pixels[(row * 2 + 1) * cols + col]);
+                let cell = &mut self.shown[row * cols + col];
+                if *cell == (top, bottom) {
+                    continue;
+                }
+                *cell = (top, bottom);
+                if cursor != Some((row, col)) {
+                    let _ = write!(out, "\x1b[{};{}H", row + 1, col + 1);
+                }
+                if bg != Some(bottom) {
+                    self.mode.sgr(&mut out, 4, bottom);
+                    bg = Some(bottom);
+                }
+                // A cell of one colour is a space; the foreground can stay.
+                if top == bottom {
+                    out.push(' ');
+                } else {
+                    if fg != Some(top) {
+                        self.mode.sgr(&mut out, 3, top);
+                        fg = Some(top);
+                    }
+                    out.push('▀');
+                }
+                cursor = Some((row, col + 1));
+            }
+        }
+        if !out.is_empty() {
+            out.push_str("\x1b[0m");
+        }
+        out
+    }
+}
+
+/// A key as a terminal reports it. Terminals send presses (and repeats)
+/// only, never releases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TermKey {
+    /// A printable ASCII character.
+    Char(u8),
+    Enter,
+    Escape,
+    Backspace,
+    Tab,
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    Insert,
+    Delete,
+    PageUp,
+    PageDown,
+    /// F1-F12.
+    F(u8),
+    /// Ctrl-C, which raw mode delivers as a byte instead of a signal.
+    Interrupt,
+    /// Ctrl-L.
+    Redraw,
+}
+
+/// Decode what a raw-mode read returned. An escape byte that doesn't start
+/// a known sequence is the Escape key; anything unrecognized is dropped.
+pub fn parse_keys(bytes: &[u8]) -> Vec<TermKey> {
+    let mut keys = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        i += 1;
+        let key = match b {
+            0x1b => match bytes.get(i) {
+                Some(b'[') | Some(b'O') => {
+                    let (key, len) = escape_sequence(&bytes[i..]);
+                    i += len;
+                    key
+                }
+                _ => Some(TermKey::Escape),
+            },
+            b'\r' | b'\n' => Some(TermKey::Enter),
+            b'\t' => Some(TermKey::Tab),
+            0x7f | 0x08 => Some(TermKey::Backspace),
+            0x03 => Some(TermKey::Interrupt),
+            0x0c => Some(TermKey::Redraw),
+            0x20..=0x7e => Some(TermKey::Char(b)),
+            _ => None,
+        };
+        keys.extend(key);
+    }
+    keys
+}
+
+/// `seq` starts at the byte after ESC ('[' or 'O'); returns the key and
+/// how many bytes it took.
+fn escape_sequence(seq: &[u8]) -> (Option<TermKey>, usize) {
+    // Parameters, then the final byte (0x40-0x7e).
+    let Some(end) = seq.iter().skip(1).position(|b| (0x40..=0x7e).contains(b)).map(|p| p + 1) else {
+        return (Some(TermKey::Escape), 0);
+    };
+    let params = std::str::from_utf8(&seq[1..end]).unwrap_or("");
+    let first: Option<u8> = params.split(';').next().and_then(|p| p.parse().ok());
+    let key = match (seq[end], first) {
+        (b'A', _) => Some(TermKey::Up),
+        (b'B', _) => Some(TermKey::Down),
+        (b'C', _) => Some(TermKey::Right),
+        (b'D', _) => Some(TermKey::Left),
+        (b'H', _) => Some(TermKey::Home),
+        (b'F', _) => Some(TermKey::End),
+        (b'P', _) => Some(TermKey::F(1)),
+        (b'Q', _) => Some(TermKey::F(2)),
+        (b'R', _) => Some(TermKey::F(3)),
+        (b'S', _) => Some(TermKey::F(4)),
+        (b'~', Some(n)) => match n {
+            1 | 7 => Some(TermKey::Home),
+            2 => Some(TermKey::Insert),
+            3 => Some(TermKey::Delete),
+            4 | 8 => Some(TermKey::End),
+            5 => Some(TermKey::PageUp),
+            6 => Some(TermKey::PageDown),
+            11..=15 => Some(TermKey::F(n - 10)),
+            17..=21 => Some(TermKey::F(n - 11)),
+            23 | 24 => Some(TermKey::F(n - 12)),
+            _ => None,
+        },
+        _ => None,
+    };
+    (key, end + 1)
+}
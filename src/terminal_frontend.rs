@@ -0,0 +1,280 @@
+// terminal_frontend.rs
+//
+// `--frontend terminal`: play in the terminal rustydoom was started from,
+// no display server needed, so over SSH too. Frames are drawn with
+// rustydoom::terminal's half blocks and keys read in raw mode. While it
+// runs, the engine's own output goes to a log instead of over the picture,
+// and is printed once the terminal is back to normal.
+use std::fs::File;
+use std::io::Write;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, bail};
+use rustydoom::config::{HostConfig, Scaler};
+use rustydoom::present::{FileSink, Presenter};
+use rustydoom::terminal::{Canvas, ColorMode, TermKey, parse_keys};
+use winit::keyboard::KeyCode;
+
+use crate::dg_io::{DGK_DOWN, DGK_ENTER, DGK_ESCAPE, DGK_FIRE, DGK_LEFT, DGK_RIGHT, DGK_UP, DGK_USE, DoomHost, Hotkeys};
+use crate::{DgHost, demos, playback, sys, title};
+
+/// Terminals only send presses, repeated while a key is held, so a key
+/// counts as held this long after its press (past the usual 500-660ms
+/// autorepeat delay, so a held key doesn't drop before its first repeat)...
+const FIRST_HOLD: Duration = Duration::from_millis(700);
+/// ...and this long after each repeat.
+const REPEAT_HOLD: Duration = Duration::from_millis(100);
+
+pub fn run(settings: &HostConfig, hotkeys: Hotkeys, mut dump_frames: Option<FileSink>) -> anyhow::Result<()> {
+    let mode = ColorMode::detect(std::env::var("COLORTERM").ok().as_deref());
+    let session = Session::enter()?;
+    let mut presenter = TerminalPresenter::new(session.output()?, mode, settings.video.scaler);
+
+    let host = DgHost;
+    let mut held = Held::default();
+    let mut size = (0, 0);
+    let mut input = [0u8; 256];
+    loop {
+        // The size is cheap to ask for; no SIGWINCH handler needed.
+        if session.size() != size {
+            size = session.size();
+            presenter.resize(size.0, size.1)?;
+        }
+        for key in parse_keys(session.read(&mut input)) {
+            match key {
+                TermKey::Interrupt => return Ok(()),
+                TermKey::Redraw => presenter.resize(size.0, size.1)?,
+                _ if hotkey(key).is_some_and(|c| hotkeys.press(&host, c)) => {}
+                _ => held.press(&host, key),
+            }
+        }
+        held.release_due(&host);
+
+        unsafe { sys::raw::dg_pump() };
+        let tics = playback::run_frame(crate::tick);
+        if demos::finished() {
+            return Ok(());
+        }
+        let Some((fb, fb_w, fb_h)) = crate::fetch_doom_fb() else { continue };
+        presenter.present(fb, fb_w, fb_h)?;
+        // Only frames that ran a tic: paused, the same one would repeat.
+        if let Some(sink) = dump_frames.as_mut().filter(|_| tics > 0) {
+            sink.present(fb, fb_w, fb_h)?;
+        }
+    }
+}
+
+/// The key a hotkey could be bound to, by winit's name for it.
+fn hotkey(key: TermKey) -> Option<KeyCode> {
+    use KeyCode::*;
+    const F: [KeyCode; 12] = [F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12];
+    Some(match key {
+        TermKey::F(n) => *F.get(n as usize - 1)?,
+        TermKey::Backspace => Backspace,
+        TermKey::Tab => Tab,
+        TermKey::Insert => Insert,
+        TermKey::Delete => Delete,
+        TermKey::Home => Home,
+        TermKey::End => End,
+        TermKey::PageUp => PageUp,
+        TermKey::PageDown => PageDown,
+        TermKey::Char(b'`') => Backquote,
+        TermKey::Char(b'-') => Minus,
+        TermKey::Char(b'=') => Equal,
+        _ => return None,
+    })
+}
+
+/// What the engine gets for a key, as for the window: ASCII, or one of the
+/// bridge's sentinels. Ctrl alone never reaches a terminal, so f fires.
+fn doom_key(key: TermKey) -> Option<i32> {
+    Some(match key {
+        TermKey::Char(b' ') => DGK_USE,
+        TermKey::Char(b'f') | TermKey::Char(b'F') => DGK_FIRE,
+        TermKey::Char(c) => c.to_ascii_lowercase() as i32,
+        TermKey::Enter => DGK_ENTER,
+        TermKey::Escape => DGK_ESCAPE,
+        TermKey::Up => DGK_UP,
+        TermKey::Down => DGK_DOWN,
+        TermKey::Left => DGK_LEFT,
+        TermKey::Right => DGK_RIGHT,
+        _ => return None,
+    })
+}
+
+/// Keys down as far as the engine knows, and when to let them go.
+#[derive(Default)]
+struct Held(Vec<(i32, Instant)>);
+
+impl Held {
+    fn press(&mut self, host: &impl DoomHost, key: TermKey) {
+        let Some(code) = doom_key(key) else { return };
+        let now = Instant::now();
+        match self.0.iter_mut().find(|(c, _)| *c == code) {
+            Some((_, until)) => *until = (*until).max(now + REPEAT_HOLD),
+            None => {
+                host.key_down(code);
+                self.0.push((code, now + FIRST_HOLD));
+            }
+        }
+    }
+
+    fn release_due(&mut self, host: &impl DoomHost) {
+        let now = Instant::now();
+        self.0.retain(|&(code, until)| {
+            let due = until <= now;
+            if due {
+                host.key_up(code);
+            }
+            !due
+        });
+    }
+}
+
+/// Draws into the terminal below a one-line status bar's worth of cells;
+/// sizes are in character cells, not pixels.
+struct TerminalPresenter {
+    out: File,
+    canvas: Canvas,
+    cols: usize,
+    rows: usize,
+    status: String,
+}
+
+impl TerminalPresenter {
+    fn new(out: File, mode: ColorMode, scaler: Scaler) -> Self {
+        TerminalPresenter { out, canvas: Canvas::new(mode, scaler), cols: 0, rows: 0, status: String::new() }
+    }
+}
+
+impl Presenter for TerminalPresenter {
+    fn resize(&mut self, width: u32, height: u32) -> anyhow::Result<()> {
+        (self.cols, self.rows) = (width as usize, height as usize);
+        self.canvas.resize(self.cols, self.rows.saturating_sub(1));
+        self.status.clear();
+        Ok(())
+    }
+
+    fn present(&mut self, frame: &[u32], width: usize, height: usize) -> anyhow::Result<()> {
+        let mut text = self.canvas.draw(frame, width, height);
+        let status: String = format!("{}  (Ctrl-C quits)", title::window_title(true)).chars().take(self.cols).collect();
+        if self.rows > 0 && (status != self.status || text.starts_with("\x1b[0m\x1b[2J")) {
+            text.push_str(&format!("\x1b[{};1H\x1b[0m\x1b[2K{}", self.rows, status));
+            self.status = status;
+        }
+        if !text.is_empty() {
+            self.out.write_all(text.as_bytes()).context("writing to the terminal")?;
+            self.out.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// How to put the terminal back: its settings, where stdout and stderr
+/// went, and the log they go to meanwhile.
+struct Saved {
+    termios: libc::termios,
+    stdout: OwnedFd,
+    stderr: OwnedFd,
+    log: PathBuf,
+}
+
+static SAVED: Mutex<Option<Saved>> = Mutex::new(None);
+
+/// The terminal in raw mode on the alternate screen, until dropped. The
+/// engine can also quit with exit() from under us, hence the atexit hook.
+struct Session {
+    tty: OwnedFd,
+}
+
+impl Session {
+    fn enter() -> anyhow::Result<Self> {
+        if unsafe { libc::isatty(0) == 0 || libc::isatty(1) == 0 } {
+            bail!("--frontend terminal needs a terminal on stdin and stdout");
+        }
+        let mut termios: libc::termios = unsafe { std::mem::zeroed() };
+        if unsafe { libc::tcgetattr(0, &mut termios) } != 0 {
+            return Err(std::io::Error::last_os_error()).context("reading terminal settings");
+        }
+        let dup = |fd| -> anyhow::Result<OwnedFd> {
+            let new = unsafe { libc::dup(fd) };
+            if new < 0 {
+                return Err(std::io::Error::last_os_error()).context("duplicating stdout");
+            }
+            Ok(unsafe { OwnedFd::from_raw_fd(new) })
+        };
+        let (tty, stdout, stderr) = (dup(1)?, dup(1)?, dup(2)?);
+        let log = std::env::temp_dir().join(format!("rustydoom-terminal-{}.log", std::process::id()));
+        let log_file = File::create(&log).with_context(|| format!("creating {}", log.display()))?;
+
+        let mut raw = termios;
+        unsafe {
+            libc::cfmakeraw(&mut raw);
+            // Reads return at once, with whatever has been typed.
+            raw.c_cc[libc::VMIN] = 0;
+            raw.c_cc[libc::VTIME] = 0;
+            libc::tcsetattr(0, libc::TCSANOW, &raw);
+            std::io::stdout().flush()?;
+            libc::dup2(log_file.as_raw_fd(), 1);
+            libc::dup2(log_file.as_raw_fd(), 2);
+            libc::atexit(restore_at_exit);
+        }
+        *SAVED.lock().unwrap() = Some(Saved { termios, stdout, stderr, log });
+        let session = Session { tty };
+        // Alternate screen, cursor hidden.
+        session.output()?.write_all(b"\x1b[?1049h\x1b[?25l")?;
+        Ok(session)
+    }
+
+    /// Somewhere to draw: the terminal stdout was.
+    fn output(&self) -> anyhow::Result<File> {
+        Ok(File::from(self.tty.try_clone()?))
+    }
+
+    /// Columns and rows.
+    fn size(&self) -> (u32, u32) {
+        let mut ws: libc::winsize = unsafe { std::mem::zeroed() };
+        if unsafe { libc::ioctl(self.tty.as_raw_fd(), libc::TIOCGWINSZ, &mut ws) } != 0 {
+            return (80, 24);
+        }
+        (ws.ws_col as u32, ws.ws_row as u32)
+    }
+
+    /// What has been typed since last time, without waiting.
+    fn read<'a>(&self, buf: &'a mut [u8]) -> &'a [u8] {
+        let n = unsafe { libc::read(0, buf.as_mut_ptr().cast(), buf.len()) };
+        &buf[..n.max(0) as usize]
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+extern "C" fn restore_at_exit() {
+    restore();
+}
+
+fn restore() {
+    let Some(saved) = SAVED.lock().unwrap_or_else(|e| e.into_inner()).take() else { return };
+    let _ = std::io::stdout().flush();
+    unsafe {
+        libc::fflush(std::ptr::null_mut());
+        // Straight to the fd: this runs from atexit, where nothing may panic.
+        let reset = b"\x1b[0m\x1b[?25h\x1b[?1049l";
+        libc::write(saved.stdout.as_raw_fd(), reset.as_ptr().cast(), reset.len());
+        libc::tcsetattr(0, libc::TCSANOW, &saved.termios);
+        libc::dup2(saved.stdout.as_raw_fd(), 1);
+        libc::dup2(saved.stderr.as_raw_fd(), 2);
+    }
+    // What was said meanwhile, now that it can be read.
+    if let Ok(log) = std::fs::read(&saved.log) {
+        let _ = std::io::stderr().write_all(&log);
+    }
+    let _ = std::fs::remove_file(&saved.log);
+}
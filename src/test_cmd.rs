@@ -0,0 +1,308 @@
+// test_cmd.rs
+//
+// `rustydoom test MANIFEST`: run a manifest's map regression cases
+// (rustydoom::regression), each in a process of its own since the engine is
+// one per process, several at once. Every worker is this binary again with
+// `--case NAME`: it boots the engine headless, plays the case, watches the
+// tics go by for the checks, and prints its result as one JSON line for the
+// runner to collect into the summary and the JUnit report. A worker still
+// going at the case's timeout is killed, and the case fails.
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, anyhow, bail};
+use rustydoom::events::{self, EventKind};
+use rustydoom::regression::{self, Case, CaseResult, FrameCheck, Input, Manifest};
+use rustydoom::wad::graphics::{self, Image};
+
+use crate::{demos, script};
+
+/// Marks the worker's result among the engine's own output.
+const RESULT: &str = "rustydoom-test-result: ";
+
+pub fn run(
+    manifest_path: &Path,
+    jobs: Option<usize>,
+    junit: Option<&Path>,
+    update_golden: bool,
+    case: Option<&str>,
+) -> anyhow::Result<()> {
+    let manifest = Manifest::read(manifest_path)?;
+    if let Some(name) = case {
+        let case = manifest.cases.iter().find(|c| c.name == name).with_context(|| format!("no case {:?}", name))?;
+        return run_case(case, update_golden);
+    }
+
+    let cases = &manifest.cases;
+    let jobs = jobs
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()))
+        .clamp(1, cases.len());
+    let exe = std::env::current_exe().context("finding the rustydoom binary")?;
+    let started = Instant::now();
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(vec![None; cases.len()]);
+    std::thread::scope(|s| {
+        for _ in 0..jobs {
+            s.spawn(|| {
+                loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(case) = cases.get(i) else { break };
+                    let result = spawn_case(&exe, manifest_path, case, update_golden);
+                    report(&result);
+                    results.lock().unwrap()[i] = Some(result);
+                }
+            });
+        }
+    });
+    let results: Vec<CaseResult> = results.into_inner().unwrap().into_iter().flatten().collect();
+
+    let failed = results.iter().filter(|r| !r.passed()).count();
+    println!(
+        "\n{} passed, {} failed ({:.1}s, {} at a time)",
+        results.len() - failed,
+        failed,
+        started.elapsed().as_secs_f64(),
+        jobs
+    );
+    if let Some(path) = junit {
+        let suite = manifest_path.file_stem().map_or("rustydoom".into(), |s| s.to_string_lossy());
+        std::fs::write(path, regression::junit_xml(&suite, &results))
+            .with_context(|| format!("writing {}", path.display()))?;
+    }
+    if failed > 0 {
+        bail!("{} of {} cases failed", failed, results.len());
+    }
+    Ok(())
+}
+
+fn report(r: &CaseResult) {
+    match r.failures.split_first() {
+        None => println!("ok   {} ({} tics, {:.1}s)", r.name, r.tics, r.seconds),
+        Some((first, rest)) => {
+            println!("FAIL {}: {}", r.name, first);
+            rest.iter().for_each(|f| println!("       {}", f));
+        }
+    }
+}
+
+/// Run one case in a worker process and read back its result.
+fn spawn_case(exe: &Path, manifest: &Path, case: &Case, update_golden: bool) -> CaseResult {
+    let started = Instant::now();
+    let mut cmd = Command::new(exe);
+    cmd.arg("test").arg(manifest).arg("--case").arg(&case.name);
+    if update_golden {
+        cmd.arg("--update-golden");
+    }
+    cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+    let failed = |why: String| CaseResult {
+        name: case.name.clone(),
+        failures: vec![why],
+        tics: 0,
+        seconds: started.elapsed().as_secs_f64(),
+    };
+    let mut child = match cmd.spawn() {
+        Ok(child) => child,
+        Err(e) => return failed(format!("starting worker: {}", e)),
+    };
+    // Drained as it comes, so a chatty worker never blocks on a full pipe.
+    let drain = |pipe: Option<Box<dyn Read + Send>>| {
+        std::thread::spawn(move || {
+            let mut out = Vec::new();
+            if let Some(mut pipe) = pipe {
+                let _ = pipe.read_to_end(&mut out);
+            }
+            out
+        })
+    };
+    let stdout = drain(child.stdout.take().map(|p| Box::new(p) as _));
+    let stderr = drain(child.stderr.take().map(|p| Box::new(p) as _));
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if started.elapsed() < case.timeout => std::thread::sleep(Duration::from_millis(20)),
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return failed(format!("timed out after {:.1}s", case.timeout.as_secs_f64()));
+            }
+            Err(e) => return failed(format!("waiting for worker: {}", e)),
+        }
+    };
+    let out = std::process::Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    };
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let line = stdout.lines().find_map(|l| l.find(RESULT).map(|at| &l[at + RESULT.len()..]));
+    match line.map(serde_json::from_str::<CaseResult>) {
+        Some(Ok(result)) => result,
+        Some(Err(e)) => failed(format!("unreadable result from worker: {}", e)),
+        // Crashed, or the engine quit with I_Error; its last words say why.
+        None => {
+            let stderr = String::from_utf8_lossy(&out.stderr);
+            let said = |text: &str| text.lines().rfind(|l| !l.trim().is_empty()).map(str::to_string);
+            let last = said(&stderr).or_else(|| said(&stdout));
+            failed(format!("worker exited with {} ({})", out.status, last.as_deref().unwrap_or("no output").trim()))
+        }
+    }
+}
+
+/// The running case's view of the game, updated after every tic.
+struct Watch {
+    /// Tics into the case: since the warp, or into the demo.
+    tic: u32,
+    demo: bool,
+    exit_by: Option<u32>,
+    /// The tic the level was finished in.
+    exited: Option<u32>,
+    exit_seen: bool,
+    pending: Vec<FrameCheck>,
+    update_golden: bool,
+    failures: Vec<String>,
+}
+
+static WATCH: Mutex<Option<Watch>> = Mutex::new(None);
+
+/// Call after each engine tick (after events are dispatched).
+pub fn after_tick() {
+    let mut guard = WATCH.lock().unwrap();
+    let Some(w) = guard.as_mut() else { return };
+    w.tic = if w.demo { demos::status().tics.max(0) as u32 } else { w.tic + 1 };
+    observe(w);
+}
+
+fn observe(w: &mut Watch) {
+    if w.exit_seen && w.exited.is_none() {
+        w.exited = Some(w.tic);
+    }
+    let (due, later): (Vec<_>, Vec<_>) = std::mem::take(&mut w.pending).into_iter().partition(|f| f.tic <= w.tic);
+    w.pending = later;
+    for check in due {
+        if let Err(e) = check_frame(&check, w.update_golden) {
+            w.failures.push(format!("frame at tic {}: {:#}", check.tic, e));
+        }
+    }
+}
+
+fn check_frame(check: &FrameCheck, update: bool) -> anyhow::Result<()> {
+    let (fb, width, height) = crate::fetch_doom_fb().ok_or_else(|| anyhow!("no frame"))?;
+    let frame = Image::from_xrgb(fb, width, height);
+    let write = |path: &Path| -> anyhow::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let file = std::fs::File::create(path).with_context(|| format!("writing {}", path.display()))?;
+        graphics::write_png(&frame, std::io::BufWriter::new(file))
+    };
+    if update {
+        return write(&check.golden);
+    }
+    let golden = std::fs::File::open(&check.golden)
+        .map_err(anyhow::Error::from)
+        .and_then(|f| graphics::read_png(std::io::BufReader::new(f)))
+        .with_context(|| format!("reading {}", check.golden.display()))?;
+    let diff = regression::frame_difference(&golden, &frame);
+    if diff.is_some_and(|d| d <= check.tolerance) {
+        return Ok(());
+    }
+    let actual = check.golden.with_extension("actual.png");
+    write(&actual)?;
+    match diff {
+        Some(d) => bail!(
+            "differs from {} by {:.2} (tolerance {}); see {}",
+            check.golden.display(),
+            d,
+            check.tolerance,
+            actual.display()
+        ),
+        None => bail!(
+            "is {}x{}, {} isn't; see {}",
+            frame.width,
+            frame.height,
+            check.golden.display(),
+            actual.display()
+        ),
+    }
+}
+
+/// Whether a demo or idle case has seen all it needs to.
+fn finished(case: &Case) -> bool {
+    let guard = WATCH.lock().unwrap();
+    let Some(w) = guard.as_ref() else { return true };
+    if let Some(last) = case.last_tic() {
+        return w.tic >= last;
+    }
+    let exit_decided = w.exited.is_some() || w.exit_by.is_none_or(|n| w.tic > n);
+    let checks = case.exit_by.is_some() || !case.frames.is_empty();
+    checks && exit_decided && w.pending.is_empty()
+}
+
+/// Play `case` here and print its result.
+fn run_case(case: &Case, update_golden: bool) -> anyhow::Result<()> {
+    let started = Instant::now();
+    let demo = matches!(case.input, Input::Demo(_));
+    *WATCH.lock().unwrap() = Some(Watch {
+        tic: 0,
+        demo,
+        exit_by: case.exit_by,
+        exited: None,
+        exit_seen: false,
+        pending: case.frames.clone(),
+        update_golden,
+        failures: Vec::new(),
+    });
+    events::set_handler(|ev| {
+        if matches!(ev.kind, EventKind::LevelFinish { .. })
+            && let Some(w) = WATCH.lock().unwrap().as_mut()
+        {
+            w.exit_seen = true;
+        }
+    });
+
+    if let Err(e) = play(case)
+        && let Some(w) = WATCH.lock().unwrap().as_mut()
+    {
+        w.failures.push(format!("{:#}", e));
+    }
+
+    let w = WATCH.lock().unwrap().take().expect("watch in place");
+    let mut failures = w.failures;
+    match (case.exit_by, w.exited) {
+        (Some(n), Some(at)) if at > n => failures.push(format!("level finished at tic {}, after {}", at, n)),
+        (Some(n), None) => failures.push(format!("level not finished by tic {} (ran {})", n, w.tic)),
+        _ => {}
+    }
+    for check in &w.pending {
+        failures.push(format!("frame at tic {}: never got there (ran {})", check.tic, w.tic));
+    }
+    let result = CaseResult { name: case.name.clone(), failures, tics: w.tic, seconds: started.elapsed().as_secs_f64() };
+    println!("{}{}", RESULT, serde_json::to_string(&result)?);
+    Ok(())
+}
+
+fn play(case: &Case) -> anyhow::Result<()> {
+    let extra = match &case.input {
+        Input::Demo(demo) => vec!["-playdemo".to_string(), demo.to_string_lossy().into_owned()],
+        _ => Vec::new(),
+    };
+    let _engine = script::boot_headless(case.iwad.as_deref(), &case.files, &case.deh, &extra)?;
+    if let Some(map) = &case.map {
+        script::new_game(map, case.skill as i64, 0)?;
+        if let Some(w) = WATCH.lock().unwrap().as_mut() {
+            w.tic = 0;
+            observe(w);
+        }
+    }
+    match &case.input {
+        Input::Script(path) => script::run_file(path),
+        Input::Demo(_) | Input::Idle => {
+            while !finished(case) && crate::tick() {}
+            Ok(())
+        }
+    }
+}
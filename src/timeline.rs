@@ -0,0 +1,240 @@
+// timeline.rs
+//
+// TAS input timelines: the game settings a run starts from and the console
+// player's ticcmd for every tic after, as text meant to be edited and
+// diffed. Converts to and from single-player vanilla demos.
+//
+//     # rustydoom TAS timeline
+//     skill 4
+//     episode 1
+//     map 1
+//     # forward side turn buttons [xN]
+//     50 0 0 - x120
+//     50 -24 512 A
+//     0 0 0 U3
+//
+// One line per tic, or per run of identical tics with `xN`. Buttons are `-`
+// for none, else `A` (attack), `U` (use) and a weapon key 1-8 to switch, or
+// `$hh` for a raw byte those don't cover. `longtics` on a line of its own
+// keeps full turn precision; without it turns round to a byte, as in a
+// vanilla demo.
+use std::fmt::Write as _;
+use std::path::Path;
+
+use anyhow::{Context, bail};
+
+use crate::lmp::{DOOM_VERSION, Demo, DemoHeader, End, LONGTICS_VERSION, Ticcmd};
+
+const HEADER: &str = "# rustydoom TAS timeline";
+/// The longest a timeline may get, a day of tics, so a typo'd `xN` is an
+/// error rather than all the memory there is.
+pub const MAX_TICS: usize = 35 * 60 * 60 * 24;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Timeline {
+    /// 1-5.
+    pub skill: u8,
+    pub episode: u8,
+    pub map: u8,
+    pub longtics: bool,
+    pub tics: Vec<Ticcmd>,
+}
+
+impl Timeline {
+    /// An empty timeline starting on `episode`/`map`.
+    pub fn new(skill: u8, episode: u8, map: u8) -> Self {
+        Timeline { skill, episode, map, longtics: false, tics: Vec::new() }
+    }
+
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let (mut skill, mut episode, mut map, mut longtics) = (None, None, None, false);
+        let mut tics = Vec::new();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let number = |what: &str| -> anyhow::Result<u8> {
+                match words.get(1).map(|w| w.parse::<u8>()) {
+                    Some(Ok(v)) if words.len() == 2 => Ok(v),
+                    _ => bail!("line {}: expected `{} <number>`", n + 1, what),
+                }
+            };
+            match words.first() {
+                None => continue,
+                Some(&"skill") => skill = Some(number("skill")?),
+                Some(&"episode") => episode = Some(number("episode")?),
+                Some(&"map") => map = Some(number("map")?),
+                Some(&"longtics") if words.len() == 1 => longtics = true,
+                Some(_) => {
+                    let (cmd, count) = parse_tic(&words).with_context(|| format!("line {}", n + 1))?;
+                    if tics.len() + count > MAX_TICS {
+                        bail!("line {}: more than {} tics", n + 1, MAX_TICS);
+                    }
+                    tics.extend(std::iter::repeat_n(cmd, count));
+                }
+            }
+        }
+        let (Some(skill), Some(episode), Some(map)) = (skill, episode, map) else {
+            bail!("timeline needs skill, episode and map lines");
+        };
+        if !(1..=5).contains(&skill) {
+            bail!("skill must be 1-5, not {}", skill);
+        }
+        Ok(Timeline { skill, episode, map, longtics, tics })
+    }
+
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        let text = std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("parsing {}", path.display()))
+    }
+
+    pub fn write(&self, path: &Path) -> anyhow::Result<()> {
+        std::fs::write(path, self.to_string()).with_context(|| format!("writing {}", path.display()))
+    }
+
+    /// Rewrite tics from `i` on with `line`, `forward side turn buttons [xN]`
+    /// as in the file, keeping the ones after; a timeline too short for them
+    /// is padded with tics of no input. Returns how many tics were written.
+    pub fn set(&mut self, i: usize, line: &str) -> anyhow::Result<usize> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let (cmd, count) = parse_tic(&words)?;
+        let end = i.checked_add(count).filter(|&end| end <= MAX_TICS).with_context(|| format!("more than {} tics", MAX_TICS))?;
+        if self.tics.len() < end {
+            self.tics.resize(end, Ticcmd::default());
+        }
+        self.tics[i..end].fill(cmd);
+        Ok(count)
+    }
+
+    /// What the engine should be given for tic `i`: turns rounded as a
+    /// vanilla demo would store them, so the run plays back the same from
+    /// the exported .lmp.
+    pub fn engine_cmd(&self, i: usize) -> Option<Ticcmd> {
+        let cmd = *self.tics.get(i)?;
+        Some(if self.longtics { cmd } else { cmd.lowres() })
+    }
+
+    /// A single-player v1.9 demo (v1.91 with longtics) of the run.
+    pub fn to_demo(&self) -> Demo {
+        let header = DemoHeader {
+            version: Some(if self.longtics { LONGTICS_VERSION } else { DOOM_VERSION }),
+            skill: self.skill - 1,
+            episode: self.episode,
+            map: self.map,
+            deathmatch: 0,
+            respawn: false,
+            fast: false,
+            nomonsters: false,
+            consoleplayer: 0,
+            players: [true, false, false, false],
+        };
+        Demo { header, tics: self.tics.iter().map(|&cmd| vec![cmd]).collect(), end: End::Marker { trailing: 0 } }
+    }
+
+    pub fn from_demo(demo: &Demo) -> anyhow::Result<Self> {
+        let h = &demo.header;
+        if h.player_count() != 1 || h.deathmatch != 0 {
+            bail!("only single-player demos can become timelines ({} players)", h.player_count());
+        }
+        // A timeline plays as player 1, which is what export writes.
+        if h.players != [true, false, false, false] || h.consoleplayer != 0 {
+            bail!("only demos recorded as player 1 can become timelines");
+        }
+        if h.respawn || h.fast || h.nomonsters {
+            bail!("demo uses -respawn, -fast or -nomonsters, which timelines don't keep");
+        }
+        Ok(Timeline {
+            skill: h.skill + 1,
+            episode: h.episode,
+            map: h.map,
+            longtics: h.longtics(),
+            tics: demo.tics.iter().map(|players| players[0]).collect(),
+        })
+    }
+}
+
+impl std::fmt::Display for Timeline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut out = format!("{}\nskill {}\nepisode {}\nmap {}\n", HEADER, self.skill, self.episode, self.map);
+        if self.longtics {
+            out.push_str("longtics\n");
+        }
+        out.push_str("# forward side turn buttons [xN]\n");
+        let mut rest = &self.tics[..];
+        while let Some(&cmd) = rest.first() {
+            let run = rest.iter().take_while(|&&c| c == cmd).count();
+            let _ = write!(out, "{} {} {} {}", cmd.forwardmove, cmd.sidemove, cmd.angleturn, format_buttons(cmd.buttons));
+            if run > 1 {
+                let _ = write!(out, " x{}", run);
+            }
+            out.push('\n');
+            rest = &rest[run..];
+        }
+        f.write_str(&out)
+    }
+}
+
+fn parse_tic(words: &[&str]) -> anyhow::Result<(Ticcmd, usize)> {
+    let (fields, count) = match words {
+        [f, s, t, b] => ([f, s, t, b], 1),
+        [f, s, t, b, n] => {
+            let count = n.strip_prefix('x').and_then(|n| n.parse::<usize>().ok());
+            let Some(count) = count.filter(|&c| c > 0) else { bail!("bad repeat {:?}, expected xN", n) };
+            if count > MAX_TICS {
+                bail!("repeat {:?} is more than {} tics", n, MAX_TICS);
+            }
+            ([f, s, t, b], count)
+        }
+        _ => bail!("expected `forward side turn buttons [xN]`"),
+    };
+    let cmd = Ticcmd {
+        forwardmove: fields[0].parse().with_context(|| format!("bad forward move {:?}", fields[0]))?,
+        sidemove: fields[1].parse().with_context(|| format!("bad side move {:?}", fields[1]))?,
+        angleturn: fields[2].parse().with_context(|| format!("bad turn {:?}", fields[2]))?,
+        buttons: parse_buttons(fields[3])?,
+    };
+    Ok((cmd, count))
+}
+
+fn parse_buttons(word: &str) -> anyhow::Result<u8> {
+    if word == "-" {
+        return Ok(0);
+    }
+    if let Some(hex) = word.strip_prefix('$') {
+        return u8::from_str_radix(hex, 16).with_context(|| format!("bad button byte {:?}", word));
+    }
+    let mut b = 0;
+    for c in word.chars() {
+        match c {
+            'A' => b |= Ticcmd::ATTACK,
+            'U' => b |= Ticcmd::USE,
+            '1'..='8' if b & Ticcmd::CHANGE == 0 => {
+                let slot = c as u8 - b'1';
+                b |= Ticcmd::CHANGE | (slot << Ticcmd::WEAPON_SHIFT);
+            }
+            _ => bail!("bad buttons {:?}: expected A, U and one weapon key 1-8, `-` or `$hh`", word),
+        }
+    }
+    Ok(b)
+}
+
+fn format_buttons(b: u8) -> String {
+    let known = Ticcmd::ATTACK | Ticcmd::USE | Ticcmd::CHANGE | Ticcmd::WEAPON_MASK;
+    let slot_without_change = b & Ticcmd::CHANGE == 0 && b & Ticcmd::WEAPON_MASK != 0;
+    if b & !known != 0 || slot_without_change {
+        return format!("${:02x}", b);
+    }
+    let mut out = String::new();
+    if b & Ticcmd::ATTACK != 0 {
+        out.push('A');
+    }
+    if b & Ticcmd::USE != 0 {
+        out.push('U');
+    }
+    if b & Ticcmd::CHANGE != 0 {
+        out.push((b'1' + ((b & Ticcmd::WEAPON_MASK) >> Ticcmd::WEAPON_SHIFT)) as char);
+    }
+    if out.is_empty() {
+        out.push('-');
+    }
+    out
+}
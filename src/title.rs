@@ -0,0 +1,85 @@
+// title.rs
+use std::ffi::CStr;
+
+use rustydoom::constants::{APP_NAME, APP_VERSION};
+use rustydoom::state::{self, GameState};
+
+use crate::{playback, tas};
+use crate::sys;
+
+// Values from d_mode.h (GameMission_t)
+const MISSION_DOOM: i32 = 0;
+const MISSION_DOOM2: i32 = 1;
+const MISSION_TNT: i32 = 2;
+const MISSION_PLUTONIA: i32 = 3;
+
+pub(crate) const SKILL_NAMES: [&str; 5] = [
+    "I'm too young to die",
+    "Hey, not too rough",
+    "Hurt me plenty",
+    "Ultra-Violence",
+    "Nightmare!",
+];
+
+pub fn app_title() -> String {
+    format!("{} v{}", APP_NAME, APP_VERSION)
+}
+
+/// Builds the window title from the engine's own title (set through
+/// `DG_SetWindowTitle`) and, if `show_status` is on, the live game status.
+pub fn window_title(show_status: bool) -> String {
+    let mut title = app_title();
+
+    // SAFETY: the bridge returns null or a pointer to a static NUL-terminated buffer.
+    let engine = unsafe { sys::raw::dg_window_title() };
+    if !engine.is_null() {
+        let engine = unsafe { CStr::from_ptr(engine) }.to_string_lossy();
+        if !engine.is_empty() {
+            title.push_str(" - ");
+            title.push_str(&engine);
+        }
+    }
+
+    // Host pause and fast-forward, and the TAS position, shown whether or
+    // not status is on.
+    for label in [playback::label(), tas::label()].into_iter().flatten() {
+        title.push_str(" [");
+        title.push_str(&label);
+        title.push(']');
+    }
+
+    if show_status {
+        for part in status_parts(&state::game_state()) {
+            title.push_str(" | ");
+            title.push_str(&part);
+        }
+    }
+    title
+}
+
+fn status_parts(st: &GameState) -> Vec<String> {
+    let mut parts = Vec::new();
+    if let Some(game) = game_name(st.mission) {
+        parts.push(game.to_string());
+    }
+    // The attract loop has no "current map" worth showing.
+    if !st.title_screen && st.map > 0 {
+        parts.push(st.map_name());
+        if let Some(skill) = usize::try_from(st.skill - 1).ok().and_then(|s| SKILL_NAMES.get(s)) {
+            parts.push(skill.to_string());
+        }
+    }
+    if st.paused {
+        parts.push("Paused".to_string());
+    }
+    parts
+}
+
+fn game_name(mission: i32) -> Option<&'static str> {
+    match mission {
+        MISSION_DOOM => Some("Doom"),
+        MISSION_DOOM2 => Some("Doom II"),
+        MISSION_TNT | MISSION_PLUTONIA => Some("Final Doom"),
+        _ => None,
+    }
+}
@@ -0,0 +1,190 @@
+// vfs.rs
+//
+// Virtual WAD files served to the engine through csrc/platform/w_file_host.c.
+// Anything mounted here is visible to W_OpenFile/M_FileExists under its
+// virtual path, ahead of the real filesystem, so the IWAD and PWADs can come
+// from memory, from inside zip/pk3 archives or from the binary itself.
+use std::borrow::Cow;
+use std::ffi::CStr;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::sync::Mutex;
+
+use anyhow::{Context, bail};
+use libc::{c_char, c_int, c_void, size_t};
+
+use crate::iwad::{self, Iwad};
+use crate::wad::{Wad, merge};
+
+struct Mount {
+    path: String,
+    data: Cow<'static, [u8]>,
+}
+
+// Mounts are never removed (re-mounting a path swaps its data in place), so
+// a handle is just an index and stays valid. A file open across a re-mount
+// reads the new data; reads are clamped to its length, so that's all it risks.
+static MOUNTS: Mutex<Vec<Mount>> = Mutex::new(Vec::new());
+
+/// Serve `data` to the engine as the file `path`. Re-mounting a path replaces it.
+pub fn mount(path: impl Into<String>, data: impl Into<Cow<'static, [u8]>>) {
+    let (path, data) = (path.into(), data.into());
+    let mut mounts = MOUNTS.lock().unwrap();
+    match mounts.iter_mut().find(|m| m.path == path) {
+        Some(m) => m.data = data,
+        None => mounts.push(Mount { path, data }),
+    }
+}
+
+pub fn is_archive(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("zip") || e.eq_ignore_ascii_case("pk3"))
+}
+
+/// Mount every `.wad` inside a zip/pk3 as `<archive>/<entry>` and return the
+/// virtual paths in archive order.
+pub fn mount_archive(archive: &Path) -> anyhow::Result<Vec<String>> {
+    let file = File::open(archive).with_context(|| format!("opening {}", archive.display()))?;
+    let mut zip = zip::ZipArchive::new(file).with_context(|| format!("reading {}", archive.display()))?;
+
+    let mut paths = Vec::new();
+    for i in 0..zip.len() {
+        let mut entry = zip.by_index(i)?;
+        let name = entry.name().to_string();
+        if !entry.is_file() || !name.to_ascii_lowercase().ends_with(".wad") {
+            continue;
+        }
+        // Grow as the data comes rather than trusting the header's size up
+        // front, and stop at that size.
+        let mut data = Vec::new();
+        let size = entry.size();
+        entry.by_ref().take(size).read_to_end(&mut data).with_context(|| format!("extracting {} from {}", name, archive.display()))?;
+        let path = format!("{}/{}", archive.display(), name);
+        mount(path.clone(), data);
+        paths.push(path);
+    }
+    if paths.is_empty() {
+        bail!("{} contains no .wad files", archive.display());
+    }
+    Ok(paths)
+}
+
+/// Mount an archive given as the IWAD and pick the first entry that identifies as one.
+pub fn mount_archive_iwad(archive: &Path) -> anyhow::Result<Iwad> {
+    let mut rejected = Vec::new();
+    for path in mount_archive(archive)? {
+        let data = file_bytes(&path).unwrap_or_default();
+        match iwad::identify_bytes(Path::new(&path), data) {
+            Ok(found) => return Ok(found),
+            Err(e) => rejected.push(format!("  {:#}", e)),
+        }
+    }
+    bail!("no IWAD inside {}:\n{}", archive.display(), rejected.join("\n"))
+}
+
+/// Expand `--file` arguments: archives become the WADs inside them.
+pub fn expand_files(files: &[String]) -> anyhow::Result<Vec<String>> {
+    let mut out = Vec::new();
+    for f in files {
+        if is_archive(Path::new(f)) {
+            out.extend(mount_archive(Path::new(f))?);
+        } else {
+            out.push(f.clone());
+        }
+    }
+    Ok(out)
+}
+
+/// Apply deutex-style (`--merge`) then NWT-style (`--nwtmerge`) merges to the
+/// IWAD and mount the result in its place. The virtual path keeps the IWAD's
+/// file name, which the engine uses to tell the game mission.
+pub fn mount_merged_iwad(iwad: &Iwad, merge: &[String], nwtmerge: &[String]) -> anyhow::Result<Iwad> {
+    if merge.is_empty() && nwtmerge.is_empty() {
+        return Ok(iwad.clone());
+    }
+    let mut base = read_wad(&iwad.path.to_string_lossy())?;
+    for f in expand_files(merge)? {
+        println!(" merging {}", f);
+        base = Wad::from_bytes(merge::merge_deutex(&base, &read_wad(&f)?))?;
+    }
+    for f in expand_files(nwtmerge)? {
+        println!(" performing NWT-style merge of {}", f);
+        base = Wad::from_bytes(merge::merge_nwt(&base, &read_wad(&f)?))?;
+    }
+
+    let name = iwad.path.file_name().unwrap_or_default().to_string_lossy();
+    let path = format!("merged/{}", name);
+    mount(path.clone(), base.bytes().to_vec());
+    Ok(Iwad { path: path.into(), ..iwad.clone() })
+}
+
+/// Read a WAD from the VFS if it's mounted there, else from disk.
+pub fn read_wad(path: &str) -> anyhow::Result<Wad> {
+    match file_bytes(path) {
+        Some(data) => Wad::from_bytes(data).with_context(|| format!("parsing {}", path)),
+        None => Wad::open(Path::new(path)),
+    }
+}
+
+#[cfg(feature = "embed-iwad")]
+mod embedded {
+    // Generated by build.rs from RUSTYDOOM_EMBED_IWAD: `NAME` and `DATA`.
+    include!(concat!(env!("OUT_DIR"), "/embedded_iwad.rs"));
+}
+
+/// The IWAD compiled into the binary with the `embed-iwad` feature, if any.
+pub fn mount_embedded_iwad() -> Option<anyhow::Result<Iwad>> {
+    #[cfg(feature = "embed-iwad")]
+    {
+        let path = format!("embedded/{}", embedded::NAME);
+        mount(path.clone(), embedded::DATA);
+        Some(iwad::identify_bytes(Path::new(&path), embedded::DATA.to_vec()))
+    }
+    #[cfg(not(feature = "embed-iwad"))]
+    None
+}
+
+fn file_bytes(path: &str) -> Option<Vec<u8>> {
+    let mounts = MOUNTS.lock().unwrap();
+    mounts.iter().find(|m| m.path == path).map(|m| m.data.to_vec())
+}
+
+// ---- Callbacks for w_file_host.c ----
+
+/// Look up a mounted file; its handle, or -1 if nothing is mounted there.
+///
+/// # Safety
+/// `path` must be a NUL-terminated string and `length` valid for writes (or null).
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dg_vfs_open(path: *const c_char, length: *mut u64) -> c_int {
+    if path.is_null() {
+        return -1;
+    }
+    let path = unsafe { CStr::from_ptr(path) }.to_string_lossy();
+    let mounts = MOUNTS.lock().unwrap();
+    let Some(i) = mounts.iter().position(|m| m.path == path) else { return -1 };
+    if !length.is_null() {
+        unsafe { *length = mounts[i].data.len() as u64 };
+    }
+    i as c_int
+}
+
+/// Copy up to `len` bytes from `offset` in the file; how many were copied.
+///
+/// # Safety
+/// `buffer` must be valid for `len` bytes of writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dg_vfs_read(handle: c_int, offset: u64, buffer: *mut c_void, len: size_t) -> size_t {
+    let mounts = MOUNTS.lock().unwrap();
+    let Some(m) = usize::try_from(handle).ok().and_then(|h| mounts.get(h)) else { return 0 };
+    let Some(src) = usize::try_from(offset).ok().and_then(|o| m.data.get(o..)) else { return 0 };
+    let n = src.len().min(len);
+    unsafe { std::ptr::copy_nonoverlapping(src.as_ptr(), buffer as *mut u8, n) };
+    n
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn dg_vfs_close(_handle: c_int) {
+    // Mounts live for the whole run; nothing to release per open.
+}
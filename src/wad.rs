@@ -0,0 +1,367 @@
+// wad.rs
+//
+// Pure-Rust WAD reader. Mirrors what w_wad.c does when it loads a file, so
+// host tooling can look at exactly the lumps the engine will see, but refuses
+// to load directories that point outside the file instead of trusting them.
+use std::fmt;
+use std::path::Path;
+
+use anyhow::{Context, bail};
+
+pub mod graphics;
+pub mod merge;
+pub mod mus;
+pub mod sound;
+
+const HEADER_SIZE: usize = 12;
+const DIR_ENTRY_SIZE: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WadKind {
+    Iwad,
+    Pwad,
+}
+
+impl WadKind {
+    fn magic(self) -> &'static [u8; 4] {
+        match self {
+            WadKind::Iwad => b"IWAD",
+            WadKind::Pwad => b"PWAD",
+        }
+    }
+}
+
+impl fmt::Display for WadKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            WadKind::Iwad => "IWAD",
+            WadKind::Pwad => "PWAD",
+        })
+    }
+}
+
+/// One directory entry. `name` is upper-cased, like the engine compares it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LumpInfo {
+    pub name: String,
+    pub offset: usize,
+    pub size: usize,
+}
+
+/// Marker-delimited lump ranges the engine treats specially.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Namespace {
+    /// `S_START`..`S_END` (and the `SS_` PWAD spelling).
+    Sprites,
+    /// `F_START`..`F_END` (and `FF_`).
+    Flats,
+    /// `P_START`..`P_END` (and `PP_`).
+    Patches,
+}
+
+impl Namespace {
+    fn prefixes(self) -> &'static [&'static str] {
+        match self {
+            Namespace::Sprites => &["S", "SS"],
+            Namespace::Flats   => &["F", "FF"],
+            Namespace::Patches => &["P", "PP"],
+        }
+    }
+
+    /// `Some(true)` for an outer start marker (`S_START`, `SS_START`),
+    /// `Some(false)` for an outer end marker, `None` otherwise.
+    fn outer_marker(self, name: &str) -> Option<bool> {
+        let (stem, start) = match name.strip_suffix("_START") {
+            Some(stem) => (stem, true),
+            None => (name.strip_suffix("_END")?, false),
+        };
+        self.prefixes().contains(&stem).then_some(start)
+    }
+
+    /// True for any start/end marker of this namespace, including the
+    /// numbered inner ones (`F1_START`, `P2_END`, ...).
+    pub fn is_marker(self, name: &str) -> bool {
+        let Some(stem) = name.strip_suffix("_START").or_else(|| name.strip_suffix("_END")) else {
+            return false;
+        };
+        let letter = &self.prefixes()[0][..1];
+        self.prefixes().contains(&stem)
+            || (stem.len() == 2 && stem.starts_with(letter) && stem.as_bytes()[1].is_ascii_digit())
+    }
+}
+
+/// Lumps that make up a map, in the order they follow the map marker.
+pub const MAP_LUMPS: &[&str] = &[
+    "THINGS", "LINEDEFS", "SIDEDEFS", "VERTEXES", "SEGS",
+    "SSECTORS", "NODES", "SECTORS", "REJECT", "BLOCKMAP",
+];
+
+/// What a lump appears to contain, judged by name, position and content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LumpType {
+    Marker,
+    Map,
+    MapData,
+    Palette,
+    Colormap,
+    Endoom,
+    Textures,
+    PatchNames,
+    Demo,
+    Dehacked,
+    Flat,
+    Sprite,
+    Patch,
+    Sound,
+    Music,
+    Data,
+}
+
+impl LumpType {
+    pub fn name(self) -> &'static str {
+        match self {
+            LumpType::Marker     => "marker",
+            LumpType::Map        => "map",
+            LumpType::MapData    => "mapdata",
+            LumpType::Palette    => "palette",
+            LumpType::Colormap   => "colormap",
+            LumpType::Endoom     => "endoom",
+            LumpType::Textures   => "textures",
+            LumpType::PatchNames => "pnames",
+            LumpType::Demo       => "demo",
+            LumpType::Dehacked   => "dehacked",
+            LumpType::Flat       => "flat",
+            LumpType::Sprite     => "sprite",
+            LumpType::Patch      => "patch",
+            LumpType::Sound      => "sound",
+            LumpType::Music      => "music",
+            LumpType::Data       => "data",
+        }
+    }
+}
+
+impl fmt::Display for LumpType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A WAD file held in memory with a validated directory.
+#[derive(Debug, Clone)]
+pub struct Wad {
+    kind: WadKind,
+    lumps: Vec<LumpInfo>,
+    data: Vec<u8>,
+}
+
+impl Wad {
+    pub fn open(path: &Path) -> anyhow::Result<Wad> {
+        let data = std::fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+        Wad::from_bytes(data).with_context(|| format!("parsing {}", path.display()))
+    }
+
+    /// Parse a WAD image, checking that the header, directory and every lump
+    /// lie inside `data`.
+    pub fn from_bytes(data: Vec<u8>) -> anyhow::Result<Wad> {
+        if data.len() < HEADER_SIZE {
+            bail!("file too short for a WAD header ({} bytes)", data.len());
+        }
+        let kind = match &data[0..4] {
+            b"IWAD" => WadKind::Iwad,
+            b"PWAD" => WadKind::Pwad,
+            _ => bail!("not a WAD file (bad magic)"),
+        };
+        let numlumps = read_i32(&data, 4);
+        let dir_ofs = read_i32(&data, 8);
+        if numlumps < 0 || dir_ofs < 0 {
+            bail!("corrupt WAD header (numlumps {}, directory offset {})", numlumps, dir_ofs);
+        }
+        let (numlumps, dir_ofs) = (numlumps as usize, dir_ofs as usize);
+        let dir_end = numlumps
+            .checked_mul(DIR_ENTRY_SIZE)
+            .and_then(|n| n.checked_add(dir_ofs))
+            .filter(|&end| end <= data.len());
+        let Some(dir_end) = dir_end else {
+            bail!("directory of {} lumps at offset {} runs past end of file ({} bytes)",
+                  numlumps, dir_ofs, data.len());
+        };
+
+        let mut lumps = Vec::with_capacity(numlumps);
+        for (i, e) in data[dir_ofs..dir_end].chunks_exact(DIR_ENTRY_SIZE).enumerate() {
+            let offset = read_i32(e, 0);
+            let size = read_i32(e, 4);
+            let name = lump_name(&e[8..16]);
+            if offset < 0 || size < 0 {
+                bail!("lump {} ({}) has negative offset or size", i, name);
+            }
+            let (offset, size) = (offset as usize, size as usize);
+            // Zero-length markers often carry junk offsets; only real data must fit.
+            if size > 0 && offset.checked_add(size).is_none_or(|end| end > data.len()) {
+                bail!("lump {} ({}) at offset {} size {} runs past end of file", i, name, offset, size);
+            }
+            lumps.push(LumpInfo { name, offset, size });
+        }
+        Ok(Wad { kind, lumps, data })
+    }
+
+    pub fn kind(&self) -> WadKind {
+        self.kind
+    }
+
+    pub fn lumps(&self) -> &[LumpInfo] {
+        &self.lumps
+    }
+
+    /// The whole file, e.g. for checksums.
+    pub fn bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Index of the last lump called `name`, matching W_CheckNumForName
+    /// (later lumps override earlier ones).
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.lumps.iter().rposition(|l| l.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.find(name).is_some()
+    }
+
+    pub fn lump(&self, index: usize) -> Option<&[u8]> {
+        let l = self.lumps.get(index)?;
+        if l.size == 0 {
+            return Some(&[]);
+        }
+        Some(&self.data[l.offset..l.offset + l.size])
+    }
+
+    pub fn lump_by_name(&self, name: &str) -> Option<&[u8]> {
+        self.lump(self.find(name)?)
+    }
+
+    /// Indices of the lumps inside `ns`, in directory order, without the markers.
+    /// A WAD may open the same namespace several times; all ranges are returned.
+    pub fn namespace(&self, ns: Namespace) -> Vec<usize> {
+        let mut out = Vec::new();
+        let mut inside = false;
+        for (i, l) in self.lumps.iter().enumerate() {
+            if ns.is_marker(&l.name) {
+                // Outer markers switch the range; inner numbered ones are just skipped.
+                if let Some(start) = ns.outer_marker(&l.name) {
+                    inside = start;
+                }
+                continue;
+            }
+            if inside {
+                out.push(i);
+            }
+        }
+        out
+    }
+
+    /// Classify every lump. Done for the whole directory at once because
+    /// sprites and flats are only recognisable by the markers around them.
+    pub fn lump_types(&self) -> Vec<LumpType> {
+        let mut types = vec![LumpType::Data; self.lumps.len()];
+        for i in self.namespace(Namespace::Flats) {
+            types[i] = LumpType::Flat;
+        }
+        for i in self.namespace(Namespace::Sprites) {
+            types[i] = LumpType::Sprite;
+        }
+        for (i, l) in self.lumps.iter().enumerate() {
+            if types[i] != LumpType::Data {
+                continue;
+            }
+            let data = self.lump(i).unwrap_or_default();
+            let next_is_things = self.lumps.get(i + 1).is_some_and(|n| n.name == "THINGS");
+            types[i] = match l.name.as_str() {
+                _ if next_is_things => LumpType::Map,
+                n if MAP_LUMPS.contains(&n) => LumpType::MapData,
+                _ if l.size == 0 => LumpType::Marker,
+                "PLAYPAL" => LumpType::Palette,
+                "COLORMAP" => LumpType::Colormap,
+                "ENDOOM" => LumpType::Endoom,
+                "TEXTURE1" | "TEXTURE2" => LumpType::Textures,
+                "PNAMES" => LumpType::PatchNames,
+                "DEHACKED" => LumpType::Dehacked,
+                n if n.starts_with("DEMO") => LumpType::Demo,
+                _ if mus::is_mus(data) => LumpType::Music,
+                n if n.starts_with("DS") && sound::is_dmx_sound(data) => LumpType::Sound,
+                _ if graphics::is_patch(data) => LumpType::Patch,
+                _ => LumpType::Data,
+            };
+        }
+        types
+    }
+
+    /// Map header lumps (`E1M1`, `MAP01`, ...): a marker followed by THINGS.
+    pub fn maps(&self) -> Vec<&str> {
+        self.lumps
+            .windows(2)
+            .filter(|w| w[1].name == "THINGS")
+            .map(|w| w[0].name.as_str())
+            .collect()
+    }
+}
+
+/// Builds WAD images in memory; used by tools that write lumps back out and
+/// by tests that need small synthetic WADs.
+#[derive(Debug, Clone)]
+pub struct WadBuilder {
+    kind: WadKind,
+    lumps: Vec<(String, Vec<u8>)>,
+}
+
+impl WadBuilder {
+    pub fn new(kind: WadKind) -> Self {
+        Self { kind, lumps: Vec::new() }
+    }
+
+    pub fn lump(mut self, name: &str, data: impl Into<Vec<u8>>) -> Self {
+        self.lumps.push((name.to_string(), data.into()));
+        self
+    }
+
+    /// A zero-length marker lump such as `F_START`.
+    pub fn marker(self, name: &str) -> Self {
+        self.lump(name, Vec::new())
+    }
+
+    /// Lay out the lump data after the header, then the directory.
+    pub fn build(&self) -> Vec<u8> {
+        let data_len: usize = self.lumps.iter().map(|(_, d)| d.len()).sum();
+        let dir_ofs = HEADER_SIZE + data_len;
+
+        let mut out = Vec::with_capacity(dir_ofs + self.lumps.len() * DIR_ENTRY_SIZE);
+        out.extend_from_slice(self.kind.magic());
+        out.extend_from_slice(&(self.lumps.len() as i32).to_le_bytes());
+        out.extend_from_slice(&(dir_ofs as i32).to_le_bytes());
+        for (_, d) in &self.lumps {
+            out.extend_from_slice(d);
+        }
+
+        let mut offset = HEADER_SIZE;
+        for (name, d) in &self.lumps {
+            out.extend_from_slice(&(offset as i32).to_le_bytes());
+            out.extend_from_slice(&(d.len() as i32).to_le_bytes());
+            let mut raw = [0u8; 8];
+            for (dst, src) in raw.iter_mut().zip(name.bytes()) {
+                *dst = src.to_ascii_uppercase();
+            }
+            out.extend_from_slice(&raw);
+            offset += d.len();
+        }
+        out
+    }
+}
+
+fn read_i32(buf: &[u8], at: usize) -> i32 {
+    i32::from_le_bytes(buf[at..at + 4].try_into().unwrap())
+}
+
+/// Lump names are up to 8 bytes, NUL padded; anything after the first NUL is junk.
+fn lump_name(raw: &[u8]) -> String {
+    let end = raw.iter().position(|&b| b == 0).unwrap_or(raw.len());
+    String::from_utf8_lossy(&raw[..end]).to_ascii_uppercase()
+}
@@ -0,0 +1,167 @@
+// wad/graphics.rs
+//
+// Decoding of Doom's palettised picture formats (column-based patches and
+// raw 64x64 flats) into RGBA, plus PNG input and output.
+use std::io::{Read, Write};
+
+use anyhow::{Context, bail};
+
+/// One palette in PLAYPAL: 256 RGB triples. The lump holds 14 of them.
+pub const PLAYPAL_SIZE: usize = 768;
+pub const FLAT_SIZE: usize = 64 * 64;
+
+/// The first 256-colour palette from a PLAYPAL lump.
+#[derive(Debug, Clone)]
+pub struct Palette([[u8; 3]; 256]);
+
+impl Palette {
+    pub fn from_playpal(lump: &[u8]) -> anyhow::Result<Palette> {
+        if lump.len() < PLAYPAL_SIZE {
+            bail!("PLAYPAL is {} bytes, need at least {}", lump.len(), PLAYPAL_SIZE);
+        }
+        let mut colors = [[0u8; 3]; 256];
+        for (c, rgb) in colors.iter_mut().zip(lump.chunks_exact(3)) {
+            c.copy_from_slice(rgb);
+        }
+        Ok(Palette(colors))
+    }
+
+    pub fn rgb(&self, index: u8) -> [u8; 3] {
+        self.0[index as usize]
+    }
+}
+
+/// A decoded picture: row-major RGBA, alpha 0 where a patch has no pixels.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub left_offset: i16,
+    pub top_offset: i16,
+    pub rgba: Vec<u8>,
+}
+
+impl Image {
+    /// The engine's framebuffer (0x00RRGGBB pixels, row-major) as an opaque picture.
+    pub fn from_xrgb(pixels: &[u32], width: usize, height: usize) -> Image {
+        let rgba = pixels
+            .iter()
+            .flat_map(|&px| {
+                let [_, r, g, b] = px.to_be_bytes();
+                [r, g, b, 0xFF]
+            })
+            .collect();
+        Image { width, height, left_offset: 0, top_offset: 0, rgba }
+    }
+}
+
+/// Check that `lump` has a plausible patch header and column table, without decoding.
+pub fn is_patch(lump: &[u8]) -> bool {
+    patch_header(lump).is_some()
+}
+
+fn patch_header(lump: &[u8]) -> Option<(usize, usize, i16, i16)> {
+    if lump.len() < 8 {
+        return None;
+    }
+    let width = u16::from_le_bytes([lump[0], lump[1]]) as usize;
+    let height = u16::from_le_bytes([lump[2], lump[3]]) as usize;
+    if width == 0 || height == 0 || width > 4096 || height > 4096 {
+        return None;
+    }
+    let table_end = 8 + width * 4;
+    if lump.len() < table_end {
+        return None;
+    }
+    let columns_ok = (0..width).all(|x| {
+        let ofs = read_u32(lump, 8 + x * 4) as usize;
+        ofs >= table_end && ofs < lump.len()
+    });
+    columns_ok.then(|| {
+        let left = i16::from_le_bytes([lump[4], lump[5]]);
+        let top = i16::from_le_bytes([lump[6], lump[7]]);
+        (width, height, left, top)
+    })
+}
+
+/// Decode a patch (sprites, wall patches, menu graphics, ...).
+pub fn decode_patch(lump: &[u8], pal: &Palette) -> anyhow::Result<Image> {
+    let (width, height, left_offset, top_offset) =
+        patch_header(lump).context("not a valid patch")?;
+    let mut rgba = vec![0u8; width * height * 4];
+
+    for x in 0..width {
+        let mut p = read_u32(lump, 8 + x * 4) as usize;
+        loop {
+            let Some(&topdelta) = lump.get(p) else { bail!("column {} runs off the end", x) };
+            if topdelta == 0xFF {
+                break;
+            }
+            let Some(&len) = lump.get(p + 1) else { bail!("column {} runs off the end", x) };
+            // Post layout: topdelta, length, pad, pixels[length], pad.
+            let start = p + 3;
+            let Some(pixels) = lump.get(start..start + len as usize) else {
+                bail!("post in column {} runs off the end", x)
+            };
+            for (i, &idx) in pixels.iter().enumerate() {
+                let y = topdelta as usize + i;
+                if y >= height {
+                    break;
+                }
+                let o = (y * width + x) * 4;
+                let [r, g, b] = pal.rgb(idx);
+                rgba[o..o + 4].copy_from_slice(&[r, g, b, 0xFF]);
+            }
+            p = start + len as usize + 1;
+        }
+    }
+    Ok(Image { width, height, left_offset, top_offset, rgba })
+}
+
+/// Decode a 64x64 flat (floor/ceiling texture).
+pub fn decode_flat(lump: &[u8], pal: &Palette) -> anyhow::Result<Image> {
+    if lump.len() < FLAT_SIZE {
+        bail!("flat is {} bytes, expected {}", lump.len(), FLAT_SIZE);
+    }
+    let rgba = lump[..FLAT_SIZE]
+        .iter()
+        .flat_map(|&i| {
+            let [r, g, b] = pal.rgb(i);
+            [r, g, b, 0xFF]
+        })
+        .collect();
+    Ok(Image { width: 64, height: 64, left_offset: 0, top_offset: 0, rgba })
+}
+
+/// Encode `img` as an RGBA PNG.
+pub fn write_png(img: &Image, out: impl Write) -> anyhow::Result<()> {
+    let mut enc = png::Encoder::new(out, img.width as u32, img.height as u32);
+    enc.set_color(png::ColorType::Rgba);
+    enc.set_depth(png::BitDepth::Eight);
+    let mut w = enc.write_header()?;
+    w.write_image_data(&img.rgba)?;
+    Ok(())
+}
+
+/// Decode a PNG of any 8- or 16-bit colour type into RGBA.
+pub fn read_png(input: impl Read) -> anyhow::Result<Image> {
+    let mut dec = png::Decoder::new(input);
+    dec.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = dec.read_info()?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf)?;
+    buf.truncate(info.buffer_size());
+    let rgba = match info.color_type {
+        png::ColorType::Rgba => buf,
+        png::ColorType::Rgb => buf.chunks_exact(3).flat_map(|p| [p[0], p[1], p[2], 0xFF]).collect(),
+        png::ColorType::GrayscaleAlpha => buf.chunks_exact(2).flat_map(|p| [p[0], p[0], p[0], p[1]]).collect(),
+        png::ColorType::Grayscale => buf.iter().flat_map(|&v| [v, v, v, 0xFF]).collect(),
+        png::ColorType::Indexed => bail!("indexed PNG wasn't expanded"),
+    };
+    let (width, height) = (info.width as usize, info.height as usize);
+    Ok(Image { width, height, left_offset: 0, top_offset: 0, rgba })
+}
+
+fn read_u32(buf: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(buf[at..at + 4].try_into().unwrap())
+}
@@ -0,0 +1,208 @@
+// wad/merge.rs
+//
+// Host-side replacement for Chocolate Doom's w_merge.c. Instead of patching
+// the engine's lump directory at load time we build a merged IWAD image and
+// serve it to the engine, which then sees one consistent sprite and flat
+// namespace. Two flavours, matching the old command-line tools:
+//
+// * deutex `-merge`: PWAD sprites and flats go into the IWAD's S_START/S_END
+//   and F_START/F_END sections; IWAD sprite lumps whose frames and rotations
+//   are all superseded are dropped, same-named flats are replaced.
+// * NWT `-merge`: PWAD sprites and flats overwrite same-named IWAD lumps in
+//   place; ones the IWAD doesn't have are added at the end of the section.
+//
+// In both cases the rest of the PWAD is appended, as `-file` would.
+use std::collections::HashMap;
+
+use super::{Namespace, Wad, WadBuilder};
+
+/// A lump in the merged directory, borrowed from either input.
+type Entry<'a> = (&'a str, &'a [u8]);
+
+pub fn merge_deutex(base: &Wad, pwad: &Wad) -> Vec<u8> {
+    let pwad_sprites = lumps_in(pwad, Namespace::Sprites);
+    let pwad_flats = lumps_in(pwad, Namespace::Flats);
+    let frames = SpriteFrames::build(base, &pwad_sprites);
+
+    let mut out: Vec<Entry> = Vec::with_capacity(base.lumps().len() + pwad.lumps().len());
+    let mut section = None;
+    let (mut did_sprites, mut did_flats) = (false, false);
+
+    for (i, l) in base.lumps().iter().enumerate() {
+        let data = base.lump(i).unwrap_or_default();
+        match (section, l.name.as_str()) {
+            (None, "S_START") => section = Some(Namespace::Sprites),
+            (None, "F_START") => section = Some(Namespace::Flats),
+            (Some(Namespace::Sprites), "S_END") => {
+                out.extend(&pwad_sprites);
+                did_sprites = true;
+                section = None;
+            }
+            (Some(Namespace::Flats), "F_END") => {
+                out.extend(&pwad_flats);
+                did_flats = true;
+                section = None;
+            }
+            (Some(Namespace::Sprites), name) if !frames.base_lump_needed(i, name) => continue,
+            (Some(Namespace::Flats), name) if pwad_flats.iter().any(|(n, _)| *n == name) => continue,
+            _ => {}
+        }
+        out.push((&l.name, data));
+    }
+
+    add_missing_section(&mut out, did_sprites, "S_START", "S_END", &pwad_sprites);
+    add_missing_section(&mut out, did_flats, "F_START", "F_END", &pwad_flats);
+    append_rest(&mut out, pwad);
+    build(base, &out)
+}
+
+pub fn merge_nwt(base: &Wad, pwad: &Wad) -> Vec<u8> {
+    let mut out: Vec<Entry> = Vec::with_capacity(base.lumps().len() + pwad.lumps().len());
+    for (i, l) in base.lumps().iter().enumerate() {
+        out.push((&l.name, base.lump(i).unwrap_or_default()));
+    }
+
+    // Replace in place for both sections before splicing anything in, while
+    // base indices still line up with `out`.
+    let sections = [(Namespace::Sprites, "S_START", "S_END"), (Namespace::Flats, "F_START", "F_END")];
+    let mut added = [Vec::new(), Vec::new()];
+    for ((ns, _, _), added) in sections.iter().zip(&mut added) {
+        let section = base.namespace(*ns);
+        for (name, data) in lumps_in(pwad, *ns) {
+            match section.iter().find(|&&i| base.lumps()[i].name == name) {
+                Some(&i) => out[i].1 = data,
+                None => added.push((name, data)),
+            }
+        }
+    }
+
+    for ((_, start, end), added) in sections.into_iter().zip(added) {
+        if added.is_empty() {
+            continue;
+        }
+        match out.iter().position(|(n, _)| *n == end) {
+            Some(at) => {
+                out.splice(at..at, added);
+            }
+            None => add_missing_section(&mut out, false, start, end, &added),
+        }
+    }
+
+    append_rest(&mut out, pwad);
+    build(base, &out)
+}
+
+fn lumps_in(wad: &Wad, ns: Namespace) -> Vec<Entry<'_>> {
+    wad.namespace(ns)
+        .into_iter()
+        .map(|i| (wad.lumps()[i].name.as_str(), wad.lump(i).unwrap_or_default()))
+        .collect()
+}
+
+/// A base without the section gets one at the end, so the new lumps are still found.
+fn add_missing_section<'a>(out: &mut Vec<Entry<'a>>, done: bool, start: &'a str, end: &'a str, lumps: &[Entry<'a>]) {
+    if done || lumps.is_empty() {
+        return;
+    }
+    out.push((start, &[]));
+    out.extend(lumps);
+    out.push((end, &[]));
+}
+
+/// Everything in the PWAD outside its sprite and flat sections.
+fn append_rest<'a>(out: &mut Vec<Entry<'a>>, pwad: &'a Wad) {
+    let mut inside = false;
+    for (i, l) in pwad.lumps().iter().enumerate() {
+        let marker = [Namespace::Sprites, Namespace::Flats]
+            .iter()
+            .find(|ns| ns.is_marker(&l.name));
+        if let Some(ns) = marker {
+            if let Some(start) = ns.outer_marker(&l.name) {
+                inside = start;
+            }
+            continue;
+        }
+        if !inside {
+            out.push((&l.name, pwad.lump(i).unwrap_or_default()));
+        }
+    }
+}
+
+fn build(base: &Wad, entries: &[Entry]) -> Vec<u8> {
+    entries
+        .iter()
+        .fold(WadBuilder::new(base.kind()), |b, (name, data)| b.lump(name, *data))
+        .build()
+}
+
+/// Which lump draws each (sprite, frame, rotation), after the PWAD's
+/// sprites have been laid over the base's.
+struct SpriteFrames {
+    // (sprite name, frame letter) -> owner of rotations 1..=8
+    slots: HashMap<([u8; 4], u8), [Option<Owner>; 8]>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Owner {
+    Base(usize),
+    Pwad,
+}
+
+impl SpriteFrames {
+    fn build(base: &Wad, pwad_sprites: &[Entry]) -> Self {
+        let mut frames = SpriteFrames { slots: HashMap::new() };
+        for i in base.namespace(Namespace::Sprites) {
+            frames.claim(&base.lumps()[i].name, Owner::Base(i));
+        }
+        for (name, _) in pwad_sprites {
+            frames.claim(name, Owner::Pwad);
+        }
+        frames
+    }
+
+    fn claim(&mut self, name: &str, owner: Owner) {
+        for (frame, rotation) in sprite_frames(name) {
+            let slots = self.slots.entry((sprite_name(name), frame)).or_default();
+            match rotation {
+                0 => slots.fill(Some(owner)),
+                r => slots[r as usize - 1] = Some(owner),
+            }
+        }
+    }
+
+    /// A base sprite survives if it still draws at least one rotation.
+    /// Lumps that aren't valid sprite names, or were never claimed (odd
+    /// markers can leave them out), are always kept.
+    fn base_lump_needed(&self, index: usize, name: &str) -> bool {
+        let frames = sprite_frames(name);
+        frames.is_empty()
+            || frames.iter().any(|&(frame, rotation)| {
+                let Some(slots) = self.slots.get(&(sprite_name(name), frame)) else { return true };
+                let owner = Some(Owner::Base(index));
+                match rotation {
+                    0 => slots.contains(&owner),
+                    r => slots[r as usize - 1] == owner,
+                }
+            })
+    }
+}
+
+/// Only called for names `sprite_frames` accepted, which are at least 6 bytes.
+fn sprite_name(name: &str) -> [u8; 4] {
+    name.as_bytes()[..4].try_into().unwrap()
+}
+
+/// The (frame, rotation) pairs a sprite lump provides: `TROOA1` gives one,
+/// `TROOA2A8` two (the second mirrored). Empty if the name isn't a sprite name.
+fn sprite_frames(name: &str) -> Vec<(u8, u8)> {
+    let b = name.as_bytes();
+    let pair = |f: u8, r: u8| (f.is_ascii_graphic() && (b'0'..=b'8').contains(&r)).then_some((f, r - b'0'));
+    match b.len() {
+        6 => pair(b[4], b[5]).into_iter().collect(),
+        8 => match (pair(b[4], b[5]), pair(b[6], b[7])) {
+            (Some(a), Some(c)) => vec![a, c],
+            _ => Vec::new(),
+        },
+        _ => Vec::new(),
+    }
+}
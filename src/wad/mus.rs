@@ -0,0 +1,155 @@
+// wad/mus.rs
+//
+// DMX MUS music lumps to Standard MIDI (format 0), following the same
+// channel and controller mapping as Chocolate Doom's mus2mid.
+use anyhow::{Context, bail};
+
+const MUS_MAGIC: &[u8; 4] = b"MUS\x1a";
+const MUS_PERCUSSION: u8 = 15;
+const MIDI_PERCUSSION: u8 = 9;
+/// MUS runs at 140 ticks/s: 70 ticks per quarter note at the default 120 bpm.
+const MIDI_DIVISION: u16 = 70;
+/// The longest delta time a MIDI variable-length quantity holds (28 bits).
+const MAX_DELAY: u32 = 0x0FFF_FFFF;
+
+/// MUS controller numbers 1..=9 to MIDI controllers (0 is program change).
+/// Bank select goes to the LSB, 32, as mus2mid has it.
+const CONTROLLER_MAP: [u8; 10] = [0, 32, 1, 7, 10, 11, 91, 93, 64, 67];
+/// MUS system events 10..=14 to MIDI channel-mode controllers.
+const SYSTEM_MAP: [u8; 5] = [120, 123, 126, 127, 121];
+
+pub fn is_mus(lump: &[u8]) -> bool {
+    lump.starts_with(MUS_MAGIC)
+}
+
+/// Convert a MUS lump into a complete .mid file image.
+pub fn mus_to_midi(lump: &[u8]) -> anyhow::Result<Vec<u8>> {
+    if !is_mus(lump) || lump.len() < 16 {
+        bail!("not a MUS lump");
+    }
+    let score_len = u16::from_le_bytes([lump[4], lump[5]]) as usize;
+    let score_start = u16::from_le_bytes([lump[6], lump[7]]) as usize;
+    let score = lump
+        .get(score_start..)
+        .context("MUS score starts past the end of the lump")?;
+    let score = &score[..score_len.min(score.len())];
+
+    let mut track = Vec::new();
+    // Tempo: 500000 us per quarter note.
+    track.extend_from_slice(&[0x00, 0xFF, 0x51, 0x03, 0x07, 0xA1, 0x20]);
+
+    let mut velocities = [127u8; 16];
+    let mut delay: u32 = 0;
+    let mut p = 0;
+    let byte = |p: &mut usize| -> anyhow::Result<u8> {
+        let b = *score.get(*p).context("MUS score ends mid-event")?;
+        *p += 1;
+        Ok(b)
+    };
+
+    loop {
+        let desc = byte(&mut p)?;
+        let channel = midi_channel(desc & 0x0F);
+        let kind = (desc >> 4) & 0x07;
+        let mut event = |track: &mut Vec<u8>, bytes: &[u8]| {
+            write_vlq(track, delay);
+            delay = 0;
+            track.extend_from_slice(bytes);
+        };
+
+        match kind {
+            0 => {
+                let note = byte(&mut p)? & 0x7F;
+                event(&mut track, &[0x80 | channel, note, 0x40]);
+            }
+            1 => {
+                let note = byte(&mut p)?;
+                if note & 0x80 != 0 {
+                    velocities[channel as usize] = byte(&mut p)? & 0x7F;
+                }
+                let vel = velocities[channel as usize];
+                event(&mut track, &[0x90 | channel, note & 0x7F, vel]);
+            }
+            2 => {
+                // 0..=255 with 128 centred, to MIDI's 14-bit bend.
+                let bend = byte(&mut p)? as u16 * 64;
+                event(&mut track, &[0xE0 | channel, (bend & 0x7F) as u8, (bend >> 7) as u8 & 0x7F]);
+            }
+            3 => {
+                let ctrl = byte(&mut p)?;
+                if let Some(&midi) = ctrl.checked_sub(10).and_then(|i| SYSTEM_MAP.get(i as usize)) {
+                    event(&mut track, &[0xB0 | channel, midi, 0]);
+                }
+            }
+            4 => {
+                let ctrl = byte(&mut p)?;
+                let value = byte(&mut p)?.min(127);
+                match ctrl {
+                    0 => event(&mut track, &[0xC0 | channel, value]),
+                    1..=9 => event(&mut track, &[0xB0 | channel, CONTROLLER_MAP[ctrl as usize], value]),
+                    _ => {}
+                }
+            }
+            5 | 7 => {}
+            6 => break,
+            _ => unreachable!(),
+        }
+
+        if desc & 0x80 != 0 {
+            // Events that write nothing (and their delays) add up.
+            delay = delay
+                .checked_add(read_vlq(score, &mut p)?)
+                .filter(|&d| d <= MAX_DELAY)
+                .context("MUS delay too long for a MIDI file")?;
+        }
+    }
+
+    write_vlq(&mut track, delay);
+    track.extend_from_slice(&[0xFF, 0x2F, 0x00]);
+
+    let mut out = Vec::with_capacity(22 + track.len());
+    out.extend_from_slice(b"MThd");
+    out.extend_from_slice(&6u32.to_be_bytes());
+    out.extend_from_slice(&0u16.to_be_bytes()); // format 0
+    out.extend_from_slice(&1u16.to_be_bytes()); // one track
+    out.extend_from_slice(&MIDI_DIVISION.to_be_bytes());
+    out.extend_from_slice(b"MTrk");
+    out.extend_from_slice(&(track.len() as u32).to_be_bytes());
+    out.extend_from_slice(&track);
+    Ok(out)
+}
+
+/// MUS channel 15 is percussion; everything else skips over MIDI channel 9.
+fn midi_channel(mus: u8) -> u8 {
+    match mus {
+        MUS_PERCUSSION => MIDI_PERCUSSION,
+        c if c >= MIDI_PERCUSSION => c + 1,
+        c => c,
+    }
+}
+
+fn read_vlq(buf: &[u8], p: &mut usize) -> anyhow::Result<u32> {
+    let mut value: u32 = 0;
+    for _ in 0..4 {
+        let b = *buf.get(*p).context("MUS score ends mid-delay")?;
+        *p += 1;
+        value = (value << 7) | (b & 0x7F) as u32;
+        if b & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    bail!("MUS delay longer than 4 bytes")
+}
+
+fn write_vlq(out: &mut Vec<u8>, mut value: u32) {
+    let mut buf = [0u8; 5];
+    let mut i = buf.len() - 1;
+    buf[i] = (value & 0x7F) as u8;
+    value >>= 7;
+    while value > 0 {
+        i -= 1;
+        buf[i] = (value & 0x7F) as u8 | 0x80;
+        value >>= 7;
+    }
+    out.extend_from_slice(&buf[i..]);
+}
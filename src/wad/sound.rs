@@ -0,0 +1,58 @@
+// wad/sound.rs
+//
+// DMX digital sound lumps (the DS* lumps) to WAV.
+use std::io::Write;
+
+use anyhow::bail;
+
+const DMX_FORMAT: u16 = 3;
+const DMX_HEADER: usize = 8;
+/// DMX pads each sample buffer with 16 bytes at both ends.
+const DMX_PAD: usize = 16;
+
+/// A decoded DMX sound: 8-bit unsigned mono PCM.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sound {
+    pub sample_rate: u32,
+    pub samples: Vec<u8>,
+}
+
+pub fn is_dmx_sound(lump: &[u8]) -> bool {
+    lump.len() >= DMX_HEADER && u16::from_le_bytes([lump[0], lump[1]]) == DMX_FORMAT
+}
+
+pub fn decode_dmx(lump: &[u8]) -> anyhow::Result<Sound> {
+    if !is_dmx_sound(lump) {
+        bail!("not a DMX sound lump");
+    }
+    let sample_rate = u16::from_le_bytes([lump[2], lump[3]]) as u32;
+    let count = u32::from_le_bytes(lump[4..8].try_into().unwrap()) as usize;
+    let body = &lump[DMX_HEADER..];
+    if count > body.len() {
+        bail!("sound claims {} samples but only {} bytes follow", count, body.len());
+    }
+    let mut samples = &body[..count];
+    if samples.len() > 2 * DMX_PAD {
+        samples = &samples[DMX_PAD..samples.len() - DMX_PAD];
+    }
+    Ok(Sound { sample_rate, samples: samples.to_vec() })
+}
+
+/// Write `snd` as a RIFF/WAVE file.
+pub fn write_wav(snd: &Sound, mut out: impl Write) -> anyhow::Result<()> {
+    let data_len = snd.samples.len() as u32;
+    out.write_all(b"RIFF")?;
+    out.write_all(&(36 + data_len).to_le_bytes())?;
+    out.write_all(b"WAVEfmt ")?;
+    out.write_all(&16u32.to_le_bytes())?;       // fmt chunk size
+    out.write_all(&1u16.to_le_bytes())?;        // PCM
+    out.write_all(&1u16.to_le_bytes())?;        // mono
+    out.write_all(&snd.sample_rate.to_le_bytes())?;
+    out.write_all(&snd.sample_rate.to_le_bytes())?; // byte rate: 1 byte per sample
+    out.write_all(&1u16.to_le_bytes())?;        // block align
+    out.write_all(&8u16.to_le_bytes())?;        // bits per sample
+    out.write_all(b"data")?;
+    out.write_all(&data_len.to_le_bytes())?;
+    out.write_all(&snd.samples)?;
+    Ok(())
+}
@@ -0,0 +1,154 @@
+// wad_cmd.rs
+//
+// `rustydoom wad ...`: lump listing, extraction and conversion for modders.
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, bail};
+use rustydoom::iwad;
+use rustydoom::wad::graphics::{self, Palette};
+use rustydoom::wad::{LumpType, MAP_LUMPS, Wad, WadKind, mus, sound};
+
+use crate::cli::WadCommand;
+
+pub fn run(cmd: WadCommand) -> anyhow::Result<()> {
+    match cmd {
+        WadCommand::List { wad } => list(&wad),
+        WadCommand::Extract { wad, lump, output } => extract(&wad, &lump, output),
+        WadCommand::Export { wad, lumps, out_dir, palette } => export(&wad, &lumps, &out_dir, palette.as_deref()),
+        WadCommand::Info { wad } => info(&wad),
+    }
+}
+
+fn list(path: &Path) -> anyhow::Result<()> {
+    let wad = Wad::open(path)?;
+    let types = wad.lump_types();
+    let mut out = io::stdout().lock();
+    writeln!(out, "{:>5}  {:<8}  {:>9}  {:>9}  TYPE", "#", "NAME", "SIZE", "OFFSET")?;
+    for (i, (l, t)) in wad.lumps().iter().zip(&types).enumerate() {
+        writeln!(out, "{:>5}  {:<8}  {:>9}  {:>9}  {}", i, l.name, l.size, l.offset, t)?;
+    }
+    Ok(())
+}
+
+fn extract(path: &Path, name: &str, output: Option<PathBuf>) -> anyhow::Result<()> {
+    let wad = Wad::open(path)?;
+    let data = wad
+        .lump_by_name(name)
+        .with_context(|| format!("no lump named {} in {}", name.to_ascii_uppercase(), path.display()))?;
+
+    let output = output.unwrap_or_else(|| PathBuf::from(format!("{}.lmp", file_stem(name))));
+    if output.as_os_str() == "-" {
+        io::stdout().lock().write_all(data)?;
+    } else {
+        fs::write(&output, data).with_context(|| format!("writing {}", output.display()))?;
+        eprintln!("{} ({} bytes) -> {}", name.to_ascii_uppercase(), data.len(), output.display());
+    }
+    Ok(())
+}
+
+fn export(path: &Path, only: &[String], out_dir: &Path, palette_wad: Option<&Path>) -> anyhow::Result<()> {
+    let wad = Wad::open(path)?;
+    let types = wad.lump_types();
+    fs::create_dir_all(out_dir).with_context(|| format!("creating {}", out_dir.display()))?;
+
+    // Resolved on first use, so sound/music-only PWADs don't need a palette.
+    let mut palette: Option<Palette> = None;
+    let mut load_palette = || -> anyhow::Result<Palette> {
+        if let Some(p) = &palette {
+            return Ok(p.clone());
+        }
+        let p = match (wad.lump_by_name("PLAYPAL"), palette_wad) {
+            (Some(lump), _) => Palette::from_playpal(lump)?,
+            (None, Some(other)) => {
+                let other = Wad::open(other)?;
+                let lump = other.lump_by_name("PLAYPAL")
+                    .with_context(|| format!("{} has no PLAYPAL either", palette_wad.unwrap().display()))?;
+                Palette::from_playpal(lump)?
+            }
+            (None, None) => bail!("{} has no PLAYPAL; pass --palette <iwad>", path.display()),
+        };
+        palette = Some(p.clone());
+        Ok(p)
+    };
+
+    let (mut written, mut skipped) = (0, 0);
+    for (i, (l, &t)) in wad.lumps().iter().zip(&types).enumerate() {
+        let wanted = only.is_empty() || only.iter().any(|n| n.eq_ignore_ascii_case(&l.name));
+        if !wanted {
+            continue;
+        }
+        let data = wad.lump(i).unwrap_or_default();
+        let stem = out_dir.join(file_stem(&l.name));
+        let result = match t {
+            LumpType::Flat => graphics::decode_flat(data, &load_palette()?)
+                .and_then(|img| write_with(&stem.with_extension("png"), |w| graphics::write_png(&img, w))),
+            LumpType::Sprite | LumpType::Patch => graphics::decode_patch(data, &load_palette()?)
+                .and_then(|img| write_with(&stem.with_extension("png"), |w| graphics::write_png(&img, w))),
+            LumpType::Sound => sound::decode_dmx(data)
+                .and_then(|snd| write_with(&stem.with_extension("wav"), |w| sound::write_wav(&snd, w))),
+            LumpType::Music => mus::mus_to_midi(data)
+                .and_then(|mid| write_with(&stem.with_extension("mid"), |w| Ok(w.write_all(&mid)?))),
+            _ => {
+                if !only.is_empty() {
+                    eprintln!("{}: {} lumps can't be converted; use `wad extract`", l.name, t);
+                }
+                continue;
+            }
+        };
+        match result {
+            Ok(()) => written += 1,
+            Err(e) => {
+                eprintln!("{}: skipped: {:#}", l.name, e);
+                skipped += 1;
+            }
+        }
+    }
+    eprintln!("exported {} lumps to {} ({} skipped)", written, out_dir.display(), skipped);
+    Ok(())
+}
+
+fn info(path: &Path) -> anyhow::Result<()> {
+    let wad = Wad::open(path)?;
+    let mut out = io::stdout().lock();
+    writeln!(out, "File:   {}", path.display())?;
+    writeln!(out, "Type:   {}, {} lumps, {} bytes", wad.kind(), wad.lumps().len(), wad.bytes().len())?;
+    writeln!(out, "MD5:    {:x}", md5::compute(wad.bytes()))?;
+    if wad.kind() == WadKind::Iwad {
+        match iwad::identify(path) {
+            Ok(found) => writeln!(out, "Game:   {} {}", found.game, found.release.unwrap_or("(unrecognised release)"))?,
+            Err(e) => writeln!(out, "Game:   unknown ({:#})", e)?,
+        }
+    }
+
+    let maps = wad.maps();
+    writeln!(out, "Maps:   {}", maps.len())?;
+    for map in maps {
+        // Checksum the map's own lumps so edited versions of a map can be told apart.
+        let start = wad.lumps().iter().position(|l| l.name == map).unwrap();
+        let mut ctx = md5::Context::new();
+        for (i, l) in wad.lumps().iter().enumerate().skip(start + 1) {
+            if !MAP_LUMPS.contains(&l.name.as_str()) {
+                break;
+            }
+            ctx.consume(wad.lump(i).unwrap_or_default());
+        }
+        writeln!(out, "  {:<8} {:x}", map, ctx.finalize())?;
+    }
+    Ok(())
+}
+
+fn write_with(path: &Path, f: impl FnOnce(&mut BufWriter<File>) -> anyhow::Result<()>) -> anyhow::Result<()> {
+    let mut w = BufWriter::new(File::create(path).with_context(|| format!("creating {}", path.display()))?);
+    f(&mut w)?;
+    w.flush()?;
+    Ok(())
+}
+
+/// Lump names may contain characters that aren't safe in file names (`VILE\1`).
+fn file_stem(name: &str) -> String {
+    name.chars()
+        .map(|c| if matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|') { '^' } else { c })
+        .collect()
+}
@@ -0,0 +1,14 @@
+use rustydoom::args::normalize_doom_args;
+
+#[test]
+fn doubles_doom_style_long_flags() {
+    let args = normalize_doom_args(["rustydoom", "-iwad", "doom.wad", "-warp", "1", "3", "-file", "a.wad"]);
+    assert_eq!(args, ["rustydoom", "--iwad", "doom.wad", "--warp", "1", "3", "--file", "a.wad"]);
+}
+
+#[test]
+fn leaves_everything_else_alone() {
+    let args = ["--iwad", "-o", "-", "--", "-1", "-9x", "demo.lmp", "--skill=4", "x-y"];
+    assert_eq!(normalize_doom_args(args), args);
+    assert!(normalize_doom_args(Vec::<String>::new()).is_empty());
+}
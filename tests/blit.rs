@@ -0,0 +1,70 @@
+use rustydoom::blit::blit_nn;
+use rustydoom::config::Scaler;
+
+/// A frame whose every pixel says where it came from.
+fn frame(w: usize, h: usize) -> Vec<u32> {
+    (0..w * h).map(|i| 0x0100_0000 | i as u32).collect()
+}
+
+fn blit(dw: usize, dh: usize, src: &[u32], sw: usize, sh: usize, scaler: Scaler) -> Vec<u32> {
+    let mut dst = vec![0xdead_beef; dw * dh];
+    blit_nn(&mut dst, dw, dh, src, sw, sh, scaler);
+    dst
+}
+
+/// The drawn rectangle: (x, y, width, height).
+fn drawn(dst: &[u32], dw: usize) -> (usize, usize, usize, usize) {
+    let lit: Vec<usize> = (0..dst.len()).filter(|&i| dst[i] != 0).collect();
+    let (xs, ys) = (lit.iter().map(|i| i % dw), lit.iter().map(|i| i / dw));
+    let (x0, x1) = (xs.clone().min().unwrap(), xs.max().unwrap());
+    let (y0, y1) = (ys.clone().min().unwrap(), ys.max().unwrap());
+    (x0, y0, x1 - x0 + 1, y1 - y0 + 1)
+}
+
+#[test]
+fn fit_keeps_aspect_and_centers() {
+    let src = frame(320, 200);
+    // Wider than 8:5: bars left and right.
+    let dst = blit(800, 400, &src, 320, 200, Scaler::Fit);
+    assert_eq!(drawn(&dst, 800), (80, 0, 640, 400));
+    assert_eq!(dst[80], src[0]);
+    assert_eq!(dst[399 * 800 + 719], src[199 * 320 + 319]);
+    // Taller: bars above and below.
+    let dst = blit(320, 300, &src, 320, 200, Scaler::Fit);
+    assert_eq!(drawn(&dst, 320), (0, 50, 320, 200));
+    assert_eq!(&dst[50 * 320..51 * 320], &src[..320]);
+}
+
+#[test]
+fn fit_handles_odd_and_tiny_windows() {
+    let src = frame(320, 200);
+    for (dw, dh) in [(1, 1), (1, 1000), (1000, 1), (2, 3), (3, 2), (321, 201), (639, 399), (7, 5000)] {
+        let dst = blit(dw, dh, &src, 320, 200, Scaler::Fit);
+        let (x, y, w, h) = drawn(&dst, dw);
+        assert!(w >= 1 && h >= 1 && x + w <= dw && y + h <= dh, "{}x{}: drew {}x{} at {},{}", dw, dh, w, h, x, y);
+        // Centered, to within a pixel of rounding.
+        assert!((dw - w) / 2 == x && (dh - h) / 2 == y, "{}x{}: off center", dw, dh);
+        assert_eq!(dst[y * dw + x], src[0]);
+    }
+}
+
+#[test]
+fn integer_and_stretch() {
+    let src = frame(4, 2);
+    let dst = blit(13, 7, &src, 4, 2, Scaler::Integer);
+    assert_eq!(drawn(&dst, 13), (0, 0, 12, 6));
+    assert_eq!(&dst[..12], &[src[0], src[0], src[0], src[1], src[1], src[1], src[2], src[2], src[2], src[3], src[3], src[3]]);
+    // Below 1x it fits instead.
+    let dst = blit(2, 2, &src, 4, 2, Scaler::Integer);
+    assert_eq!(dst, [src[0], src[2], 0, 0]);
+
+    let dst = blit(8, 1, &src, 4, 2, Scaler::Stretch);
+    assert_eq!(dst, [src[0], src[0], src[1], src[1], src[2], src[2], src[3], src[3]]);
+}
+
+#[test]
+fn empty_sizes_draw_nothing() {
+    let src = frame(4, 2);
+    assert_eq!(blit(0, 5, &src, 4, 2, Scaler::Fit), []);
+    assert_eq!(blit(3, 3, &src, 0, 0, Scaler::Fit), [0xdead_beef; 9]);
+}
@@ -0,0 +1,206 @@
+// The IWAD the engine tests run against, generated so that no game data is
+// needed: a palette, the status bar and HUD, a checkered one-room E1M1 with a
+// barrel and a medikit, and the same room as E1M2 with two zombiemen to
+// fight.
+use std::path::PathBuf;
+
+use rustydoom::wad::{WadBuilder, WadKind};
+
+/// A fresh scratch directory `name` under the target dir, holding the test
+/// IWAD as `doom1.wad`.
+pub fn iwad_dir(name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("doom1.wad"), test_iwad()).unwrap();
+    dir
+}
+
+/// A patch of `w`x`h` solid columns, shaded by `color(x, y)`.
+fn patch(w: usize, h: usize, left: i16, top: i16, color: impl Fn(usize, usize) -> u8) -> Vec<u8> {
+    let mut out = Vec::new();
+    for v in [w as i16, h as i16, left, top] {
+        out.extend_from_slice(&v.to_le_bytes());
+    }
+    let mut columns = Vec::new();
+    for x in 0..w {
+        out.extend_from_slice(&((8 + 4 * w + columns.len()) as u32).to_le_bytes());
+        columns.extend_from_slice(&[0, h as u8, 0]);
+        columns.extend((0..h).map(|y| color(x, y)));
+        columns.extend_from_slice(&[0, 0xff]);
+    }
+    out.extend(columns);
+    out
+}
+
+fn solid(w: usize, h: usize, color: u8) -> Vec<u8> {
+    patch(w, h, 0, 0, |_, _| color)
+}
+
+/// A thing's sprite, standing on the floor.
+fn sprite(w: usize, h: usize, color: u8) -> Vec<u8> {
+    patch(w, h, w as i16 / 2, h as i16, |x, _| color - (x % 4) as u8 * 8)
+}
+
+fn le16(values: &[i16]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn name8(name: &str) -> [u8; 8] {
+    let mut raw = [0u8; 8];
+    raw[..name.len()].copy_from_slice(name.as_bytes());
+    raw
+}
+
+/// Everything the engine loads to start a game and draw E1M1: a grey
+/// palette with light levels, the status bar and HUD font, a checkered
+/// 512x512 room with a barrel and a medikit, and the pistol. E1M2 is the
+/// room again with two zombiemen facing the player instead.
+pub fn test_iwad() -> Vec<u8> {
+    let mut wad = WadBuilder::new(WadKind::Iwad)
+        .lump("PLAYPAL", (0..14 * 256).flat_map(|i| [(i % 256) as u8; 3]).collect::<Vec<_>>())
+        .lump(
+            "COLORMAP",
+            (0..34 * 256)
+                .map(|i| match (i / 256, i % 256) {
+                    (level @ 0..32, c) => (c * (32 - level) / 32) as u8,
+                    (32, c) => 255 - c as u8,
+                    _ => 0,
+                })
+                .collect::<Vec<_>>(),
+        )
+        .lump("ENDOOM", [0x20, 0x07].repeat(2000))
+        .lump("TITLEPIC", solid(320, 200, 40))
+        .lump("STBAR", solid(320, 32, 90))
+        .lump("STARMS", solid(40, 32, 100))
+        .lump("STTPRCNT", solid(14, 16, 200))
+        .lump("STTMINUS", solid(14, 16, 200));
+
+    let mut small = Vec::new();
+    small.extend((33..=95).map(|c| format!("STCFN{:03}", c)));
+    for i in 0..10 {
+        small.extend([format!("STTNUM{}", i), format!("STYSNUM{}", i)]);
+    }
+    small.extend((0..6).map(|i| format!("STKEYS{}", i)));
+    small.extend((2..8).map(|i| format!("STGNUM{}", i)));
+    small.extend((0..4).flat_map(|i| [format!("STFB{}", i), format!("STPB{}", i)]));
+    for pain in 0..5 {
+        small.extend((0..3).map(|i| format!("STFST{}{}", pain, i)));
+        small.extend(["STFTR", "STFTL"].map(|f| format!("{}{}0", f, pain)));
+        small.extend(["STFOUCH", "STFEVL", "STFKILL"].map(|f| format!("{}{}", f, pain)));
+    }
+    small.extend(["STFGOD0", "STFDEAD0", "D_INTRO", "D_E1M1", "D_E1M2"].map(String::from));
+    for name in &small {
+        let shade = 150 + (name.len() * 7 % 100) as u8;
+        wad = wad.lump(name, solid(8, 8, shade));
+    }
+
+    // One wall patch checkered 8x16, and every texture the switch list and
+    // sky look up made of it; texture 0 means none, so a dummy goes first.
+    wad = wad
+        .lump("PNAMES", [&1u32.to_le_bytes()[..], &name8("WALL")].concat())
+        .lump("WALL", patch(64, 128, 0, 0, |x, y| if (x / 8 + y / 16) % 2 == 0 { 200 } else { 120 }));
+    let mut textures = vec!["AASTINKY".to_string(), "STARTAN3".to_string(), "SKY1".to_string()];
+    for base in [
+        "BRCOM", "BRN1", "BRN2", "BRNGN", "BROWN", "COMM", "COMP", "DIRT", "EXIT", "GRAY", "GRAY1", "METAL",
+        "PIPE", "SLAD", "STARG", "STON1", "STON2", "STONE", "STRTN",
+    ] {
+        textures.extend([format!("SW1{}", base), format!("SW2{}", base)]);
+    }
+    let defs: Vec<Vec<u8>> = textures
+        .iter()
+        .map(|name| {
+            let mut def = name8(name).to_vec();
+            def.extend_from_slice(&0u32.to_le_bytes()); // masked
+            def.extend(le16(&[64, 128]));
+            def.extend_from_slice(&0u32.to_le_bytes()); // columndirectory
+            def.extend(le16(&[1, 0, 0, 0, 1, 0])); // one patch at 0,0
+            def
+        })
+        .collect();
+    let mut texture1 = (defs.len() as u32).to_le_bytes().to_vec();
+    let mut offset = 4 + 4 * defs.len();
+    for def in &defs {
+        texture1.extend_from_slice(&(offset as u32).to_le_bytes());
+        offset += def.len();
+    }
+    texture1.extend(defs.concat());
+    wad = wad.lump("TEXTURE1", texture1);
+
+    // A square room, one sector and one subsector, so no nodes.
+    // E1M1 has a barrel and a medikit, E1M2 two zombiemen.
+    let corners: [(i16, i16); 4] = [(-256, -256), (-256, 256), (256, 256), (256, -256)];
+    let things = le16(&[-192, 0, 0, 1, 7, 128, 64, 0, 2035, 7, 64, -96, 0, 2012, 7]);
+    let arena = le16(&[-192, 0, 0, 1, 7, 160, 48, 180, 3004, 7, 160, -48, 180, 3004, 7]);
+    let linedefs: Vec<i16> = (0..4).flat_map(|i| [i, (i + 1) % 4, 1, 0, 0, i, -1]).collect();
+    let sidedef = [&le16(&[0, 0])[..], &name8("-"), &name8("-"), &name8("STARTAN3"), &le16(&[0])].concat();
+    let segs: Vec<i16> = (0..4)
+        .flat_map(|i| {
+            let ((x0, y0), (x1, y1)) = (corners[i], corners[(i + 1) % 4]);
+            let angle = ((y1 - y0) as f64).atan2((x1 - x0) as f64) / std::f64::consts::TAU * 65536.0;
+            [i as i16, (i as i16 + 1) % 4, angle.rem_euclid(65536.0) as u16 as i16, i as i16, 0, 0]
+        })
+        .collect();
+    let sector = [&le16(&[0, 128])[..], &name8("FLOOR"), &name8("CEIL"), &le16(&[192, 0, 0])].concat();
+    // 5x5 blocks of 128 from (-264,-264); each lists the walls it touches.
+    let mut lists = Vec::new();
+    for row in 0..5 {
+        for col in 0..5 {
+            let (bx, by) = (-264 + col * 128, -264 + row * 128);
+            let mut list = vec![0i16];
+            for (i, &(x0, y0)) in corners.iter().enumerate() {
+                let (x1, y1) = corners[(i + 1) % 4];
+                if x0.max(x1) >= bx && x0.min(x1) < bx + 128 && y0.max(y1) >= by && y0.min(y1) < by + 128 {
+                    list.push(i as i16);
+                }
+            }
+            list.push(-1);
+            lists.push(list);
+        }
+    }
+    let mut blockmap = vec![-264, -264, 5, 5];
+    let mut at = 4 + lists.len();
+    for list in &lists {
+        blockmap.push(at as i16);
+        at += list.len();
+    }
+    blockmap.extend(lists.concat());
+    for (map, things) in [("E1M1", things), ("E1M2", arena)] {
+        wad = wad
+            .marker(map)
+            .lump("THINGS", things)
+            .lump("LINEDEFS", le16(&linedefs))
+            .lump("SIDEDEFS", sidedef.repeat(4))
+            .lump("VERTEXES", le16(&corners.iter().flat_map(|&(x, y)| [x, y]).collect::<Vec<_>>()))
+            .lump("SEGS", le16(&segs))
+            .lump("SSECTORS", le16(&[4, 0]))
+            .marker("NODES")
+            .lump("SECTORS", sector.clone())
+            .lump("REJECT", vec![0])
+            .lump("BLOCKMAP", le16(&blockmap));
+    }
+
+    wad = wad
+        .marker("F_START")
+        .lump("FLOOR", (0..4096).map(|i| if (i % 64 / 8 + i / 512) % 2 == 0 { 80 } else { 110 }).collect::<Vec<u8>>())
+        .lump("CEIL", vec![60; 4096])
+        .lump("F_SKY1", vec![0; 4096])
+        .marker("F_END")
+        .marker("S_START");
+    for frame in ["PISGA0", "PISGB0", "PISGC0", "PISGD0", "PISGE0", "PISFA0", "PUNGA0", "PUNGB0", "PUNGC0", "PUNGD0"] {
+        wad = wad.lump(frame, patch(32, 40, -144, -128, |x, y| 160 + ((x / 4 + y / 8) % 2) as u8 * 60));
+    }
+    // The zombieman in all its frames, and what a fight leaves around.
+    for frame in 'A'..='U' {
+        wad = wad.lump(&format!("POSS{}0", frame), sprite(20, 52, 180 + (frame as u8 - b'A')));
+    }
+    for frame in ["PUFFA0", "PUFFB0", "PUFFC0", "PUFFD0", "BLUDA0", "BLUDB0", "BLUDC0"] {
+        wad = wad.lump(frame, sprite(8, 8, 240));
+    }
+    wad.lump("CLIPA0", sprite(12, 8, 210))
+        .lump("BAR1A0", sprite(24, 32, 230))
+        .lump("BAR1B0", sprite(24, 32, 220))
+        .lump("MEDIA0", sprite(28, 20, 250))
+        .marker("S_END")
+        .build()
+}
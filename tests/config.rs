@@ -0,0 +1,52 @@
+use rustydoom::config::{HostConfig, Scaler};
+
+#[test]
+fn partial_file_keeps_defaults() {
+    let c = HostConfig::parse("[window]\nwidth = 1280\n\n[video]\nscaler = \"integer\"\n").unwrap();
+    assert_eq!(c.window.width, 1280);
+    assert_eq!(c.window.height, 600);
+    assert_eq!(c.video.scaler, Scaler::Integer);
+    assert_eq!(c.bindings.quicksave, "F6");
+    assert!(c.audio.sfx);
+}
+
+#[test]
+fn rejects_unknown_settings() {
+    assert!(HostConfig::parse("[window]\nwidht = 1280\n").is_err());
+    assert!(HostConfig::parse("[video]\nscaler = \"blurry\"\n").is_err());
+}
+
+#[test]
+fn get_and_set_by_key() {
+    let mut c = HostConfig::default();
+    assert!(HostConfig::keys().contains(&"bindings.rewind".to_string()));
+    assert_eq!(c.get("video.scaler").as_deref(), Some("fit"));
+    assert_eq!(c.get("window.nope"), None);
+
+    c.set("window.fullscreen", "true").unwrap();
+    c.set("window.height", "800").unwrap();
+    c.set("bindings.quickload", "F12").unwrap();
+    c.set("video.scaler", "stretch").unwrap();
+    assert!(c.window.fullscreen);
+    assert_eq!(c.window.height, 800);
+    assert_eq!(c.get("bindings.quickload").as_deref(), Some("F12"));
+    assert_eq!(c.video.scaler, Scaler::Stretch);
+
+    assert!(c.set("window.height", "tall").is_err());
+    assert!(c.set("window.height", "-1").is_err());
+    assert!(c.set("video.scaler", "blurry").is_err());
+    assert!(c.set("nope", "1").is_err());
+    assert_eq!(c.window.height, 800);
+}
+
+#[test]
+fn saved_file_loads_back() {
+    let path = std::env::temp_dir().join(format!("rustydoom-config-{}.toml", std::process::id()));
+    let mut c = HostConfig::default();
+    c.set("audio.music", "false").unwrap();
+    c.save(&path).unwrap();
+    let back = HostConfig::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(back, c);
+    assert_eq!(HostConfig::load(&path).unwrap(), HostConfig::default());
+}
@@ -0,0 +1,86 @@
+use rustydoom::deh::{self, Change, Table};
+
+fn sets(patch: &deh::Patch) -> Vec<(Table, i32, usize, i32)> {
+    patch
+        .changes
+        .iter()
+        .filter_map(|c| match *c {
+            Change::Set { table, index, field, value, .. } => Some((table, index, field, value)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn parses_vanilla_sections() {
+    let patch = deh::parse(
+        b"Patch File for DeHackEd v3.0\n\
+          # a comment\n\
+          Doom version = 19\n\
+          Patch format = 6\n\
+          \n\
+          Thing 1 (Player)\n\
+          Hit points = 200\n\
+          Bits = 2147483648\n\
+          \n\
+          Frame 10\n\
+          Duration = -1\n\
+          \n\
+          Pointer 5 (Frame 12)\n\
+          Codep Frame = 20\n\
+          \n\
+          Misc 0\n\
+          Initial Bullets = 100\n\
+          Monsters Infight = 221\n",
+    );
+    assert_eq!(patch.warnings, []);
+    assert_eq!(
+        sets(&patch),
+        [
+            (Table::Thing, 0, 2, 200),
+            (Table::Thing, 0, 21, i32::MIN),
+            (Table::Frame, 10, 2, -1),
+            (Table::Frame, 12, 6, 20),
+            (Table::Misc, 0, 1, 100),
+            (Table::Misc, 0, 15, 1),
+        ]
+    );
+}
+
+#[test]
+fn text_spans_lines_and_ignores_cr() {
+    let patch = deh::parse(b"Text 6 9\r\nE1M1: Hangar\r\nE1\r\n\r\nAmmo 0\r\nMax ammo = 400\r\n");
+    assert_eq!(patch.warnings, []);
+    match &patch.changes[0] {
+        Change::Text { from, to, .. } => {
+            assert_eq!(from, "E1M1: ");
+            assert_eq!(to, "Hangar\nE1");
+        }
+        c => panic!("expected text, got {:?}", c),
+    }
+    assert_eq!(sets(&patch), [(Table::Ammo, 0, 0, 400)]);
+}
+
+#[test]
+fn reports_what_it_cannot_apply() {
+    let patch = deh::parse(
+        b"Thing 3\n\
+          Hit points = lots\n\
+          Translucency = 1\n\
+          \n\
+          Cheat 0\n\
+          Chainsaw = idchoppers\n\
+          \n\
+          [STRINGS]\n\
+          GOTARMOR = Armor!\n\
+          \n\
+          HUSTR_1 = level 1\n\
+          \n\
+          Misc 0\n\
+          Monsters Infight = 7\n",
+    );
+    let lines: Vec<usize> = patch.warnings.iter().map(|w| w.line).collect();
+    assert_eq!(lines, [2, 3, 5, 8, 14], "{:#?}", patch.warnings);
+    assert!(patch.warnings[1].message.contains("Translucency"));
+    assert!(patch.changes.is_empty());
+}
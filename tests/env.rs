@@ -0,0 +1,97 @@
+use rustydoom::env::{Action, Controls, DoomEnv, EnvConfig, GameVars, ObservationConfig, Rewards, Screen, Step, parse_map};
+use rustydoom::events::Weapon;
+use rustydoom::lmp::Ticcmd;
+
+mod common;
+
+#[test]
+fn screen_downscales_by_averaging() {
+    // 4x2, two 2x2 blocks: one red/black, one white.
+    let fb = [0xff0000, 0x000000, 0xffffff, 0xffffff, 0x000000, 0xff0000, 0xffffff, 0xffffff];
+    let rgb = Screen::capture(&fb, 4, 2, &ObservationConfig { scale: 2, greyscale: false });
+    assert_eq!((rgb.width, rgb.height, rgb.channels), (2, 1, 3));
+    assert_eq!(rgb.pixels, [127, 0, 0, 255, 255, 255]);
+
+    let grey = Screen::capture(&fb, 4, 2, &ObservationConfig { scale: 2, greyscale: true });
+    assert_eq!(grey.pixels, [37, 255]);
+
+    let full = Screen::capture(&fb, 4, 2, &ObservationConfig { scale: 1, greyscale: false });
+    assert_eq!((full.width, full.height), (4, 2));
+    assert_eq!(&full.pixels[..6], [255, 0, 0, 0, 0, 0]);
+}
+
+#[test]
+fn actions_become_ticcmds() {
+    let run_left = Controls { forward: true, turn_left: true, run: true, attack: true, ..Default::default() };
+    assert_eq!(
+        Action::Discrete(run_left).to_ticcmd(),
+        Ticcmd { forwardmove: 0x32, sidemove: 0, angleturn: 1280, buttons: Ticcmd::ATTACK }
+    );
+    let back_right = Controls { backward: true, strafe_right: true, weapon: Some(Weapon::SuperShotgun), ..Default::default() };
+    assert_eq!(
+        back_right.to_ticcmd(),
+        Ticcmd { forwardmove: -0x19, sidemove: 0x18, angleturn: 0, buttons: Ticcmd::CHANGE | (2 << Ticcmd::WEAPON_SHIFT) }
+    );
+
+    let analog = Action::Continuous { forward: 0.5, strafe: -2.0, turn: -90.0, attack: false, use_: true };
+    assert_eq!(analog.to_ticcmd(), Ticcmd { forwardmove: 25, sidemove: -0x28, angleturn: -16384, buttons: Ticcmd::USE });
+}
+
+#[test]
+fn map_names() {
+    assert_eq!(parse_map("e2m7", false), Some((2, 7)));
+    assert_eq!(parse_map("MAP32", true), Some((1, 32)));
+    assert_eq!(parse_map("MAP01", false), None);
+    assert_eq!(parse_map("E1M1", true), None);
+    assert_eq!(parse_map("E1M10", false), None);
+}
+
+#[test]
+fn rewards_follow_the_player() {
+    let rewards = Rewards::default();
+    let before = GameVars { health: 100, ..Default::default() };
+    let after = GameVars { health: 80, kills: 2, items: 1, ..before };
+    assert!((rewards.score(&before, &after, false) - (2.0 + 0.1 - 0.2)).abs() < 1e-6);
+
+    let dead = GameVars { health: -10, dead: true, ..after };
+    assert!((rewards.score(&after, &dead, false) - (-0.8 - 5.0)).abs() < 1e-6);
+    assert_eq!(rewards.score(&before, &before, true), 10.0);
+}
+
+/// The whole environment in one test: the engine can only boot once a process.
+#[test]
+fn episodes_replay_exactly() {
+    let dir = common::iwad_dir("env");
+    let iwad = dir.join("doom1.wad");
+    let config = EnvConfig { iwad: Some(iwad), configdir: Some(dir.clone()), max_tics: Some(160), ..Default::default() };
+    let mut env = DoomEnv::new(config.clone()).unwrap();
+    assert!(DoomEnv::new(config).is_err(), "a second environment booted");
+
+    let fight = Action::Continuous { forward: 0.0, strafe: 0.0, turn: 0.3, attack: true, use_: false };
+    let mut episode = |seed| {
+        let start = env.reset("E1M2", 3, seed).unwrap();
+        let mut steps: Vec<Step> = Vec::new();
+        while steps.last().is_none_or(|s| !s.done) {
+            steps.push(env.step(&fight));
+        }
+        (start, steps)
+    };
+    let (start, steps) = episode(7);
+    assert_eq!(start.vars.health, 100);
+    assert_eq!(start.vars.weapon, Some(Weapon::Pistol));
+    assert_eq!((start.screen.width, start.screen.height), (320, 200));
+    assert_eq!(steps.len(), 40);
+    let last = steps.last().unwrap();
+    assert!(last.info.truncated && !last.info.dead);
+    assert!(last.observation.vars.ammo[0] < start.vars.ammo[0], "the pistol never fired");
+
+    // The same seed and actions play out the same game, frame for frame.
+    let (again, replay) = episode(7);
+    assert!(again == start, "the episode started differently");
+    assert_eq!(replay.len(), steps.len());
+    for (i, (a, b)) in replay.iter().zip(&steps).enumerate() {
+        assert_eq!(a.observation.vars, b.observation.vars, "step {}", i);
+        assert!(a.observation.screen == b.observation.screen, "step {} looked different", i);
+        assert_eq!((a.reward, a.done, a.info), (b.reward, b.done, b.info), "step {}", i);
+    }
+}
@@ -0,0 +1,84 @@
+use std::sync::{Arc, Mutex};
+
+use rustydoom::bridge::sys;
+use rustydoom::env::{DoomEnv, EnvConfig};
+use rustydoom::events::{self, DgEvent, Event, EventKind, Weapon};
+use rustydoom::input;
+use rustydoom::lmp::Ticcmd;
+
+mod common;
+
+fn raw(kind: i32, player: i32, a: i32, b: i32, name: &str) -> DgEvent {
+    let mut ev = DgEvent { kind, tic: 412, player, a, b, ..Default::default() };
+    for (dst, src) in ev.name.iter_mut().zip(name.bytes()) {
+        *dst = src as libc::c_char;
+    }
+    ev
+}
+
+#[test]
+fn decodes_engine_events() {
+    let pickup = Event::from_raw(&raw(5, 0, 61, 0, "MEDI")).unwrap();
+    assert_eq!(pickup.tic, 412);
+    assert_eq!(pickup.kind, EventKind::Pickup { player: 1, item: "MEDI".into(), thing_type: 61 });
+
+    let damage = Event::from_raw(&raw(3, 1, 12, 88, "")).unwrap();
+    assert_eq!(damage.kind, EventKind::Damage { player: 2, amount: 12, health: 88, source: None });
+
+    let kill = Event::from_raw(&raw(6, -1, 1, 0, "POSS")).unwrap();
+    assert_eq!(kill.kind, EventKind::Kill { player: None, monster: "POSS".into(), thing_type: 1 });
+
+    let weapon = Event::from_raw(&raw(8, 0, 8, 0, "")).unwrap();
+    assert_eq!(weapon.kind, EventKind::WeaponChange { player: 1, weapon: Weapon::SuperShotgun });
+
+    assert!(Event::from_raw(&raw(99, 0, 0, 0, "")).is_none());
+    assert!(Event::from_raw(&raw(8, 0, 9, 0, "")).is_none());
+}
+
+#[test]
+fn serializes_as_flat_json() {
+    let ev = Event { tic: 7, kind: EventKind::Secret { player: 1, sector: 42 } };
+    let line = serde_json::to_string(&ev).unwrap();
+    assert_eq!(line, r#"{"tic":7,"event":"secret","player":1,"sector":42}"#);
+    assert_eq!(serde_json::from_str::<Event>(&line).unwrap(), ev);
+}
+
+fn tick(tics: usize) {
+    for _ in 0..tics {
+        unsafe { sys::raw::dg_tick() };
+    }
+    events::dispatch();
+}
+
+/// Rewinding reloads the level inside the engine, which mustn't come out as
+/// a level start or weapon raise. One test: the engine boots once a process.
+#[test]
+fn rewinds_report_nothing() {
+    let dir = common::iwad_dir("events");
+    let iwad = dir.join("doom1.wad");
+    let mut env = DoomEnv::new(EnvConfig { iwad: Some(iwad), configdir: Some(dir), ..Default::default() }).unwrap();
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let seen = log.clone();
+    events::set_handler(move |ev| seen.lock().unwrap().push(ev.kind.clone()));
+    env.reset("E1M1", 3, 0).unwrap();
+    tick(10);
+
+    let mut data = std::ptr::null_mut();
+    let mut len = 0;
+    assert_eq!(unsafe { sys::raw::dg_snapshot_save(&mut data, &mut len) }, 0);
+    let snapshot = unsafe { std::slice::from_raw_parts(data, len) }.to_vec();
+    unsafe { sys::raw::dg_snapshot_free(data) };
+
+    // Switching to the fist is reported, so events do get through...
+    input::hold(Ticcmd { buttons: 0x04, ..Default::default() }); // BT_CHANGE, weapon 0
+    tick(1);
+    input::release();
+    tick(35);
+    assert_eq!(std::mem::take(&mut *log.lock().unwrap()), [EventKind::WeaponChange { player: 1, weapon: Weapon::Fist }]);
+
+    // ...but going back to before it isn't anything.
+    assert_eq!(unsafe { sys::raw::dg_snapshot_load(snapshot.as_ptr(), snapshot.len()) }, 0);
+    tick(1);
+    assert_eq!(std::mem::take(&mut *log.lock().unwrap()), []);
+    events::clear_handler();
+}
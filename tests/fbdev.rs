@@ -0,0 +1,96 @@
+use std::fs::{File, OpenOptions};
+use std::path::PathBuf;
+
+use rustydoom::config::Scaler;
+use rustydoom::evdev::{self, EV_KEY, EV_REL, InputDevice, InputEvent, REL_X};
+use rustydoom::fbdev::{Framebuffer, Layout, PixelFormat};
+use rustydoom::present::Presenter;
+
+const FRAME: [u32; 4] = [0x00ff_0000, 0x0000_ff00, 0x0000_00ff, 0x0080_4020];
+
+fn scratch(name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("fbdev");
+    std::fs::create_dir_all(&dir).unwrap();
+    dir.join(name)
+}
+
+/// A 2x2 screen in `format` with rows padded to `stride`, after the given
+/// offset into a file of 0xee bytes; what presenting FRAME leaves in it.
+fn present_to_file(name: &str, format: PixelFormat, stride: usize, offset: u64) -> Vec<u8> {
+    let path = scratch(name);
+    std::fs::write(&path, vec![0xee; offset as usize + 2 * stride]).unwrap();
+    let file = OpenOptions::new().write(true).open(&path).unwrap();
+    let layout = Layout { width: 2, height: 2, stride, offset, format };
+    let mut fb = Framebuffer::with_layout(file, layout, Scaler::Stretch).unwrap();
+    fb.resize(640, 480).unwrap();
+    fb.present(&FRAME, 2, 2).unwrap();
+    std::fs::read(&path).unwrap()
+}
+
+#[test]
+fn writes_32_bit_pixels() {
+    let bytes = present_to_file("xrgb.fb", PixelFormat::XRGB8888, 8, 0);
+    assert_eq!(bytes, [0, 0, 255, 0, 0, 255, 0, 0, 255, 0, 0, 0, 0x20, 0x40, 0x80, 0]);
+}
+
+#[test]
+fn writes_24_bit_pixels_at_the_offset() {
+    let bytes = present_to_file("rgb.fb", PixelFormat::RGB888, 8, 4);
+    assert_eq!(bytes[..4], [0xee; 4]);
+    assert_eq!(bytes[4..10], [0, 0, 255, 0, 255, 0]);
+    // The padding past each row is left as it was.
+    assert_eq!(bytes[10..12], [0xee; 2]);
+    assert_eq!(bytes[12..18], [255, 0, 0, 0x20, 0x40, 0x80]);
+    assert_eq!(bytes[18..], [0xee; 2]);
+}
+
+#[test]
+fn writes_16_bit_pixels() {
+    let bytes = present_to_file("rgb565.fb", PixelFormat::RGB565, 4, 0);
+    let pixels: Vec<u16> = bytes.chunks(2).map(|p| u16::from_le_bytes([p[0], p[1]])).collect();
+    assert_eq!(pixels, [0xf800, 0x07e0, 0x001f, (0x80 * 31 / 255) << 11 | (0x40 * 63 / 255) << 5 | (0x20 * 31 / 255)]);
+
+    // BGR order is just other offsets.
+    let bgr = PixelFormat::new(16, (0, 5), (5, 6), (11, 5));
+    assert_eq!(bgr.pack(0xff0000), 0x001f);
+}
+
+#[test]
+fn scales_channels_to_their_width() {
+    // 10 bits a channel, two spare: full intensity is 0x3ff, not 0xff << 2.
+    let rgb30 = PixelFormat::new(32, (20, 10), (10, 10), (0, 10));
+    assert_eq!(rgb30.pack(0xffffff), 0x3fff_ffff);
+    assert_eq!(rgb30.pack(0x804020), (0x80 * 1023 / 255) << 20 | (0x40 * 1023 / 255) << 10 | (0x20 * 1023 / 255));
+    let bytes = present_to_file("rgb30.fb", rgb30, 8, 0);
+    assert_eq!(bytes[..4], 0x3ff0_0000u32.to_le_bytes());
+}
+
+#[test]
+fn rejects_what_it_cant_draw() {
+    let file = File::create(scratch("bad.fb")).unwrap();
+    let palette = Layout { width: 2, height: 2, stride: 2, offset: 0, format: PixelFormat::new(8, (0, 8), (0, 8), (0, 8)) };
+    assert!(Framebuffer::with_layout(file.try_clone().unwrap(), palette, Scaler::Fit).is_err());
+    let narrow = Layout { width: 2, height: 2, stride: 6, offset: 0, format: PixelFormat::XRGB8888 };
+    assert!(Framebuffer::with_layout(file, narrow, Scaler::Fit).is_err());
+    assert!(Framebuffer::open(&scratch("bad.fb"), Scaler::Fit).is_err());
+}
+
+#[test]
+fn reads_input_events() {
+    let events = [
+        InputEvent { kind: EV_KEY, code: 30, value: 1 },
+        InputEvent { kind: EV_REL, code: REL_X, value: -3 },
+        InputEvent { kind: EV_KEY, code: 30, value: 0 },
+    ];
+    let bytes: Vec<u8> = events.iter().flat_map(|&e| evdev::encode_event(e)).collect();
+    assert_eq!(evdev::parse_events(&bytes), events);
+    // Devices only return whole events; a torn one at the end is dropped.
+    assert_eq!(evdev::parse_events(&bytes[..bytes.len() - 1]), events[..2]);
+
+    let path = scratch("events");
+    std::fs::write(&path, &bytes).unwrap();
+    let mut device = InputDevice::open(&path).unwrap();
+    assert_eq!(device.read().unwrap(), events);
+    assert_eq!(device.read().unwrap(), []);
+    assert_eq!(evdev::open_devices(&[path, scratch("missing")]).len(), 1);
+}
@@ -0,0 +1,61 @@
+// Renders a few tics of a generated one-room IWAD with `rustydoom test` and
+// compares them against the PNGs in tests/golden. After a deliberate change
+// to the rendering, run with RUSTYDOOM_UPDATE_GOLDEN=1 to rewrite them.
+use std::path::Path;
+use std::process::Command;
+
+mod common;
+
+#[test]
+fn renders_like_the_goldens() {
+    let dir = common::iwad_dir("golden");
+    let iwad = dir.join("doom1.wad");
+    std::fs::write(
+        dir.join("walk.rhai"),
+        "hold(#{ forward: 0.5, turn: 2.0 });\ntick(30);\nhold(#{ strafe: -1.0 });\ntick(20);\n",
+    )
+    .unwrap();
+
+    let golden = |name: &str| Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(name);
+    let manifest = format!(
+        r#"iwad = {iwad:?}
+
+[[case]]
+name = "start"
+map = "E1M1"
+[[case.frames]]
+tic = 35
+golden = {start:?}
+
+[[case]]
+name = "walk"
+map = "E1M1"
+script = "walk.rhai"
+[[case.frames]]
+tic = 30
+golden = {turned:?}
+[[case.frames]]
+tic = 50
+golden = {strafed:?}
+"#,
+        iwad = iwad,
+        start = golden("start-35.png"),
+        turned = golden("walk-30.png"),
+        strafed = golden("walk-50.png"),
+    );
+    let manifest_path = dir.join("golden.toml");
+    std::fs::write(&manifest_path, manifest).unwrap();
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_rustydoom"));
+    cmd.arg("test").arg(&manifest_path);
+    if std::env::var_os("RUSTYDOOM_UPDATE_GOLDEN").is_some() {
+        cmd.arg("--update-golden");
+    }
+    let out = cmd.output().unwrap();
+    assert!(
+        out.status.success(),
+        "{}{}",
+        String::from_utf8_lossy(&out.stdout),
+        String::from_utf8_lossy(&out.stderr)
+    );
+}
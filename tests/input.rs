@@ -0,0 +1,75 @@
+// Ticcmds given straight to the engine: held, released back to the
+// controls, and asked of a source each tic.
+use std::sync::{Arc, Mutex};
+
+use rustydoom::bridge::sys;
+use rustydoom::env::{DoomEnv, EnvConfig};
+use rustydoom::input;
+use rustydoom::lmp::Ticcmd;
+use rustydoom::state::{self, Position};
+
+mod common;
+
+fn tick(tics: usize) {
+    for _ in 0..tics {
+        unsafe { sys::raw::dg_tick() };
+    }
+}
+
+fn position() -> Position {
+    state::game_state().player.position
+}
+
+/// The whole hookup in one test: the engine can only boot once a process.
+#[test]
+fn held_and_sourced_ticcmds_move_the_player() {
+    let dir = common::iwad_dir("input");
+    let iwad = dir.join("doom1.wad");
+    let mut env = DoomEnv::new(EnvConfig { iwad: Some(iwad), configdir: Some(dir), ..Default::default() }).unwrap();
+    env.reset("E1M1", 3, 0).unwrap();
+
+    // Held: every tic runs forward, along the way the player faces.
+    let start = state::game_state().player;
+    input::hold(Ticcmd { forwardmove: 0x32, ..Default::default() });
+    tick(10);
+    let moved = position();
+    let facing = start.angle.to_radians();
+    let along = (moved.x - start.position.x) * facing.cos() + (moved.y - start.position.y) * facing.sin();
+    let across = (moved.x - start.position.x) * facing.sin() - (moved.y - start.position.y) * facing.cos();
+    assert!(along > 50.0 && across.abs() < 1.0, "ran {} along and {} across", along, across);
+
+    // Released: nothing is pressed, so the player slides to a stop.
+    input::release();
+    tick(70);
+    let stopped = position();
+    tick(5);
+    assert_eq!(position(), stopped);
+
+    // A source is asked every tic, with the tic and what the controls made
+    // of it (nothing here), and turns the player exactly as much as it says.
+    let asked = Arc::new(Mutex::new(Vec::new()));
+    let log = asked.clone();
+    input::set_source(move |tic, controls| {
+        log.lock().unwrap().push((tic, controls));
+        Some(Ticcmd { angleturn: 0x400, ..Default::default() })
+    });
+    let angle = state::game_state().player.angle;
+    tick(8);
+    let turned = state::game_state().player.angle;
+    assert_eq!((turned - angle).rem_euclid(360.0), 8.0 * 360.0 / 64.0);
+    assert_eq!(position(), stopped);
+    let asked = std::mem::take(&mut *asked.lock().unwrap());
+    assert_eq!(asked.len(), 8);
+    assert!(asked.windows(2).all(|w| w[1].0 == w[0].0 + 1), "tics {:?}", asked);
+    assert!(asked.iter().all(|&(_, controls)| controls == Ticcmd::default()));
+
+    // A source with nothing to say leaves the tic to what is held.
+    input::set_source(|_, _| None);
+    input::hold(Ticcmd { sidemove: 0x28, ..Default::default() });
+    tick(10);
+    assert_ne!(position(), stopped);
+    assert_eq!(state::game_state().player.angle, turned);
+
+    input::clear_source();
+    input::release();
+}
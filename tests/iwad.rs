@@ -0,0 +1,100 @@
+use std::path::{Path, PathBuf};
+
+use rustydoom::iwad::{self, Game};
+use rustydoom::wad::{WadBuilder, WadKind};
+
+/// An IWAD with just these map markers and lumps.
+fn iwad_with(lumps: &[&str]) -> Vec<u8> {
+    lumps.iter().fold(WadBuilder::new(WadKind::Iwad), |w, name| w.marker(name)).build()
+}
+
+fn scratch(name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("iwad").join(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn identify(file_name: &str, lumps: &[&str]) -> Game {
+    iwad::identify_bytes(Path::new(file_name), iwad_with(lumps)).unwrap().game
+}
+
+#[test]
+fn identifies_games_by_their_lumps() {
+    assert_eq!(identify("doom1.wad", &["E1M1"]), Game::DoomShareware);
+    assert_eq!(identify("doom.wad", &["E1M1", "E2M1", "E3M1"]), Game::DoomRegistered);
+    assert_eq!(identify("doom.wad", &["E1M1", "E4M1"]), Game::UltimateDoom);
+    assert_eq!(identify("doom2.wad", &["MAP01"]), Game::Doom2);
+    // Final Doom shares Doom II's layout; the file name tells them apart.
+    assert_eq!(identify("TNT.WAD", &["MAP01"]), Game::Tnt);
+    assert_eq!(identify("plutonia.wad", &["MAP01"]), Game::Plutonia);
+    assert_eq!(identify("freedoom1.wad", &["FREEDOOM", "E1M1"]), Game::Freedoom1);
+    assert_eq!(identify("freedoom2.wad", &["FREEDOOM", "MAP01"]), Game::Freedoom2);
+    assert_eq!(identify("freedm.wad", &["FREEDOOM", "FREEDM", "MAP01"]), Game::FreeDm);
+
+    let unknown = iwad::identify_bytes(Path::new("doom1.wad"), iwad_with(&["E1M1"])).unwrap();
+    assert_eq!(unknown.release, None);
+    assert!(iwad::identify_bytes(Path::new("x.wad"), iwad_with(&["PLAYPAL"])).is_err());
+    let pwad = WadBuilder::new(WadKind::Pwad).marker("E1M1").build();
+    assert!(iwad::identify_bytes(Path::new("x.wad"), pwad).is_err());
+}
+
+#[test]
+fn knows_the_released_iwads_by_md5() {
+    assert_eq!(iwad::known_release("f0cefca49926d00903cf57551d901abe"), Some((Game::DoomShareware, "v1.9")));
+    assert_eq!(iwad::known_release("c4fe9fd920207691a9f5f5d1ffb87d2d"), Some((Game::UltimateDoom, "v1.9")));
+    assert_eq!(iwad::known_release("25e1459ca71d321525f84628f45ca8cd"), Some((Game::Doom2, "v1.9")));
+    assert_eq!(iwad::known_release("1d39e405bf6ee3df69a8d2646c8d5c49"), Some((Game::Tnt, "v1.9 (id Anthology)")));
+    assert_eq!(iwad::known_release("3493be7e1e2588bc9c8b31eab2587a04"), Some((Game::Plutonia, "v1.9 (id Anthology)")));
+    assert_eq!(iwad::known_release("F0CEFCA49926D00903CF57551D901ABE"), None);
+    assert_eq!(iwad::known_release("00000000000000000000000000000000"), None);
+}
+
+// One test, since it sets the environment the search reads.
+#[test]
+fn searches_in_order() {
+    let wadpath = scratch("wadpath");
+    let waddir = scratch("waddir");
+    let data_home = scratch("data_home");
+    let explicit = scratch("explicit");
+    std::fs::create_dir_all(data_home.join("doom")).unwrap();
+    unsafe {
+        std::env::set_var("DOOMWADDIR", &waddir);
+        std::env::set_var("DOOMWADPATH", &wadpath);
+        std::env::set_var("XDG_DATA_HOME", &data_home);
+        std::env::set_var("XDG_DATA_DIRS", scratch("data_dirs"));
+    }
+    let dirs = iwad::search_dirs();
+    assert_eq!(dirs[..4], [waddir.clone(), wadpath.clone(), data_home.join("doom"), data_home.join("games/doom")]);
+    assert_eq!(dirs.last(), Some(&std::env::current_dir().unwrap()));
+
+    // Earlier directories win over preferred names in later ones...
+    std::fs::write(wadpath.join("doom2.wad"), iwad_with(&["MAP01"])).unwrap();
+    std::fs::write(data_home.join("doom/doom1.wad"), iwad_with(&["E1M1"])).unwrap();
+    assert_eq!(iwad::locate(None, None).unwrap().path, wadpath.join("doom2.wad"));
+    // ...and within a directory, the order of IWAD_NAMES, in any case.
+    std::fs::write(waddir.join("DOOM1.WAD"), iwad_with(&["E1M1"])).unwrap();
+    std::fs::write(waddir.join("Plutonia.wad"), iwad_with(&["MAP01"])).unwrap();
+    let found = iwad::locate(None, None).unwrap();
+    assert_eq!((found.path, found.game), (waddir.join("Plutonia.wad"), Game::Plutonia));
+    // Something that isn't an IWAD is passed over.
+    std::fs::write(waddir.join("doom2.wad"), b"not a wad").unwrap();
+    assert_eq!(iwad::locate(None, None).unwrap().path, waddir.join("Plutonia.wad"));
+
+    // --iwad beats the positional one; a directory is searched, a bare
+    // name resolved through the search path.
+    std::fs::write(explicit.join("doom.wad"), iwad_with(&["E1M1", "E4M1"])).unwrap();
+    let given = explicit.join("doom.wad");
+    assert_eq!(iwad::locate(Some(given.to_str().unwrap()), Some("doom2.wad")).unwrap().path, given);
+    assert_eq!(iwad::locate(None, Some(explicit.to_str().unwrap())).unwrap().game, Game::UltimateDoom);
+    assert_eq!(iwad::locate(Some("doom1.wad"), None).unwrap().path, waddir.join("DOOM1.WAD"));
+    assert!(iwad::locate(Some("nothing.wad"), None).is_err());
+
+    std::fs::remove_file(waddir.join("DOOM1.WAD")).unwrap();
+    std::fs::remove_file(waddir.join("Plutonia.wad")).unwrap();
+    std::fs::remove_file(wadpath.join("doom2.wad")).unwrap();
+    std::fs::remove_file(data_home.join("doom/doom1.wad")).unwrap();
+    let err = format!("{:#}", iwad::locate(None, None).unwrap_err());
+    assert!(err.contains("no IWAD found") && err.contains(&waddir.display().to_string()), "{}", err);
+    assert!(err.contains("Rejected candidates"), "{}", err);
+}
@@ -0,0 +1,93 @@
+use rustydoom::lmp::{Demo, End, MARKER, StateHashes, Ticcmd};
+
+fn header(version: u8, players: [u8; 4]) -> Vec<u8> {
+    let mut data = vec![version, 3, 1, 7, 0, 0, 1, 0, 0];
+    data.extend(players);
+    data
+}
+
+#[test]
+fn reads_header_and_tics() {
+    let mut data = header(109, [1, 0, 0, 0]);
+    data.extend([25, 0xf6, 0x02, 1]); // forward, strafe left, turn, fire
+    data.extend([0, 0, 0xfe, 0]);
+    data.push(MARKER);
+
+    let demo = Demo::parse(&data).unwrap();
+    let h = &demo.header;
+    assert_eq!(h.version_name(), "v1.9");
+    assert!(h.playable() && !h.longtics());
+    assert_eq!((h.skill, h.episode, h.map), (3, 1, 7));
+    assert!(h.fast && !h.respawn && !h.nomonsters);
+    assert_eq!(h.player_count(), 1);
+    assert_eq!(h.map_name(Some(true)), "MAP07");
+    assert_eq!(demo.tics.len(), 2);
+    assert_eq!(demo.tics[0], [Ticcmd { forwardmove: 25, sidemove: -10, angleturn: 0x0200, buttons: 1 }]);
+    assert_eq!(demo.tics[1][0].angleturn, -0x0200);
+    assert_eq!(demo.end, End::Marker { trailing: 0 });
+}
+
+#[test]
+fn reads_longtics_and_multiplayer() {
+    let mut data = header(111, [1, 0, 1, 0]);
+    for _ in 0..35 {
+        data.extend([50, 0, 0x34, 0x12, 0]); // player 0
+        data.extend([0, 0, 0xff, 0xff, 2]); // player 2
+    }
+    data.push(MARKER);
+    data.extend(b"footer");
+
+    let demo = Demo::parse(&data).unwrap();
+    assert!(demo.header.longtics());
+    assert_eq!(demo.tics.len(), 35);
+    assert_eq!(demo.tics[0].len(), 2);
+    assert_eq!(demo.tics[0][0].angleturn, 0x1234);
+    assert_eq!(demo.tics[0][1].angleturn, -1);
+    assert_eq!(demo.duration().as_secs(), 1);
+    assert_eq!(demo.end, End::Marker { trailing: 6 });
+}
+
+#[test]
+fn reads_old_demos() {
+    let mut data = vec![2, 1, 3, 1, 0, 0, 0];
+    data.extend([0, 0, 0, 0]);
+    data.push(MARKER);
+    let demo = Demo::parse(&data).unwrap();
+    assert_eq!(demo.header.version, None);
+    assert!(!demo.header.playable());
+    assert_eq!((demo.header.skill, demo.header.map), (2, 3));
+    assert_eq!(demo.tics.len(), 1);
+}
+
+#[test]
+fn notices_missing_marker() {
+    let mut data = header(109, [1, 0, 0, 0]);
+    data.extend([1, 2, 3, 4, 5, 6]);
+    let demo = Demo::parse(&data).unwrap();
+    assert_eq!(demo.tics.len(), 1);
+    assert_eq!(demo.end, End::Missing { partial: 2 });
+}
+
+#[test]
+fn rejects_non_vanilla() {
+    assert!(Demo::parse(&[]).is_err());
+    assert!(Demo::parse(&header(203, [1, 0, 0, 0])).is_err()); // MBF
+    assert!(Demo::parse(&header(109, [0, 0, 0, 0])).is_err());
+    assert!(Demo::parse(&header(109, [1, 0, 0, 0])[..8]).is_err());
+}
+
+#[test]
+fn state_hashes_round_trip_and_compare() {
+    let ours = StateHashes(vec![1, 0xdead_beef, u64::MAX]);
+    let path = std::env::temp_dir().join(format!("rustydoom-hashes-{}.hashes", std::process::id()));
+    ours.write(&path).unwrap();
+    let back = StateHashes::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(back, ours);
+
+    assert_eq!(ours.first_mismatch(&ours), None);
+    assert_eq!(ours.first_mismatch(&StateHashes(vec![1, 2, u64::MAX])), Some(1));
+    assert_eq!(ours.first_mismatch(&StateHashes(vec![1])), Some(1));
+    assert!(StateHashes::parse("2 00ff\n").is_err());
+    assert!(StateHashes::parse("1 zz\n").is_err());
+}
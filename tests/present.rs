@@ -0,0 +1,47 @@
+use std::path::PathBuf;
+
+use rustydoom::config::Scaler;
+use rustydoom::present::{FileSink, Offscreen, Presenter};
+use rustydoom::wad::graphics;
+
+const FRAME: [u32; 4] = [0x00ff_0000, 0x0000_ff00, 0x0000_00ff, 0x00ff_ffff];
+
+#[test]
+fn offscreen_keeps_the_scaled_frame() {
+    let mut out = Offscreen::new(Scaler::Fit);
+    assert_eq!((out.size(), out.frames()), ((0, 0), 0));
+
+    out.present(&FRAME, 2, 2).unwrap();
+    assert_eq!((out.size(), out.pixels()), ((2, 2), &FRAME[..]));
+
+    out.resize(6, 4).unwrap();
+    out.present(&FRAME, 2, 2).unwrap();
+    assert_eq!((out.size(), out.frames()), ((6, 4), 2));
+    assert_eq!(&out.pixels()[..6], &[0, FRAME[0], FRAME[0], FRAME[1], FRAME[1], 0]);
+    assert_eq!(out.image().rgba[4..8], [255, 0, 0, 255]);
+}
+
+#[test]
+fn pairs_present_to_both() {
+    let mut both = (Offscreen::new(Scaler::Stretch), Box::new(Offscreen::new(Scaler::Fit)) as Box<dyn Presenter>);
+    both.resize(4, 1).unwrap();
+    both.present(&FRAME, 2, 2).unwrap();
+    assert_eq!(both.0.pixels(), [FRAME[0], FRAME[0], FRAME[1], FRAME[1]]);
+}
+
+#[test]
+fn file_sink_numbers_pngs() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("present-frames");
+    let _ = std::fs::remove_dir_all(&dir);
+    let mut sink = FileSink::new(&dir).unwrap();
+    sink.resize(640, 480).unwrap();
+    sink.present(&FRAME, 2, 2).unwrap();
+    sink.present(&FRAME[..2], 2, 1).unwrap();
+    assert_eq!(sink.frames(), 2);
+
+    let read = |name: &str| graphics::read_png(std::fs::File::open(dir.join(name)).unwrap()).unwrap();
+    let first = read("frame-000001.png");
+    assert_eq!((first.width, first.height), (2, 2));
+    assert_eq!(first.rgba[8..12], [0, 0, 255, 255]);
+    assert_eq!(read("frame-000002.png").height, 1);
+}
@@ -0,0 +1,130 @@
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+use rustydoom::regression::{CaseResult, DEFAULT_TIMEOUT, Input, Manifest, frame_difference, junit_xml};
+use rustydoom::wad::graphics::{self, Image};
+
+mod common;
+
+const MANIFEST: &str = r#"
+iwad = "doom2.wad"
+skill = 4
+timeout = 90
+
+[[case]]
+name = "map01-exit"
+map = "MAP01"
+script = "map01.rhai"
+exit_by = 2100
+
+[[case]]
+name = "map01-start"
+map = "MAP01"
+skill = 2
+files = []
+[[case.frames]]
+tic = 35
+golden = "golden/map01.png"
+tolerance = 0.5
+
+[[case]]
+name = "demo"
+demo = "demos/run.lmp"
+timeout = 0.5
+"#;
+
+#[test]
+fn parses_a_manifest() {
+    let m = Manifest::parse(MANIFEST, Path::new("ci")).unwrap();
+    let [exit, start, demo] = &m.cases[..] else { panic!("{} cases", m.cases.len()) };
+
+    assert_eq!(exit.iwad.as_deref(), Some(Path::new("ci/doom2.wad").to_str().unwrap()));
+    assert_eq!(exit.skill, 4);
+    assert_eq!(exit.input, Input::Script("ci/map01.rhai".into()));
+    assert_eq!((exit.exit_by, exit.last_tic()), (Some(2100), None));
+
+    assert_eq!((start.skill, start.input.clone()), (2, Input::Idle));
+    assert_eq!(start.frames[0].golden, Path::new("ci/golden/map01.png"));
+    assert_eq!(start.last_tic(), Some(35));
+
+    assert_eq!(demo.input, Input::Demo("ci/demos/run.lmp".into()));
+    assert_eq!((demo.map.as_deref(), demo.last_tic()), (None, None));
+
+    assert_eq!((exit.timeout, demo.timeout), (Duration::from_secs(90), Duration::from_millis(500)));
+    let plain = Manifest::parse("[[case]]\nname = \"a\"\nmap = \"E1M1\"\ntics = 1", Path::new("")).unwrap();
+    assert_eq!(plain.cases[0].timeout, DEFAULT_TIMEOUT);
+}
+
+#[test]
+fn rejects_bad_cases() {
+    let bad = [
+        "[[case]]\nname = \"a\"\nmap = \"E1M1\"\ntics = 1\n[[case]]\nname = \"a\"\nmap = \"E1M1\"\ntics = 1",
+        "[[case]]\nname = \"a\"\nscript = \"a.rhai\"\ndemo = \"a.lmp\"",
+        "[[case]]\nname = \"a\"\nmap = \"E1M1\"\ndemo = \"a.lmp\"",
+        "[[case]]\nname = \"a\"\nmap = \"E1M1\"",
+        "[[case]]\nname = \"a\"\nmap = \"E1M1\"\nscript = \"a.rhai\"\ntics = 10",
+        "[[case]]\nname = \"a\"\nmap = \"E1M1\"\nskill = 6\ntics = 10",
+        "iwad = \"doom.wad\"",
+        "[[case]]\nname = \"a\"\nmap = \"E1M1\"\ntics = 1\ntimeout = 0",
+        "[[case]]\nname = \"a\"\nmap = \"E1M1\"\ntics = 1\ntimeout = -5",
+    ];
+    for text in bad {
+        assert!(Manifest::parse(text, Path::new("")).is_err(), "accepted {:?}", text);
+    }
+}
+
+#[test]
+fn compares_frames_and_reads_pngs() {
+    let a = Image { width: 2, height: 1, left_offset: 0, top_offset: 0, rgba: vec![10, 20, 30, 255, 0, 0, 0, 255] };
+    let mut png = Vec::new();
+    graphics::write_png(&a, &mut png).unwrap();
+    let back = graphics::read_png(&png[..]).unwrap();
+    assert_eq!(back, a);
+    assert_eq!(frame_difference(&a, &back), Some(0.0));
+
+    let b = Image { rgba: vec![10, 20, 30, 0, 6, 6, 6, 255], ..a.clone() };
+    assert_eq!(frame_difference(&a, &b), Some(3.0));
+    assert_eq!(frame_difference(&a, &Image { width: 1, height: 2, ..a.clone() }), None);
+}
+
+#[test]
+fn writes_junit() {
+    let results = [
+        CaseResult { name: "ok".into(), failures: vec![], tics: 35, seconds: 0.5 },
+        CaseResult { name: "E1M1 <exit>".into(), failures: vec!["not finished & \"stuck\"".into()], tics: 70, seconds: 1.0 },
+    ];
+    let xml = junit_xml("maps", &results);
+    assert!(xml.contains("<testsuite name=\"maps\" tests=\"2\" failures=\"1\" time=\"1.500\">"));
+    assert!(xml.contains("<testcase name=\"ok\" classname=\"maps\" time=\"0.500\"/>"));
+    assert!(xml.contains("name=\"E1M1 &lt;exit&gt;\""));
+    assert!(xml.contains("<failure message=\"not finished &amp; &quot;stuck&quot;\">"));
+}
+
+#[test]
+fn hung_cases_time_out() {
+    let dir = common::iwad_dir("regression");
+    std::fs::write(dir.join("hang.rhai"), "loop { tick(); }\n").unwrap();
+    let manifest = dir.join("hang.toml");
+    std::fs::write(
+        &manifest,
+        "iwad = \"doom1.wad\"\ntimeout = 2\n\n[[case]]\nname = \"hang\"\nmap = \"E1M1\"\nscript = \"hang.rhai\"\n\n\
+         [[case]]\nname = \"idle\"\nmap = \"E1M1\"\ntics = 10\n",
+    )
+    .unwrap();
+
+    let junit = dir.join("hang.xml");
+    let out = Command::new(env!("CARGO_BIN_EXE_rustydoom"))
+        .arg("test")
+        .arg(&manifest)
+        .arg("--junit")
+        .arg(&junit)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(!out.status.success(), "{}", stdout);
+    assert!(stdout.contains("FAIL hang: timed out after 2.0s"), "{}", stdout);
+    assert!(stdout.contains("ok   idle"), "{}", stdout);
+    let xml = std::fs::read_to_string(&junit).unwrap();
+    assert!(xml.contains("failures=\"1\"") && xml.contains("<failure message=\"timed out after 2.0s\">"), "{}", xml);
+}
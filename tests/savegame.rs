@@ -0,0 +1,50 @@
+use rustydoom::savegame::{self, SaveHeader};
+
+fn header(description: &str, episode: u8, map: u8, leveltime: u32) -> Vec<u8> {
+    let mut data = vec![0u8; 24];
+    data[..description.len()].copy_from_slice(description.as_bytes());
+    let mut version = b"version 109".to_vec();
+    version.resize(16, 0);
+    data.extend(version);
+    data.extend([2, episode, map, 1, 0, 0, 0]);
+    data.extend(&leveltime.to_be_bytes()[1..]);
+    data.extend([0x1d]); // rest of the save
+    data
+}
+
+#[test]
+fn reads_header() {
+    let h = SaveHeader::parse(&header("hangar run", 1, 3, 35 * 83)).unwrap();
+    assert_eq!(h.description, "hangar run");
+    assert_eq!(h.version, "version 109");
+    assert_eq!((h.skill, h.episode, h.map), (2, 1, 3));
+    assert_eq!(h.players, [true, false, false, false]);
+    assert_eq!(h.time(), "1:23");
+    assert_eq!(h.map_name(Some(false)), "E1M3");
+    assert_eq!(h.map_name(Some(true)), "MAP03");
+}
+
+#[test]
+fn rejects_non_saves() {
+    assert!(SaveHeader::parse(&[0; 10]).is_err());
+    assert!(SaveHeader::parse(&[b'x'; 64]).is_err());
+}
+
+#[test]
+fn lists_slots_in_a_directory() {
+    let dir = std::env::temp_dir().join(format!("rustydoom-saves-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(savegame::slot_path(&dir, 1), header("one", 1, 1, 0)).unwrap();
+    std::fs::write(savegame::slot_path(&dir, 4), b"garbage").unwrap();
+    std::fs::write(savegame::slot_path(&dir, savegame::QUICK_SLOT), header("quick", 1, 1, 0)).unwrap();
+
+    let saves = savegame::list(&dir);
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(saves.len(), 3);
+    assert_eq!(saves[0].0, 1);
+    assert_eq!(saves[0].1.as_ref().unwrap().description, "one");
+    assert_eq!(saves[1].0, 4);
+    assert!(saves[1].1.is_err());
+    // The quicksave is past the menu's slots, not in one of them.
+    assert_eq!(saves[2].0, savegame::QUICK_SLOT);
+}
@@ -0,0 +1,76 @@
+// `rustydoom script` against the generated IWAD: the functions scripts get,
+// and the exit status a failed assert leaves.
+use std::path::Path;
+use std::process::{Command, Output};
+
+mod common;
+
+const PLAY: &str = r#"
+warp("E1M1", 4);
+let s = state();
+assert(s.in_level && s.map == 1 && s.skill == 4, "not on E1M1 at skill 4");
+assert(s.player.health == 100, "started hurt");
+assert(mobjs().some(|m| m.sprite == "MEDI"), "no medikit");
+
+let bad = false;
+try { warp("E9M9"); } catch { bad = true; }
+assert(bad, "warped to a map that isn't there");
+
+let start = state().player.position;
+hold(#{ forward: 1.0 });
+tick(20);
+release();
+tick(35);
+let here = state().player.position;
+assert(here.x != start.x || here.y != start.y, "didn't move");
+
+let saved = save();
+let hash_then = hash();
+hold(#{ strafe: -1.0, turn: 5.0 });
+tick(20);
+release();
+assert(hash() != hash_then, "nothing changed");
+load(saved);
+assert(hash() == hash_then, "load didn't go back");
+assert(state().player.position.x == here.x, "load didn't put the player back");
+
+tick();
+screenshot("shot.png");
+"#;
+
+/// Run `script` in `dir`; also returns whether its settings dir was left behind.
+fn run_script(dir: &Path, script: &str) -> (Output, bool) {
+    std::fs::write(dir.join("test.rhai"), script).unwrap();
+    let child = Command::new(env!("CARGO_BIN_EXE_rustydoom"))
+        .args(["script", "test.rhai", "--iwad", "doom1.wad"])
+        .current_dir(dir)
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    let settings = std::env::temp_dir().join(format!("rustydoom-script-{}", child.id()));
+    let out = child.wait_with_output().unwrap();
+    (out, settings.exists())
+}
+
+#[test]
+fn scripts_play_the_game() {
+    let dir = common::iwad_dir("script/play");
+    let (out, left_behind) = run_script(&dir, PLAY);
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    assert!(std::fs::read(dir.join("shot.png")).unwrap().starts_with(b"\x89PNG"));
+    assert!(!left_behind, "the engine settings dir wasn't removed");
+}
+
+#[test]
+fn failed_asserts_fail_the_run() {
+    let dir = common::iwad_dir("script/fail");
+    let (out, left_behind) = run_script(&dir, "tick(5);\nassert(state().tic < 0, \"on purpose\");\n");
+    assert!(!out.status.success());
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains("assertion failed: on purpose"), "{}", stderr);
+    assert!(!left_behind, "the engine settings dir wasn't removed");
+
+    let (out, _) = run_script(&dir, "tick(;\n");
+    assert!(!out.status.success());
+}
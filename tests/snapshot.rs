@@ -0,0 +1,59 @@
+// Snapshots mid-fight: after restoring one, the game has to play on tic for
+// tic as it did the first time, which the state hashes `--hashes` uses show.
+use std::process::Command;
+
+mod common;
+
+const SCRIPT: &str = r#"
+warp("E1M2", 3);
+hold(#{ attack: true, turn: 0.3 });
+tick(20);
+let here = save();
+let expected = [];
+for i in 0..105 {
+    tick(1);
+    expected.push(hash());
+}
+assert(mobjs().some(|m| m.kind == 1 && m.health < 20), "the zombiemen weren't hurt");
+
+load(here);
+for i in 0..105 {
+    tick(1);
+    assert(hash() == expected[i], "tic " + i + " after restoring differs");
+}
+
+// A damaged snapshot is refused and changes nothing.
+let before = hash();
+let truncated = here;
+truncated.truncate(truncated.len() / 2);
+let flipped = here;
+flipped[flipped.len() - 1] ^= 1;
+for bad in [truncated, flipped, blob(64, 0)] {
+    let refused = false;
+    try { load(bad); } catch { refused = true; }
+    assert(refused, "a damaged snapshot was loaded");
+}
+assert(hash() == before, "a refused snapshot changed the game");
+"#;
+
+#[test]
+fn restores_a_fight_exactly() {
+    let dir = common::iwad_dir("snapshot");
+    let iwad = dir.join("doom1.wad");
+    let script = dir.join("fight.rhai");
+    std::fs::write(&script, SCRIPT).unwrap();
+
+    let out = Command::new(env!("CARGO_BIN_EXE_rustydoom"))
+        .arg("script")
+        .arg(&script)
+        .arg("--iwad")
+        .arg(&iwad)
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "{}{}",
+        String::from_utf8_lossy(&out.stdout),
+        String::from_utf8_lossy(&out.stderr)
+    );
+}
@@ -0,0 +1,56 @@
+use rustydoom::events::Weapon;
+use rustydoom::state::{DgGameState, DgMobj, GameState, Key, Mobj, Position};
+
+#[test]
+fn decodes_the_player() {
+    let mut raw = DgGameState {
+        gametic: 120,
+        episode: 1,
+        map: 3,
+        skill: 3,
+        totalkills: 24,
+        health: 87,
+        ammo: [40, 8, 0, 2],
+        weapon: 2,
+        x: 1056 << 16,
+        y: -3616 << 16,
+        angle: 0x4000_0000,
+        ..Default::default()
+    };
+    raw.weaponowned[..3].fill(1);
+    raw.cards[5] = 1;
+
+    let st = GameState::from_raw(&raw);
+    assert!(st.in_level);
+    assert_eq!((st.tic, st.episode, st.map, st.skill), (120, 1, 3, 4));
+    assert_eq!(st.totals.kills, 24);
+    let p = &st.player;
+    assert_eq!((p.health, p.ammo.bullets, p.ammo.shells, p.ammo.rockets), (87, 40, 8, 2));
+    assert_eq!(p.weapon, Some(Weapon::Shotgun));
+    assert_eq!(p.weapons, [Weapon::Fist, Weapon::Pistol, Weapon::Shotgun]);
+    assert_eq!(p.keys, [Key::RedSkull]);
+    assert_eq!(p.position, Position { x: 1056.0, y: -3616.0, z: 0.0 });
+    assert_eq!(p.angle, 90.0);
+    assert_eq!(st.map_name(), "E1M3");
+    assert!(!st.commercial && !st.title_screen && !st.paused && !st.menu);
+
+    let title = GameState::from_raw(&DgGameState { mission: 1, mode: 2, gamestate: 3, map: 7, menu: 1, ..raw });
+    assert!(title.commercial && title.title_screen && title.menu && !title.in_level);
+    assert_eq!((title.mission, title.map_name()), (1, "MAP07".to_string()));
+}
+
+#[test]
+fn decodes_map_objects() {
+    let mut raw = DgMobj { kind: 1, doomednum: 3004, x: 3 << 15, health: 20, flags: 0x400006, ..Default::default() };
+    for (dst, src) in raw.sprite.iter_mut().zip(b"POSS") {
+        *dst = *src as libc::c_char;
+    }
+    let zombie = Mobj::from_raw(&raw);
+    assert_eq!((zombie.sprite.as_str(), zombie.doomednum), ("POSS", Some(3004)));
+    assert_eq!(zombie.position.x, 1.5);
+    assert!(zombie.is_live_monster() && !zombie.is_item());
+
+    let puff = Mobj::from_raw(&DgMobj { kind: 37, doomednum: -1, ..Default::default() });
+    assert_eq!(puff.doomednum, None);
+    assert!(!puff.is_monster());
+}
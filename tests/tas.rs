@@ -0,0 +1,73 @@
+// A timeline edited tic by tic and exported has to play back as the edit
+// says: the demo is checked with --verifydemo against state hashes from a
+// script holding the same input.
+use std::path::Path;
+use std::process::{Command, Output};
+
+use rustydoom::lmp::{self, StateHashes};
+use rustydoom::timeline::Timeline;
+
+mod common;
+
+const TICS: usize = 70;
+
+/// Idle, then running, turning and firing for tics 10-29.
+const SCRIPT: &str = r#"
+warp("E1M1", 3);
+for i in 0..70 {
+    if i >= 9 && i < 29 {
+        hold(#{ forward: 1.0, turn: 1.40625, attack: true });
+    } else {
+        hold(#{});
+    }
+    tick(1);
+    print(hash());
+}
+"#;
+
+fn rustydoom(dir: &Path, args: &[&str]) -> Output {
+    let out = Command::new(env!("CARGO_BIN_EXE_rustydoom"))
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(out.status.success() || args.contains(&"--verifydemo"), "{}", String::from_utf8_lossy(&out.stderr));
+    out
+}
+
+#[test]
+fn edited_tics_play_back_from_the_exported_demo() {
+    let dir = common::iwad_dir("tas");
+    std::fs::write(dir.join("hashes.rhai"), SCRIPT).unwrap();
+    let mut idle = Timeline::new(3, 1, 1);
+    idle.set(0, &format!("0 0 0 - x{}", TICS)).unwrap();
+    idle.write(&dir.join("run.tas")).unwrap();
+
+    rustydoom(&dir, &["demo", "export", "run.tas", "-o", "idle.lmp"]);
+    rustydoom(&dir, &["demo", "edit", "run.tas", "10", "50 0 256 A x20"]);
+    let edited = Timeline::read(&dir.join("run.tas")).unwrap();
+    assert_eq!(edited.tics.len(), TICS);
+    assert_eq!(edited.tics[8..30].iter().filter(|t| t.forwardmove == 50).count(), 20);
+    rustydoom(&dir, &["demo", "export", "run.tas", "-o", "run.lmp"]);
+
+    let out = rustydoom(&dir, &["script", "hashes.rhai", "--iwad", "doom1.wad"]);
+    let hashes: Vec<u64> = String::from_utf8_lossy(&out.stdout)
+        .lines()
+        .filter_map(|line| u64::from_str_radix(line.trim(), 16).ok().filter(|_| line.trim().len() == 16))
+        .collect();
+    assert_eq!(hashes.len(), TICS);
+    for demo in ["run.lmp", "idle.lmp"] {
+        StateHashes(hashes.clone()).write(&lmp::hashes_path(&dir.join(demo))).unwrap();
+    }
+
+    let verify = |demo| {
+        let args = ["--iwad", "doom1.wad", "--configdir", ".", "--savedir", ".", "--verifydemo", demo];
+        let out = rustydoom(&dir, &args);
+        (out.status.success(), String::from_utf8_lossy(&out.stdout).into_owned() + &String::from_utf8_lossy(&out.stderr))
+    };
+    let (ok, log) = verify("run.lmp");
+    assert!(ok && log.contains("all 70 tics match"), "{}", log);
+    // Without the edit, the game goes its own way from the first edited tic.
+    let (ok, log) = verify("idle.lmp");
+    assert!(!ok && log.contains("desync at tic 10 "), "{}", log);
+}
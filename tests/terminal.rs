@@ -0,0 +1,71 @@
+use rustydoom::config::Scaler;
+use rustydoom::terminal::{Canvas, ColorMode, TermKey, ansi256, parse_keys};
+
+// Red over blue, then two whites.
+const FRAME: [u32; 4] = [0x00ff_0000, 0x00ff_ffff, 0x0000_00ff, 0x00ff_ffff];
+
+#[test]
+fn colour_mode_comes_from_colorterm() {
+    assert_eq!(ColorMode::detect(Some("truecolor")), ColorMode::TrueColor);
+    assert_eq!(ColorMode::detect(Some("24bit")), ColorMode::TrueColor);
+    assert_eq!(ColorMode::detect(Some("")), ColorMode::Ansi256);
+    assert_eq!(ColorMode::detect(None), ColorMode::Ansi256);
+}
+
+#[test]
+fn ansi256_picks_the_cube_or_the_grey_ramp() {
+    assert_eq!(ansi256(0x000000), 16);
+    assert_eq!(ansi256(0xffffff), 231);
+    assert_eq!(ansi256(0xff0000), 196);
+    assert_eq!(ansi256(0x5f87af), 16 + 36 + 2 * 6 + 3);
+    assert_eq!(ansi256(0x808080), 244);
+    assert_eq!(ansi256(0x121212), 233);
+}
+
+#[test]
+fn canvas_sends_only_what_changed() {
+    let mut canvas = Canvas::new(ColorMode::TrueColor, Scaler::Stretch);
+    canvas.resize(2, 1);
+    let first = canvas.draw(&FRAME, 2, 2);
+    assert_eq!(
+        first,
+        "\x1b[0m\x1b[2J\x1b[1;1H\x1b[48;2;0;0;255m\x1b[38;2;255;0;0m▀\x1b[48;2;255;255;255m \x1b[0m"
+    );
+    assert_eq!(canvas.draw(&FRAME, 2, 2), "");
+
+    let mut frame = FRAME;
+    frame[3] = 0x0000_ff00;
+    assert_eq!(
+        canvas.draw(&frame, 2, 2),
+        "\x1b[1;2H\x1b[48;2;0;255;0m\x1b[38;2;255;255;255m▀\x1b[0m"
+    );
+
+    // A resize redraws it all.
+    canvas.resize(2, 1);
+    assert!(canvas.draw(&frame, 2, 2).starts_with("\x1b[0m\x1b[2J"));
+}
+
+#[test]
+fn canvas_in_256_colours() {
+    let mut canvas = Canvas::new(ColorMode::Ansi256, Scaler::Stretch);
+    canvas.resize(1, 1);
+    assert_eq!(canvas.draw(&[0xff0000, 0x000000], 1, 2), "\x1b[0m\x1b[2J\x1b[1;1H\x1b[48;5;16m\x1b[38;5;196m▀\x1b[0m");
+    // Too small a change to show in 256 colours is no change at all.
+    assert_eq!(canvas.draw(&[0xfe0101, 0x010101], 1, 2), "");
+}
+
+#[test]
+fn keys_from_raw_input() {
+    use TermKey::*;
+    assert_eq!(
+        parse_keys(b"w1 \r\x7f\t\x03\x0c"),
+        [Char(b'w'), Char(b'1'), Char(b' '), Enter, Backspace, Tab, Interrupt, Redraw]
+    );
+    assert_eq!(parse_keys(b"\x1b[A\x1b[B\x1bOC\x1b[1;5D"), [Up, Down, Right, Left]);
+    assert_eq!(parse_keys(b"\x1bOP\x1b[15~\x1b[24~\x1b[2~\x1b[6~"), [F(1), F(5), F(12), Insert, PageDown]);
+    // Escape on its own, at the end or before another key.
+    assert_eq!(parse_keys(b"\x1b"), [Escape]);
+    assert_eq!(parse_keys(b"\x1bq\x1b\x1b[C"), [Escape, Char(b'q'), Escape, Right]);
+    // Unknown sequences and non-ASCII are dropped.
+    assert_eq!(parse_keys(b"\x1b[99~a\xc3\xa9"), [Char(b'a')]);
+}
@@ -0,0 +1,79 @@
+use rustydoom::lmp::{Demo, Ticcmd};
+use rustydoom::timeline::{MAX_TICS, Timeline};
+
+const TEXT: &str = "# rustydoom TAS timeline
+skill 4
+episode 1
+map 3
+# forward side turn buttons [xN]
+50 0 0 - x3
+50 -24 300 A
+0 0 0 U3
+0 0 0 $c0
+";
+
+#[test]
+fn reads_and_writes_text() {
+    let t = Timeline::parse(TEXT).unwrap();
+    assert_eq!((t.skill, t.episode, t.map, t.longtics), (4, 1, 3, false));
+    assert_eq!(t.tics.len(), 6);
+    assert_eq!(t.tics[2], Ticcmd { forwardmove: 50, ..Default::default() });
+    assert_eq!(t.tics[3], Ticcmd { forwardmove: 50, sidemove: -24, angleturn: 300, buttons: Ticcmd::ATTACK });
+    assert_eq!(t.tics[4].buttons, Ticcmd::USE | Ticcmd::CHANGE | (2 << Ticcmd::WEAPON_SHIFT));
+    assert_eq!(t.tics[5].buttons, 0xc0);
+    assert_eq!(t.to_string(), TEXT);
+
+    assert!(Timeline::parse("skill 3\nepisode 1\n").is_err());
+    assert!(Timeline::parse("skill 6\nepisode 1\nmap 1\n").is_err());
+    let bad = Timeline::parse("skill 3\nepisode 1\nmap 1\n50 0 0 X\n").unwrap_err();
+    assert!(format!("{:#}", bad).contains("line 4"));
+}
+
+#[test]
+fn exports_vanilla_demos() {
+    let t = Timeline::parse(TEXT).unwrap();
+    // Turns round to the byte a v1.9 demo keeps, as the engine is given them.
+    assert_eq!(t.engine_cmd(3).unwrap().angleturn, 256);
+    assert_eq!(t.engine_cmd(6), None);
+
+    let demo = Demo::parse(&t.to_demo().to_bytes()).unwrap();
+    assert_eq!((demo.header.skill, demo.header.episode, demo.header.map), (3, 1, 3));
+    assert!(!demo.header.longtics());
+    let back = Timeline::from_demo(&demo).unwrap();
+    let lowres: Vec<_> = (0..t.tics.len()).map(|i| t.engine_cmd(i).unwrap()).collect();
+    assert_eq!(back.tics, lowres);
+
+    let long = Timeline { longtics: true, ..t };
+    let back = Timeline::from_demo(&Demo::parse(&long.to_demo().to_bytes()).unwrap()).unwrap();
+    assert_eq!(back, long);
+
+    // Recorded as player 2, it would come back as player 1's run.
+    let mut second = long.to_demo();
+    second.header.players = [false, true, false, false];
+    second.header.consoleplayer = 1;
+    assert!(Timeline::from_demo(&second).is_err());
+}
+
+#[test]
+fn rewrites_single_tics() {
+    let mut t = Timeline::parse(TEXT).unwrap();
+    let rest = t.tics[4..].to_vec();
+    assert_eq!(t.set(3, "0 24 -512 A").unwrap(), 1);
+    assert_eq!(t.tics[3], Ticcmd { forwardmove: 0, sidemove: 24, angleturn: -512, buttons: Ticcmd::ATTACK });
+    assert_eq!(t.tics[4..], rest);
+
+    // Past the end, the gap is tics with no input.
+    assert_eq!(t.set(8, "50 0 0 - x2").unwrap(), 2);
+    assert_eq!(t.tics.len(), 10);
+    assert_eq!(t.tics[6..8], [Ticcmd::default(); 2]);
+    assert_eq!(t.tics[9].forwardmove, 50);
+    assert!(t.set(0, "50 0 0").is_err());
+
+    // Repeats are capped, not allocated however large.
+    assert!(t.set(0, "0 0 0 - x99999999999").is_err());
+    assert!(t.set(usize::MAX, "0 0 0 -").is_err());
+    assert!(t.set(MAX_TICS - 1, "0 0 0 - x2").is_err());
+    assert_eq!(t.tics.len(), 10);
+    let day = format!("skill 3\nepisode 1\nmap 1\n0 0 0 - x{}\n0 0 0 -\n", MAX_TICS);
+    assert!(format!("{:#}", Timeline::parse(&day).unwrap_err()).contains("line 5"));
+}
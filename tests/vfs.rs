@@ -0,0 +1,100 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use rustydoom::bridge::vfs;
+use rustydoom::iwad::Game;
+use rustydoom::wad::{WadBuilder, WadKind};
+use zip::write::SimpleFileOptions;
+
+fn scratch(name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("vfs").join(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// A PWAD holding one lump, so reads can tell WADs apart.
+fn pwad(lump: &str) -> Vec<u8> {
+    WadBuilder::new(WadKind::Pwad).lump(lump, lump.as_bytes()).build()
+}
+
+fn first_lump(path: &str) -> String {
+    vfs::read_wad(path).unwrap().lumps()[0].name.clone()
+}
+
+fn write_zip(path: &Path, entries: &[(&str, Vec<u8>)]) {
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(path).unwrap());
+    for (name, data) in entries {
+        zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+        zip.write_all(data).unwrap();
+    }
+    zip.finish().unwrap();
+}
+
+fn path_str(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+#[test]
+fn reads_loose_wads_from_a_directory() {
+    let dir = scratch("loose");
+    std::fs::write(dir.join("a.wad"), pwad("ALPHA")).unwrap();
+    std::fs::write(dir.join("b.wad"), pwad("BRAVO")).unwrap();
+    let files = [path_str(&dir.join("b.wad")), path_str(&dir.join("a.wad"))];
+    // Plain files pass through untouched and in order.
+    assert_eq!(vfs::expand_files(&files).unwrap(), files);
+    assert_eq!(first_lump(&files[0]), "BRAVO");
+    assert_eq!(first_lump(&files[1]), "ALPHA");
+    assert!(vfs::read_wad(&path_str(&dir.join("missing.wad"))).is_err());
+}
+
+#[test]
+fn mounts_shadow_the_disk() {
+    let dir = scratch("shadow");
+    let path = path_str(&dir.join("shadowed.wad"));
+    std::fs::write(&path, pwad("ONDISK")).unwrap();
+    assert_eq!(first_lump(&path), "ONDISK");
+    vfs::mount(path.clone(), pwad("MOUNTED"));
+    assert_eq!(first_lump(&path), "MOUNTED");
+    // Re-mounting replaces what was there.
+    vfs::mount(path.clone(), pwad("AGAIN"));
+    assert_eq!(first_lump(&path), "AGAIN");
+    // A mount needs nothing on disk at all.
+    vfs::mount("nowhere/virtual.wad", pwad("VIRTUAL"));
+    assert_eq!(first_lump("nowhere/virtual.wad"), "VIRTUAL");
+}
+
+#[test]
+fn mounts_the_wads_inside_archives() {
+    let dir = scratch("zip");
+    let archive = dir.join("mod.pk3");
+    write_zip(&archive, &[
+        ("maps/b.wad", pwad("BRAVO")),
+        ("readme.txt", b"not a wad".to_vec()),
+        ("A.WAD", pwad("ALPHA")),
+    ]);
+    std::fs::write(dir.join("loose.wad"), pwad("LOOSE")).unwrap();
+
+    // Archives expand in place into their WADs, in archive order.
+    let files = vfs::expand_files(&[path_str(&dir.join("loose.wad")), path_str(&archive)]).unwrap();
+    let b = format!("{}/maps/b.wad", archive.display());
+    let a = format!("{}/A.WAD", archive.display());
+    assert_eq!(files, [path_str(&dir.join("loose.wad")), b.clone(), a.clone()]);
+    assert_eq!(first_lump(&b), "BRAVO");
+    assert_eq!(first_lump(&a), "ALPHA");
+
+    let empty = dir.join("empty.zip");
+    write_zip(&empty, &[("readme.txt", b"no wads".to_vec())]);
+    assert!(vfs::mount_archive(&empty).is_err());
+    assert!(vfs::is_archive(Path::new("x.ZIP")) && !vfs::is_archive(Path::new("x.wad")));
+
+    // As the IWAD, the first entry that is one is taken.
+    let iwads = dir.join("iwads.zip");
+    write_zip(&iwads, &[
+        ("patch.wad", pwad("PATCH")),
+        ("doom2.wad", WadBuilder::new(WadKind::Iwad).marker("MAP01").build()),
+    ]);
+    let found = vfs::mount_archive_iwad(&iwads).unwrap();
+    assert_eq!(found.game, Game::Doom2);
+    assert_eq!(found.path, Path::new(&format!("{}/doom2.wad", iwads.display())));
+}
@@ -0,0 +1,154 @@
+use rustydoom::wad::{Namespace, Wad, WadBuilder, WadKind};
+
+fn sample() -> Vec<u8> {
+    WadBuilder::new(WadKind::Pwad)
+        .lump("PLAYPAL", vec![1u8; 768])
+        .marker("E1M1")
+        .lump("THINGS", vec![0u8; 10])
+        .lump("LINEDEFS", vec![0u8; 14])
+        .marker("S_START")
+        .lump("TROOA1", vec![2u8; 32])
+        .marker("S_END")
+        .marker("FF_START")
+        .marker("F1_START")
+        .lump("FLOOR0_1", vec![3u8; 4096])
+        .marker("F1_END")
+        .marker("FF_END")
+        .lump("playpal", vec![9u8; 768])
+        .build()
+}
+
+#[test]
+fn parses_builder_output() {
+    let wad = Wad::from_bytes(sample()).unwrap();
+    assert_eq!(wad.kind(), WadKind::Pwad);
+    assert_eq!(wad.lumps().len(), 13);
+    assert_eq!(wad.lumps()[0].name, "PLAYPAL");
+    assert_eq!(wad.lump(0).unwrap(), &[1u8; 768][..]);
+    assert_eq!(wad.lump(3).unwrap().len(), 14);
+    assert!(wad.lump(99).is_none());
+}
+
+#[test]
+fn later_lumps_override_earlier_ones() {
+    let wad = Wad::from_bytes(sample()).unwrap();
+    assert_eq!(wad.find("PLAYPAL"), Some(12));
+    assert_eq!(wad.lump_by_name("playpal").unwrap()[0], 9);
+    assert!(!wad.contains("COLORMAP"));
+}
+
+#[test]
+fn resolves_namespaces() {
+    let wad = Wad::from_bytes(sample()).unwrap();
+    let names = |ns| -> Vec<String> {
+        wad.namespace(ns).into_iter().map(|i| wad.lumps()[i].name.clone()).collect()
+    };
+    assert_eq!(names(Namespace::Sprites), ["TROOA1"]);
+    assert_eq!(names(Namespace::Flats), ["FLOOR0_1"]);
+    assert!(names(Namespace::Patches).is_empty());
+    assert!(Namespace::Flats.is_marker("F2_END"));
+    assert!(!Namespace::Flats.is_marker("FLOOR0_1"));
+}
+
+#[test]
+fn finds_maps() {
+    let wad = Wad::from_bytes(sample()).unwrap();
+    assert_eq!(wad.maps(), ["E1M1"]);
+}
+
+#[test]
+fn rejects_bad_headers() {
+    assert!(Wad::from_bytes(Vec::new()).is_err());
+    assert!(Wad::from_bytes(b"ZWAD\0\0\0\0\x0c\0\0\0".to_vec()).is_err());
+    // Negative lump count.
+    assert!(Wad::from_bytes(b"IWAD\xff\xff\xff\xff\x0c\0\0\0".to_vec()).is_err());
+    // Directory past EOF.
+    assert!(Wad::from_bytes(b"IWAD\x01\0\0\0\x0c\0\0\0".to_vec()).is_err());
+    // Empty but valid.
+    assert!(Wad::from_bytes(b"PWAD\0\0\0\0\x0c\0\0\0".to_vec()).is_ok());
+}
+
+#[test]
+fn rejects_lump_past_eof() {
+    let mut bytes = WadBuilder::new(WadKind::Iwad).lump("DATA", vec![0u8; 8]).build();
+    let dir = bytes.len() - 16;
+    bytes[dir + 4..dir + 8].copy_from_slice(&1000i32.to_le_bytes());
+    let err = Wad::from_bytes(bytes).unwrap_err();
+    assert!(err.to_string().contains("DATA"), "{err}");
+}
+
+#[test]
+fn tolerates_junk_offsets_on_markers() {
+    let mut bytes = WadBuilder::new(WadKind::Pwad).marker("F_START").marker("F_END").build();
+    let dir = bytes.len() - 32;
+    bytes[dir..dir + 4].copy_from_slice(&i32::MAX.to_le_bytes());
+    let wad = Wad::from_bytes(bytes).unwrap();
+    assert_eq!(wad.lump(0).unwrap(), &[] as &[u8]);
+}
+
+/// Tiny deterministic PRNG so the fuzz cases are reproducible without extra crates.
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+/// Whatever the parser accepts must be safe to walk in full.
+fn exercise(bytes: Vec<u8>) {
+    if let Ok(wad) = Wad::from_bytes(bytes) {
+        for i in 0..wad.lumps().len() {
+            let lump = wad.lump(i).unwrap();
+            assert_eq!(lump.len(), wad.lumps()[i].size);
+        }
+        for ns in [Namespace::Sprites, Namespace::Flats, Namespace::Patches] {
+            for i in wad.namespace(ns) {
+                assert!(i < wad.lumps().len());
+            }
+        }
+        let _ = wad.maps();
+    }
+}
+
+#[test]
+fn fuzz_mutated_wads() {
+    let base = sample();
+    let mut rng = XorShift(0x9E37_79B9_7F4A_7C15);
+    for _ in 0..5000 {
+        let mut bytes = base.clone();
+        for _ in 0..1 + rng.below(8) {
+            let at = rng.below(bytes.len());
+            bytes[at] = rng.next() as u8;
+        }
+        // Sometimes truncate or aim straight at the header/directory.
+        match rng.below(4) {
+            0 => bytes.truncate(rng.below(base.len())),
+            1 => {
+                let at = 4 + rng.below(8);
+                bytes[at] = rng.next() as u8;
+            }
+            _ => {}
+        }
+        exercise(bytes);
+    }
+}
+
+#[test]
+fn fuzz_random_bytes() {
+    let mut rng = XorShift(0xDEAD_BEEF_CAFE_F00D);
+    for _ in 0..2000 {
+        let len = rng.below(256);
+        let mut bytes: Vec<u8> = (0..len).map(|_| rng.next() as u8).collect();
+        if len >= 4 && rng.below(2) == 0 {
+            bytes[..4].copy_from_slice(b"PWAD");
+        }
+        exercise(bytes);
+    }
+}
@@ -0,0 +1,111 @@
+use rustydoom::wad::graphics::{self, Palette};
+use rustydoom::wad::{LumpType, Wad, WadBuilder, WadKind, mus, sound};
+
+fn grey_palette() -> Palette {
+    let lump: Vec<u8> = (0..=255u8).flat_map(|i| [i, i, i]).collect();
+    Palette::from_playpal(&lump).unwrap()
+}
+
+/// A 2x3 patch: column 0 fully drawn, column 1 only its bottom pixel.
+fn small_patch() -> Vec<u8> {
+    let mut p = Vec::new();
+    p.extend_from_slice(&2u16.to_le_bytes());
+    p.extend_from_slice(&3u16.to_le_bytes());
+    p.extend_from_slice(&[0; 4]);
+    p.extend_from_slice(&16u32.to_le_bytes());
+    p.extend_from_slice(&24u32.to_le_bytes());
+    p.extend_from_slice(&[0, 3, 0, 10, 20, 30, 0, 0xFF]); // column 0
+    p.extend_from_slice(&[2, 1, 0, 40, 0, 0xFF]);         // column 1
+    p
+}
+
+#[test]
+fn decodes_patch_with_transparency() {
+    let img = graphics::decode_patch(&small_patch(), &grey_palette()).unwrap();
+    assert_eq!((img.width, img.height), (2, 3));
+    let px = |x: usize, y: usize| &img.rgba[(y * 2 + x) * 4..(y * 2 + x) * 4 + 4];
+    assert_eq!(px(0, 1), [20, 20, 20, 255]);
+    assert_eq!(px(1, 0)[3], 0);
+    assert_eq!(px(1, 2), [40, 40, 40, 255]);
+
+    let mut png = Vec::new();
+    graphics::write_png(&img, &mut png).unwrap();
+    assert!(png.starts_with(b"\x89PNG"));
+}
+
+#[test]
+fn rejects_truncated_patch() {
+    let mut p = small_patch();
+    p.truncate(20);
+    assert!(graphics::decode_patch(&p, &grey_palette()).is_err());
+}
+
+#[test]
+fn converts_dmx_to_wav() {
+    let mut lump = Vec::new();
+    lump.extend_from_slice(&3u16.to_le_bytes());
+    lump.extend_from_slice(&11025u16.to_le_bytes());
+    lump.extend_from_slice(&40u32.to_le_bytes());
+    lump.extend((0..40).map(|i| i as u8));
+    let snd = sound::decode_dmx(&lump).unwrap();
+    assert_eq!(snd.sample_rate, 11025);
+    assert_eq!(snd.samples, (16..24).collect::<Vec<u8>>());
+
+    let mut wav = Vec::new();
+    sound::write_wav(&snd, &mut wav).unwrap();
+    assert_eq!(&wav[0..4], b"RIFF");
+    assert_eq!(wav.len(), 44 + 8);
+}
+
+#[test]
+fn converts_mus_to_midi() {
+    // Play note 60 at volume 100 on channel 0, wait 10 tics, release it,
+    // select bank 2, end.
+    let score = [0x90, 0x80 | 60, 100, 10, 0x00, 60, 0x40, 1, 2, 0x60];
+    let mut lump = b"MUS\x1a".to_vec();
+    for v in [score.len() as u16, 16, 1, 0, 0, 0] {
+        lump.extend_from_slice(&v.to_le_bytes());
+    }
+    lump.extend_from_slice(&score);
+
+    let mid = mus::mus_to_midi(&lump).unwrap();
+    assert_eq!(&mid[0..4], b"MThd");
+    let track = &mid[22..];
+    let events = &track[7..];
+    assert_eq!(events, [0x00, 0x90, 60, 100, 10, 0x80, 60, 0x40, 0x00, 0xB0, 32, 2, 0x00, 0xFF, 0x2F, 0x00]);
+
+    // Two of the longest delays on events that write nothing don't fit a
+    // MIDI delta time between them.
+    let long = [0xD0, 0xFF, 0xFF, 0xFF, 0x7F];
+    let score: Vec<u8> = long.iter().chain(&long).chain(&[0x60]).copied().collect();
+    let mut lump = b"MUS\x1a".to_vec();
+    for v in [score.len() as u16, 16, 1, 0, 0, 0] {
+        lump.extend_from_slice(&v.to_le_bytes());
+    }
+    lump.extend_from_slice(&score);
+    assert!(mus::mus_to_midi(&lump).is_err());
+}
+
+#[test]
+fn detects_lump_types() {
+    let mut dmx = vec![3, 0, 0x11, 0x2B, 0, 0, 0, 0];
+    dmx.extend_from_slice(&[0x80; 4]);
+    let bytes = WadBuilder::new(WadKind::Pwad)
+        .marker("MAP01")
+        .lump("THINGS", vec![0; 10])
+        .lump("REJECT", Vec::new())
+        .marker("FF_START")
+        .lump("SLIME01", vec![0; 4096])
+        .marker("FF_END")
+        .lump("DSPISTOL", dmx)
+        .lump("TITLEPIC", small_patch())
+        .lump("D_RUNNIN", b"MUS\x1a".to_vec())
+        .lump("DEMO1", vec![109])
+        .build();
+    let wad = Wad::from_bytes(bytes).unwrap();
+    use LumpType::*;
+    assert_eq!(
+        wad.lump_types(),
+        [Map, MapData, MapData, Marker, Flat, Marker, Sound, Patch, Music, Demo]
+    );
+}
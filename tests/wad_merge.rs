@@ -0,0 +1,117 @@
+use rustydoom::wad::merge::{merge_deutex, merge_nwt};
+use rustydoom::wad::{Namespace, Wad, WadBuilder, WadKind};
+
+fn iwad() -> Wad {
+    let bytes = WadBuilder::new(WadKind::Iwad)
+        .lump("PLAYPAL", vec![0u8; 768])
+        .marker("S_START")
+        .lump("TROOA1", vec![1])
+        .lump("TROOA2A8", vec![2])
+        .lump("TROOB1", vec![3])
+        .lump("POSSA1", vec![4])
+        .marker("S_END")
+        .marker("F_START")
+        .marker("F1_START")
+        .lump("FLOOR0_1", vec![5])
+        .lump("NUKAGE1", vec![6])
+        .marker("F1_END")
+        .marker("F_END")
+        .build();
+    Wad::from_bytes(bytes).unwrap()
+}
+
+fn pwad(sprites: &[(&str, u8)], flats: &[(&str, u8)]) -> Wad {
+    let mut b = WadBuilder::new(WadKind::Pwad).marker("MAP01").lump("THINGS", vec![0u8; 10]);
+    b = b.marker("SS_START");
+    for (n, v) in sprites {
+        b = b.lump(n, vec![*v]);
+    }
+    b = b.marker("SS_END").marker("FF_START");
+    for (n, v) in flats {
+        b = b.lump(n, vec![*v]);
+    }
+    Wad::from_bytes(b.marker("FF_END").build()).unwrap()
+}
+
+fn names(wad: &Wad, ns: Namespace) -> Vec<(String, u8)> {
+    wad.namespace(ns)
+        .into_iter()
+        .map(|i| (wad.lumps()[i].name.clone(), wad.lump(i).unwrap()[0]))
+        .collect()
+}
+
+fn owned(v: &[(&str, u8)]) -> Vec<(String, u8)> {
+    v.iter().map(|(n, b)| (n.to_string(), *b)).collect()
+}
+
+#[test]
+fn deutex_replaces_flats_and_adds_new_ones() {
+    let merged = Wad::from_bytes(merge_deutex(&iwad(), &pwad(&[], &[("NUKAGE1", 60), ("SLIME01", 70)]))).unwrap();
+    assert_eq!(merged.kind(), WadKind::Iwad);
+    assert_eq!(
+        names(&merged, Namespace::Flats),
+        owned(&[("FLOOR0_1", 5), ("NUKAGE1", 60), ("SLIME01", 70)])
+    );
+    // The inner F1_ markers survive and there is exactly one flat section.
+    assert!(merged.contains("F1_START"));
+    assert_eq!(merged.lumps().iter().filter(|l| l.name == "F_START").count(), 1);
+}
+
+#[test]
+fn deutex_drops_only_fully_replaced_sprites() {
+    // TROOA2 takes rotation 2 from TROOA2A8, which still draws rotation 8.
+    let merged = Wad::from_bytes(merge_deutex(&iwad(), &pwad(&[("TROOA1", 10), ("TROOA2", 11)], &[]))).unwrap();
+    assert_eq!(
+        names(&merged, Namespace::Sprites),
+        owned(&[("TROOA2A8", 2), ("TROOB1", 3), ("POSSA1", 4), ("TROOA1", 10), ("TROOA2", 11)])
+    );
+
+    // Rotation 0 covers every angle of frame A, so both A lumps go.
+    let merged = Wad::from_bytes(merge_deutex(&iwad(), &pwad(&[("TROOA0", 12)], &[]))).unwrap();
+    assert_eq!(
+        names(&merged, Namespace::Sprites),
+        owned(&[("TROOB1", 3), ("POSSA1", 4), ("TROOA0", 12)])
+    );
+
+    // A stray SS_END ends the sprites early for the directory, leaving
+    // SARGA1 unclaimed inside the S_START/S_END the merge follows; it's kept.
+    let odd = WadBuilder::new(WadKind::Iwad)
+        .marker("S_START")
+        .lump("TROOA1", vec![1])
+        .marker("SS_END")
+        .lump("SARGA1", vec![2])
+        .marker("S_END")
+        .build();
+    let merged = merge_deutex(&Wad::from_bytes(odd).unwrap(), &pwad(&[("TROOA1", 10)], &[]));
+    let merged = Wad::from_bytes(merged).unwrap();
+    assert!(merged.lumps().iter().any(|l| l.name == "SARGA1"));
+}
+
+#[test]
+fn merges_append_the_rest_of_the_pwad() {
+    let p = pwad(&[("TROOA1", 10)], &[("SLIME01", 70)]);
+    for merged in [merge_deutex(&iwad(), &p), merge_nwt(&iwad(), &p)] {
+        let merged = Wad::from_bytes(merged).unwrap();
+        assert_eq!(merged.maps(), ["MAP01"]);
+        assert!(!merged.contains("SS_START"));
+        assert!(!merged.contains("FF_END"));
+    }
+}
+
+#[test]
+fn nwt_replaces_in_place() {
+    let merged = Wad::from_bytes(merge_nwt(&iwad(), &pwad(&[("TROOA1", 10), ("BOSSA1", 13)], &[("FLOOR0_1", 50)]))).unwrap();
+    assert_eq!(
+        names(&merged, Namespace::Sprites),
+        owned(&[("TROOA1", 10), ("TROOA2A8", 2), ("TROOB1", 3), ("POSSA1", 4), ("BOSSA1", 13)])
+    );
+    assert_eq!(names(&merged, Namespace::Flats), owned(&[("FLOOR0_1", 50), ("NUKAGE1", 6)]));
+}
+
+#[test]
+fn merge_creates_missing_sections() {
+    let bare = Wad::from_bytes(WadBuilder::new(WadKind::Iwad).lump("PLAYPAL", vec![0u8; 768]).build()).unwrap();
+    let merged = Wad::from_bytes(merge_deutex(&bare, &pwad(&[("TROOA1", 10)], &[("SLIME01", 70)]))).unwrap();
+    assert_eq!(names(&merged, Namespace::Sprites), owned(&[("TROOA1", 10)]));
+    assert_eq!(names(&merged, Namespace::Flats), owned(&[("SLIME01", 70)]));
+}